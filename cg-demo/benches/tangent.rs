@@ -0,0 +1,56 @@
+use cg_demo::mesh::compute_tangent_bitangent;
+use cgmath::{vec2, vec3, Vector2, Vector3};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Positions and texture coordinates of a triangle
+type Triangle = ([Vector3<f32>; 3], [Vector2<f32>; 3]);
+
+/// Generates the triangles of a regular grid with the given amount of cells per side
+fn grid_triangles(cells: usize) -> Vec<Triangle> {
+    let step = 1.0 / cells as f32;
+    let mut triangles = Vec::with_capacity(cells * cells * 2);
+
+    for x in 0..cells {
+        for y in 0..cells {
+            let (u, v) = (x as f32 * step, y as f32 * step);
+            let p = [
+                vec3(u, v, 0.0),
+                vec3(u, v + step, 0.0),
+                vec3(u + step, v + step, 0.0),
+                vec3(u + step, v, 0.0),
+            ];
+            let uv = [
+                vec2(u, v),
+                vec2(u, v + step),
+                vec2(u + step, v + step),
+                vec2(u + step, v),
+            ];
+            triangles.push(([p[0], p[1], p[2]], [uv[0], uv[1], uv[2]]));
+            triangles.push(([p[0], p[2], p[3]], [uv[0], uv[2], uv[3]]));
+        }
+    }
+
+    triangles
+}
+
+fn bench_tangents(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compute_tangent_bitangent");
+    group.sample_size(10);
+
+    for cells in [64, 256, 1024] {
+        let triangles = grid_triangles(cells);
+        group.throughput(Throughput::Elements(triangles.len() as u64));
+        group.bench_with_input(BenchmarkId::new("grid", format!("{}x{}", cells, cells)), &triangles, |b, triangles| {
+            b.iter(|| {
+                for (p, uv) in triangles {
+                    black_box(compute_tangent_bitangent(black_box(*p), black_box(*uv)));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_tangents);
+criterion_main!(benches);
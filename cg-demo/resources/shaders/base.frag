@@ -11,21 +11,49 @@ in VS_OUT {
     vec3 tangentFragPos;
 } fs_in;
 
-uniform sampler2D baseMap;
-uniform sampler2D normalMap;
 uniform sampler2D heightMap;
+uniform sampler2D detailMap;
+uniform sampler2D emissiveMap;
 
-uniform vec3 lightPos;
-uniform vec3 viewPos;
+uniform bool detailEnabled;
+uniform float detailScale;
+
+uniform float shininess;
+uniform float ambientStrength;
+// Light back faces with the flipped normal, for rendering without backface culling
+uniform bool doubleSided;
+uniform float emissiveStrength;
+
+// Uniforms shared by all programs, updated once per render pass
+layout (std140) uniform PerFrame {
+    mat4 projection;
+    mat4 view;
+    vec3 viewPos;
+    vec3 lightPos;
+};
+
+uniform vec3 lightColor;
+
+#include "include/material.glsl"
+#include "include/lighting.glsl"
+#include "include/override_color.glsl"
 
 void main() {
     vec3 normal = fs_in.fragNormal;
+    if (doubleSided && !gl_FrontFacing) {
+        // Back faces are lit from the other side of the surface
+        normal = -normal;
+    }
 
     // Base color
-    vec3 color = texture(baseMap, fs_in.texCoords).rgb;
+    vec3 color = sampleBaseColor(fs_in.texCoords);
+    if (detailEnabled) {
+        // Blend the tiled detail map multiplicatively; a value of 0.5 leaves the color unchanged
+        color *= 2.0 * texture(detailMap, fs_in.texCoords * detailScale).rgb;
+    }
 
     // Ambient light
-    vec3 ambient = 0.1 * color;
+    vec3 ambient = ambientStrength * color;
 
     // Diffuse light
     vec3 lightDir = normalize(fs_in.tangentLightPos - fs_in.tangentFragPos);
@@ -36,8 +64,18 @@ void main() {
     vec3 viewDir = normalize(fs_in.tangentViewPos - fs_in.tangentFragPos);
     vec3 reflectDir = reflect(-lightDir, normal);
     vec3 halfwayDir = normalize(lightDir + viewDir);
-    float spec = pow(max(dot(normal, halfwayDir), 0.0), 32.0);
+    float spec = pow(max(dot(normal, halfwayDir), 0.0), shininess);
     vec3 specular = vec3(0.2) * spec;
 
-    Color = vec4(ambient + diffuse + specular, 1.0);
+    // Emitted light
+    vec3 emissive = emissiveStrength * texture(emissiveMap, fs_in.texCoords).rgb;
+
+    // Shadowed fragments only receive the ambient light
+    float shadow = calcShadow(fs_in.fragPos);
+
+    vec3 litColor = (ambient + (1.0 - shadow) * (diffuse + specular)) * lightColor + emissive;
+
+    // Blend towards the fog color with the distance to the camera
+    float fog = calcFogFactor(distance(viewPos, fs_in.fragPos));
+    Color = applyOverrideColor(vec4(mix(fogColor, litColor, fog), 1.0));
 }
\ No newline at end of file
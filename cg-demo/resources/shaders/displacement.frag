@@ -11,34 +11,87 @@ in VS_OUT {
     vec3 tangentFragPos;
 } fs_in;
 
-uniform sampler2D baseMap;
-uniform sampler2D normalMap;
 uniform sampler2D heightMap;
+uniform sampler2D detailMap;
+uniform sampler2D emissiveMap;
 
-uniform vec3 lightPos;
-uniform vec3 viewPos;
+uniform bool detailEnabled;
+uniform float detailScale;
+
+uniform float shininess;
+uniform float ambientStrength;
+// Light back faces with the flipped normal, for rendering without backface culling
+uniform bool doubleSided;
+uniform float emissiveStrength;
+
+uniform bool worldSpaceNormals;
+uniform bool directXNormals;
+// Factor for the slope of the normal map; 0.0 is flat, 1.0 as authored and above 1.0 exaggerated
+uniform float normalStrength;
+
+// Uniforms shared by all programs, updated once per render pass
+layout (std140) uniform PerFrame {
+    mat4 projection;
+    mat4 view;
+    vec3 viewPos;
+    vec3 lightPos;
+};
+
+uniform vec3 lightColor;
+
+#include "include/material.glsl"
+#include "include/lighting.glsl"
+#include "include/override_color.glsl"
 
 void main() {
-    vec3 normal = texture(normalMap, fs_in.texCoords).rgb;
-    normal = normalize(normal * 2.0 - 1.0);
+    vec3 normal = sampleNormalMap(fs_in.texCoords);
+    vec3 color = sampleBaseColor(fs_in.texCoords);
+    if (directXNormals) {
+        // DirectX-style normal maps store the green channel (bitangent direction) inverted
+        normal.g = 1.0 - normal.g;
+    }
+    normal = normal * 2.0 - 1.0;
+    normal = normalize(vec3(normal.xy * normalStrength, normal.z));
+    if (doubleSided && !gl_FrontFacing) {
+        // Back faces are lit from the other side of the surface
+        normal = -normal;
+    }
 
-    // Base color
-    vec3 color = texture(baseMap, fs_in.texCoords).rgb;
+    if (detailEnabled) {
+        // Blend the tiled detail map multiplicatively; a value of 0.5 leaves the color unchanged
+        color *= 2.0 * texture(detailMap, fs_in.texCoords * detailScale).rgb;
+    }
 
     // Ambient light
-    vec3 ambient = 0.1 * color;
+    vec3 ambient = ambientStrength * color;
 
-    // Diffuse light
+    // Light and view direction in the space of the normal map
     vec3 lightDir = normalize(fs_in.tangentLightPos - fs_in.tangentFragPos);
+    vec3 viewDir = normalize(fs_in.tangentViewPos - fs_in.tangentFragPos);
+    if (worldSpaceNormals) {
+        lightDir = normalize(lightPos - fs_in.fragPos);
+        viewDir = normalize(viewPos - fs_in.fragPos);
+    }
+
+    // Diffuse light
     float diff = max(dot(lightDir, normal), 0.0);
     vec3 diffuse = diff * color;
 
     // Specular light
-    vec3 viewDir = normalize(fs_in.tangentViewPos - fs_in.tangentFragPos);
     vec3 reflectDir = reflect(-lightDir, normal);
     vec3 halfwayDir = normalize(lightDir + viewDir);
-    float spec = pow(max(dot(normal, halfwayDir), 0.0), 32.0);
+    float spec = pow(max(dot(normal, halfwayDir), 0.0), shininess);
     vec3 specular = vec3(0.2) * spec;
 
-    Color = vec4(ambient + diffuse + specular, 1.0);
+    // Emitted light
+    vec3 emissive = emissiveStrength * texture(emissiveMap, fs_in.texCoords).rgb;
+
+    // Shadowed fragments only receive the ambient light
+    float shadow = calcShadow(fs_in.fragPos);
+
+    vec3 litColor = (ambient + (1.0 - shadow) * (diffuse + specular)) * lightColor + emissive;
+
+    // Blend towards the fog color with the distance to the camera
+    float fog = calcFogFactor(distance(viewPos, fs_in.fragPos));
+    Color = applyOverrideColor(vec4(mix(fogColor, litColor, fog), 1.0));
 }
\ No newline at end of file
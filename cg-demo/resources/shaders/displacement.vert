@@ -3,8 +3,9 @@
 layout (location = 0) in vec3  inPos;
 layout (location = 1) in vec3  inNormal;
 layout (location = 2) in vec2  inTexCoords;
-layout (location = 3) in vec3  inTangent;
+layout (location = 3) in vec4  inTangent;
 layout (location = 4) in vec3  inBitangent;
+layout (location = 5) in mat4  inInstanceModel;
 
 out VS_OUT {
     vec3 fragPos;
@@ -15,36 +16,42 @@ out VS_OUT {
     vec3 tangentFragPos;
 } vs_out;
 
-uniform sampler2D heightMap;
+// Uniforms shared by all programs, updated once per render pass
+layout (std140) uniform PerFrame {
+    mat4 projection;
+    mat4 view;
+    vec3 viewPos;
+    vec3 lightPos;
+};
 
-uniform mat4 projection;
-uniform mat4 view;
 uniform mat4 model;
 
-uniform vec3 lightPos;
-uniform vec3 viewPos;
+uniform float tiling;
 
-const float heightScale = 0.1;
+#include "include/height.glsl"
 
 void main() {
-    vs_out.texCoords = inTexCoords;
+    mat4 instanceModel = model * inInstanceModel;
 
-    float height =  texture(heightMap, vs_out.texCoords).r;
+    vs_out.texCoords = inTexCoords * tiling;
+
+    float height = displacementHeight(vs_out.texCoords);
     vec3 displaced_vertex = inPos + heightScale * height * inNormal;
 
-    vs_out.fragPos = vec3(model * vec4(displaced_vertex, 1.0));
-    vs_out.fragNormal = vec3(model * vec4(inNormal, 1.0));
+    vs_out.fragPos = vec3(instanceModel * vec4(displaced_vertex, 1.0));
+    vs_out.fragNormal = vec3(instanceModel * vec4(inNormal, 1.0));
 
-    mat3 normalMatrix = transpose(inverse(mat3(model)));
-    vec3 adaptedTangent = normalize(normalMatrix * inTangent);
+    mat3 normalMatrix = transpose(inverse(mat3(instanceModel)));
+    vec3 adaptedTangent = normalize(normalMatrix * inTangent.xyz);
     vec3 adaptedNormal  = normalize(normalMatrix * inNormal);
     adaptedTangent = normalize(adaptedTangent - dot(adaptedTangent, adaptedNormal) *  adaptedNormal);
-    vec3 adaptedBitangent = cross(adaptedTangent, adaptedNormal);
+    // The handedness in w flips the bitangent where the texture coordinates are mirrored
+    vec3 adaptedBitangent = inTangent.w * cross(adaptedTangent, adaptedNormal);
 
     mat3 TBN = transpose(mat3(adaptedTangent, adaptedBitangent, adaptedNormal));
     vs_out.tangentLightPos = TBN * lightPos;
     vs_out.tangentViewPos = TBN * viewPos;
     vs_out.tangentFragPos = TBN * vs_out.fragPos;
 
-    gl_Position = projection * view * model * vec4(displaced_vertex, 1.0);
+    gl_Position = projection * view * instanceModel * vec4(displaced_vertex, 1.0);
 }
@@ -0,0 +1,56 @@
+#version 330 core
+
+out vec4 Color;
+
+in VS_OUT {
+    vec3 fragPos;
+    vec2 texCoords;
+    vec3 smoothNormal;
+    flat vec3 flatNormal;
+} fs_in;
+
+uniform sampler2D baseMap;
+
+uniform bool flatShading;
+uniform float shininess;
+uniform float ambientStrength;
+// Light back faces with the flipped normal, for rendering without backface culling
+uniform bool doubleSided;
+
+// Uniforms shared by all programs, updated once per render pass
+layout (std140) uniform PerFrame {
+    mat4 projection;
+    mat4 view;
+    vec3 viewPos;
+    vec3 lightPos;
+};
+
+uniform vec3 lightColor;
+
+#include "include/override_color.glsl"
+
+void main() {
+    // Lighting in world space with the surface normals of the mesh (the maps are not used)
+    vec3 normal = normalize(flatShading ? fs_in.flatNormal : fs_in.smoothNormal);
+    if (doubleSided && !gl_FrontFacing) {
+        // Back faces are lit from the other side of the surface
+        normal = -normal;
+    }
+    vec3 color = texture(baseMap, fs_in.texCoords).rgb;
+
+    // Ambient light
+    vec3 ambient = ambientStrength * color;
+
+    // Diffuse light
+    vec3 lightDir = normalize(lightPos - fs_in.fragPos);
+    float diff = max(dot(lightDir, normal), 0.0);
+    vec3 diffuse = diff * color;
+
+    // Specular light
+    vec3 viewDir = normalize(viewPos - fs_in.fragPos);
+    vec3 halfwayDir = normalize(lightDir + viewDir);
+    float spec = pow(max(dot(normal, halfwayDir), 0.0), shininess);
+    vec3 specular = vec3(0.2) * spec;
+
+    Color = applyOverrideColor(vec4((ambient + diffuse + specular) * lightColor, 1.0));
+}
@@ -0,0 +1,40 @@
+#version 330 core
+
+layout (location = 0) in vec3  inPos;
+layout (location = 1) in vec3  inNormal;
+layout (location = 2) in vec2  inTexCoords;
+layout (location = 3) in vec4  inTangent;
+layout (location = 4) in vec3  inBitangent;
+layout (location = 5) in mat4  inInstanceModel;
+
+out VS_OUT {
+    vec3 fragPos;
+    vec2 texCoords;
+    vec3 smoothNormal;
+    // Not interpolated: every fragment of a triangle gets the normal of its provoking vertex
+    flat vec3 flatNormal;
+} vs_out;
+
+// Uniforms shared by all programs, updated once per render pass
+layout (std140) uniform PerFrame {
+    mat4 projection;
+    mat4 view;
+    vec3 viewPos;
+    vec3 lightPos;
+};
+
+uniform mat4 model;
+
+uniform float tiling;
+
+void main() {
+    mat4 instanceModel = model * inInstanceModel;
+    mat3 normalMatrix = transpose(inverse(mat3(instanceModel)));
+
+    vs_out.fragPos = vec3(instanceModel * vec4(inPos, 1.0));
+    vs_out.texCoords = inTexCoords * tiling;
+    vs_out.smoothNormal = normalMatrix * inNormal;
+    vs_out.flatNormal = vs_out.smoothNormal;
+
+    gl_Position = projection * view * instanceModel * vec4(inPos, 1.0);
+}
@@ -0,0 +1,33 @@
+// Height of the displaced surface, shared by all programs that displace the vertices by the height map.
+// Every pass (including the shadow pass) displaces the vertices with it, so the shadows match the blended surface.
+
+uniform sampler2D heightMap;
+uniform bool smoothHeight;
+
+#include "material_id.glsl"
+
+uniform sampler2D secondaryHeightMap;
+
+const float heightScale = 0.1;
+
+// Samples the height map either filtered by the texture settings or with the nearest texel, independent of the texture filter
+float sampleHeight(sampler2D map, vec2 texCoords)
+{
+    if (smoothHeight) {
+        return texture(map, texCoords).r;
+    }
+    ivec2 size = textureSize(map, 0);
+    ivec2 texel = clamp(ivec2(texCoords * vec2(size)), ivec2(0), size - 1);
+    return texelFetch(map, texel, 0).r;
+}
+
+// Height at the texture coordinates; gray values of the material-ID map blend the heights of both materials
+float displacementHeight(vec2 texCoords)
+{
+    float height = sampleHeight(heightMap, texCoords);
+    float blend = materialBlend(texCoords);
+    if (blend > 0.0) {
+        height = mix(height, sampleHeight(secondaryHeightMap, texCoords), blend);
+    }
+    return height;
+}
@@ -0,0 +1,38 @@
+// Shadow and fog calculations shared by the lit fragment shaders
+
+uniform sampler2D shadowMap;
+uniform bool shadowsEnabled;
+uniform mat4 lightSpace;
+uniform float shadowBias;
+
+// Returns 1.0 if the fragment is hidden from the light by a closer surface in the shadow map, 0.0 otherwise
+float calcShadow(vec3 fragPos) {
+    if (!shadowsEnabled) {
+        return 0.0;
+    }
+    vec4 lightSpacePos = lightSpace * vec4(fragPos, 1.0);
+    vec3 projCoords = lightSpacePos.xyz / lightSpacePos.w * 0.5 + 0.5;
+    // Fragments behind the far plane of the light are never shadowed
+    if (projCoords.z > 1.0) {
+        return 0.0;
+    }
+    // The bias prevents the surface from shadowing itself ("shadow acne")
+    float closestDepth = texture(shadowMap, projCoords.xy).r;
+    return projCoords.z - shadowBias > closestDepth ? 1.0 : 0.0;
+}
+
+uniform int fogMode;
+uniform vec3 fogColor;
+uniform float fogDensity;
+uniform float fogStart;
+uniform float fogEnd;
+
+// Returns the fraction of the surface color that is visible through the fog (0 = off, 1 = linear, 2 = exponential)
+float calcFogFactor(float distance) {
+    if (fogMode == 1) {
+        return clamp((fogEnd - distance) / (fogEnd - fogStart), 0.0, 1.0);
+    } else if (fogMode == 2) {
+        return exp(-fogDensity * distance);
+    }
+    return 1.0;
+}
@@ -0,0 +1,32 @@
+// Color and normal map of the surface, shared by all programs that shade it with the texture maps.
+// The maps of the second material are blended in like the height in include/height.glsl, so the shading matches the displacement.
+
+#include "material_id.glsl"
+
+uniform sampler2D baseMap;
+uniform sampler2D normalMap;
+uniform sampler2D secondaryBaseMap;
+uniform sampler2D secondaryNormalMap;
+
+// Base color at the texture coordinates
+vec3 sampleBaseColor(vec2 texCoords)
+{
+    vec3 color = texture(baseMap, texCoords).rgb;
+    float blend = materialBlend(texCoords);
+    if (blend > 0.0) {
+        // The secondary maps are only sampled while they are bound
+        color = mix(color, texture(secondaryBaseMap, texCoords).rgb, blend);
+    }
+    return color;
+}
+
+// Value of the normal map at the texture coordinates, still encoded in the range [0, 1]
+vec3 sampleNormalMap(vec2 texCoords)
+{
+    vec3 normal = texture(normalMap, texCoords).rgb;
+    float blend = materialBlend(texCoords);
+    if (blend > 0.0) {
+        normal = mix(normal, texture(secondaryNormalMap, texCoords).rgb, blend);
+    }
+    return normal;
+}
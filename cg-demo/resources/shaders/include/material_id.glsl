@@ -0,0 +1,14 @@
+// Second material, selected by the material-ID map (0 = first material, 1 = second material).
+// Shared by the displacement (include/height.glsl) and the shading (include/material.glsl), so both blend alike.
+
+uniform bool multiMaterial;
+uniform sampler2D materialIdMap;
+
+// Share of the second material at the texture coordinates; gray values blend both materials
+float materialBlend(vec2 texCoords)
+{
+    if (!multiMaterial) {
+        return 0.0;
+    }
+    return texture(materialIdMap, texCoords).r;
+}
@@ -0,0 +1,10 @@
+// Single color replacing the shaded color, used to draw the wireframe overlay with the program of the mesh
+// (so the lines follow the tessellation and displacement of the program)
+
+uniform bool overrideColorEnabled;
+uniform vec3 overrideColor;
+
+// Returns the override color while it is enabled, otherwise the given color
+vec4 applyOverrideColor(vec4 color) {
+    return overrideColorEnabled ? vec4(overrideColor, 1.0) : color;
+}
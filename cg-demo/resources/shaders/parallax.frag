@@ -11,19 +11,100 @@ in VS_OUT {
     vec3 tangentFragPos;
 } fs_in;
 
-uniform sampler2D baseMap;
-uniform sampler2D normalMap;
 uniform sampler2D heightMap;
+uniform sampler2D detailMap;
+uniform sampler2D emissiveMap;
 
-uniform vec3 lightPos;
-uniform vec3 viewPos;
+uniform bool detailEnabled;
+uniform float detailScale;
+
+uniform float tiling;
+
+uniform float shininess;
+uniform float ambientStrength;
+// Light back faces with the flipped normal, for rendering without backface culling
+uniform bool doubleSided;
+uniform float emissiveStrength;
+
+uniform bool worldSpaceNormals;
+uniform bool directXNormals;
+// Factor for the slope of the normal map; 0.0 is flat, 1.0 as authored and above 1.0 exaggerated
+uniform float normalStrength;
+uniform bool smoothHeight;
+// Amount of layers when looking along the surface; looking straight at it uses minParallaxLayers instead
+uniform int parallaxLayers;
+uniform int minParallaxLayers;
+// Refine the intersection of the view ray with the surface between the last two layers by a binary search
+uniform bool parallaxRefinement;
+
+// Uniforms shared by all programs, updated once per render pass
+layout (std140) uniform PerFrame {
+    mat4 projection;
+    mat4 view;
+    vec3 viewPos;
+    vec3 lightPos;
+};
+
+uniform vec3 lightColor;
+
+#include "include/material.glsl"
+#include "include/lighting.glsl"
+#include "include/override_color.glsl"
 
 const float heightScale = 0.1;
+const int refinementSteps = 5;
+
+// Samples the height map either filtered by the texture settings or with the nearest texel, independent of the texture filter
+float sampleHeight(vec2 texCoords)
+{
+    if (smoothHeight) {
+        return texture(heightMap, texCoords).r;
+    }
+    ivec2 size = textureSize(heightMap, 0);
+    ivec2 texel = clamp(ivec2(texCoords * vec2(size)), ivec2(0), size - 1);
+    return texelFetch(heightMap, texel, 0).r;
+}
 
 vec2 ParallaxMapping(vec2 texCoords, vec3 viewDir)
 {
-    float height =  texture(heightMap, texCoords).r;
-    return texCoords - viewDir.xy * (height * heightScale);
+    if (parallaxLayers <= 1) {
+        float height = sampleHeight(texCoords);
+        return texCoords - viewDir.xy * (height * heightScale);
+    }
+
+    // Steep parallax mapping: step along the view ray layer by layer until it reaches the surface.
+    // Flat view angles cover a longer distance on the texture, so they use more layers.
+    int minLayers = clamp(minParallaxLayers, 1, parallaxLayers);
+    int layers = int(mix(float(parallaxLayers), float(minLayers), abs(viewDir.z)) + 0.5);
+    float layerDepth = 1.0 / float(layers);
+    vec2 deltaTexCoords = viewDir.xy / viewDir.z * heightScale * layerDepth;
+
+    float currentLayerDepth = 0.0;
+    vec2 currentTexCoords = texCoords;
+    float currentDepth = sampleHeight(currentTexCoords);
+    for (int i = 0; i < layers && currentLayerDepth < currentDepth; i++) {
+        currentTexCoords -= deltaTexCoords;
+        currentDepth = sampleHeight(currentTexCoords);
+        currentLayerDepth += layerDepth;
+    }
+    if (!parallaxRefinement) {
+        return currentTexCoords;
+    }
+
+    // Binary search between the last layer above and the first layer below the surface
+    for (int i = 0; i < refinementSteps; i++) {
+        deltaTexCoords *= 0.5;
+        layerDepth *= 0.5;
+        if (currentLayerDepth < currentDepth) {
+            currentTexCoords -= deltaTexCoords;
+            currentLayerDepth += layerDepth;
+        } else {
+            currentTexCoords += deltaTexCoords;
+            currentLayerDepth -= layerDepth;
+        }
+        currentDepth = sampleHeight(currentTexCoords);
+    }
+    return currentTexCoords;
 }
 
 void main() {
@@ -32,28 +113,57 @@ void main() {
     vec2 texCoords = fs_in.texCoords;
 
     texCoords = ParallaxMapping(fs_in.texCoords, viewDir);
-    if (texCoords.x > 1.0 || texCoords.y > 1.0 || texCoords.x < 0.0 || texCoords.y < 0.0)
+    if (texCoords.x > tiling || texCoords.y > tiling || texCoords.x < 0.0 || texCoords.y < 0.0)
     discard;
 
-    vec3 normal = texture(normalMap, texCoords).rgb;
-    normal = normalize(normal * 2.0 - 1.0);
+    vec3 normal = sampleNormalMap(texCoords);
+    if (directXNormals) {
+        // DirectX-style normal maps store the green channel (bitangent direction) inverted
+        normal.g = 1.0 - normal.g;
+    }
+    normal = normal * 2.0 - 1.0;
+    normal = normalize(vec3(normal.xy * normalStrength, normal.z));
+    if (doubleSided && !gl_FrontFacing) {
+        // Back faces are lit from the other side of the surface
+        normal = -normal;
+    }
 
     // Base color
-    vec3 color = texture(baseMap, texCoords).rgb;
+    vec3 color = sampleBaseColor(texCoords);
+    if (detailEnabled) {
+        // Blend the tiled detail map multiplicatively; a value of 0.5 leaves the color unchanged
+        color *= 2.0 * texture(detailMap, texCoords * detailScale).rgb;
+    }
 
     // Ambient light
-    vec3 ambient = 0.1 * color;
+    vec3 ambient = ambientStrength * color;
 
-    // Diffuse light
+    // Light and view direction in the space of the normal map
     vec3 lightDir = normalize(fs_in.tangentLightPos - fs_in.tangentFragPos);
+    if (worldSpaceNormals) {
+        lightDir = normalize(lightPos - fs_in.fragPos);
+        viewDir = normalize(viewPos - fs_in.fragPos);
+    }
+
+    // Diffuse light
     float diff = max(dot(lightDir, normal), 0.0);
     vec3 diffuse = diff * color;
 
     // Specular light
     vec3 reflectDir = reflect(-lightDir, normal);
     vec3 halfwayDir = normalize(lightDir + viewDir);
-    float spec = pow(max(dot(normal, halfwayDir), 0.0), 32.0);
+    float spec = pow(max(dot(normal, halfwayDir), 0.0), shininess);
     vec3 specular = vec3(0.2) * spec;
 
-    Color = vec4(ambient + diffuse + specular, 1.0);
+    // Emitted light
+    vec3 emissive = emissiveStrength * texture(emissiveMap, texCoords).rgb;
+
+    // Shadowed fragments only receive the ambient light
+    float shadow = calcShadow(fs_in.fragPos);
+
+    vec3 litColor = (ambient + (1.0 - shadow) * (diffuse + specular)) * lightColor + emissive;
+
+    // Blend towards the fog color with the distance to the camera
+    float fog = calcFogFactor(distance(viewPos, fs_in.fragPos));
+    Color = applyOverrideColor(vec4(mix(fogColor, litColor, fog), 1.0));
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+#version 330 core
+
+out vec4 Color;
+
+in VS_OUT {
+    vec3 tangent;
+    vec3 bitangent;
+    vec3 normal;
+} fs_in;
+
+// Vector of the tangent space to show: 0 = tangent, 1 = bitangent, 2 = normal
+uniform int tangentSpaceVector;
+
+#include "include/override_color.glsl"
+
+void main() {
+    vec3 vector = fs_in.tangent;
+    if (tangentSpaceVector == 1) {
+        vector = fs_in.bitangent;
+    } else if (tangentSpaceVector == 2) {
+        vector = fs_in.normal;
+    }
+
+    // Map the directions from [-1, 1] to the color range [0, 1]
+    Color = applyOverrideColor(vec4(normalize(vector) * 0.5 + 0.5, 1.0));
+}
@@ -0,0 +1,36 @@
+#version 330 core
+
+layout (location = 0) in vec3  inPos;
+layout (location = 1) in vec3  inNormal;
+layout (location = 2) in vec2  inTexCoords;
+layout (location = 3) in vec4  inTangent;
+layout (location = 4) in vec3  inBitangent;
+layout (location = 5) in mat4  inInstanceModel;
+
+out VS_OUT {
+    vec3 tangent;
+    vec3 bitangent;
+    vec3 normal;
+} vs_out;
+
+// Uniforms shared by all programs, updated once per render pass
+layout (std140) uniform PerFrame {
+    mat4 projection;
+    mat4 view;
+    vec3 viewPos;
+    vec3 lightPos;
+};
+
+uniform mat4 model;
+
+void main() {
+    mat4 instanceModel = model * inInstanceModel;
+
+    // The vectors are only transformed, not orthogonalized, so errors of the generated tangent space stay visible
+    mat3 normalMatrix = transpose(inverse(mat3(instanceModel)));
+    vs_out.tangent = mat3(instanceModel) * inTangent.xyz;
+    vs_out.bitangent = mat3(instanceModel) * inBitangent;
+    vs_out.normal = normalMatrix * inNormal;
+
+    gl_Position = projection * view * instanceModel * vec4(inPos, 1.0);
+}
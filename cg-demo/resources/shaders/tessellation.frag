@@ -0,0 +1,2 @@
+// The tessellated surface is shaded exactly like the normal mapped one
+#include "normal.frag"
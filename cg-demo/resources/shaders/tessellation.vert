@@ -0,0 +1,32 @@
+#version 410 core
+
+layout (location = 0) in vec3  inPos;
+layout (location = 1) in vec3  inNormal;
+layout (location = 2) in vec2  inTexCoords;
+layout (location = 3) in vec4  inTangent;
+layout (location = 4) in vec3  inBitangent;
+layout (location = 5) in mat4  inInstanceModel;
+
+out VS_OUT {
+    vec3 pos;
+    vec3 normal;
+    vec2 texCoords;
+    vec3 tangent;
+    float handedness;
+    mat4 instanceModel;
+} vs_out;
+
+uniform mat4 model;
+
+uniform float tiling;
+
+void main() {
+    // The vertices are only passed on; they are displaced after the tessellation
+    vs_out.pos = inPos;
+    vs_out.normal = inNormal;
+    vs_out.texCoords = inTexCoords * tiling;
+    vs_out.tangent = inTangent.xyz;
+    // The handedness in w flips the bitangent where the texture coordinates are mirrored
+    vs_out.handedness = inTangent.w;
+    vs_out.instanceModel = model * inInstanceModel;
+}
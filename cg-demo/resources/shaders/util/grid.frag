@@ -0,0 +1,30 @@
+#version 330 core
+
+out vec4 Color;
+
+in vec3 worldPos;
+
+uniform vec3 viewPos;
+uniform vec3 gridColor;
+// Distance between two lines in world units
+uniform float cellSize;
+// Distance to the camera at which the grid has faded out completely
+uniform float fadeDistance;
+
+void main() {
+    vec2 coord = worldPos.xz / cellSize;
+    vec2 derivative = fwidth(coord);
+    // Distance to the nearest line in pixels, so the lines are about one pixel wide at any distance
+    vec2 lineDistance = abs(fract(coord - 0.5) - 0.5) / derivative;
+    float line = 1.0 - min(min(lineDistance.x, lineDistance.y), 1.0);
+
+    // Fade out with the distance and where the cells get smaller than a few pixels, which would cause aliasing
+    float fade = 1.0 - smoothstep(0.0, fadeDistance, distance(viewPos, worldPos));
+    fade *= 1.0 - smoothstep(0.2, 0.5, max(derivative.x, derivative.y));
+
+    float alpha = line * fade;
+    if (alpha <= 0.0) {
+        discard;
+    }
+    Color = vec4(gridColor, alpha);
+}
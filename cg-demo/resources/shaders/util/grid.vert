@@ -0,0 +1,14 @@
+#version 330 core
+
+layout (location = 0) in vec3 inPos;
+
+out vec3 worldPos;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform mat4 model;
+
+void main() {
+    worldPos = vec3(model * vec4(inPos, 1.0));
+    gl_Position = projection * view * vec4(worldPos, 1.0);
+}
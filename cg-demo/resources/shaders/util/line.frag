@@ -0,0 +1,9 @@
+#version 330 core
+
+out vec4 Color;
+
+in vec3 lineColor;
+
+void main() {
+    Color = vec4(lineColor, 1.0);
+}
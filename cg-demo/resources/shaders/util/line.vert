@@ -0,0 +1,15 @@
+#version 330 core
+
+layout (location = 0) in vec3  inPos;
+layout (location = 1) in vec3  inColor;
+
+out vec3 lineColor;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform mat4 model;
+
+void main() {
+    lineColor = inColor;
+    gl_Position = projection * view * model * vec4(inPos, 1.0);
+}
@@ -0,0 +1,17 @@
+#version 330 core
+
+out vec4 Color;
+
+in vec2 texCoords;
+
+uniform sampler2D map;
+// Show the red channel as grayscale (for single-channel maps like height maps)
+uniform bool singleChannel;
+
+void main() {
+    vec3 color = texture(map, texCoords).rgb;
+    if (singleChannel) {
+        color = vec3(color.r);
+    }
+    Color = vec4(color, 1.0);
+}
@@ -0,0 +1,11 @@
+#version 330 core
+
+out vec4 Color;
+
+in vec3 texDir;
+
+uniform samplerCube skybox;
+
+void main() {
+    Color = vec4(texture(skybox, texDir).rgb, 1.0);
+}
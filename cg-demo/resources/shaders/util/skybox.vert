@@ -0,0 +1,16 @@
+#version 330 core
+
+layout (location = 0) in vec3 inPos;
+
+out vec3 texDir;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+void main() {
+    texDir = inPos;
+    // Remove the translation, so the cube moves with the camera
+    vec4 pos = projection * mat4(mat3(view)) * vec4(inPos, 1.0);
+    // Depth of 1.0 after the perspective division places the cube on the far plane
+    gl_Position = pos.xyww;
+}
@@ -0,0 +1,13 @@
+#version 330 core
+
+in vec2 texCoords;
+in vec4 color;
+
+out vec4 Color;
+
+// Coverage of the glyphs in the red channel
+uniform sampler2D font;
+
+void main() {
+    Color = vec4(color.rgb, color.a * texture(font, texCoords).r);
+}
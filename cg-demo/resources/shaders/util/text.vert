@@ -0,0 +1,14 @@
+#version 330 core
+
+layout (location = 0) in vec2  inPos;
+layout (location = 1) in vec2  inTexCoords;
+layout (location = 2) in vec4  inColor;
+
+out vec2 texCoords;
+out vec4 color;
+
+void main() {
+    texCoords = inTexCoords;
+    color = inColor;
+    gl_Position = vec4(inPos, 0.0, 1.0);
+}
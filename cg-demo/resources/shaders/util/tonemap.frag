@@ -0,0 +1,35 @@
+#version 330 core
+
+out vec4 Color;
+
+in vec2 texCoords;
+
+// Linear HDR colors of the rendered frame
+uniform sampler2D hdrBuffer;
+uniform float exposure;
+// Operator mapping the colors to [0, 1]: 0 = Reinhard, 1 = ACES (filmic approximation by Krzysztof Narkowicz)
+uniform int tonemapOperator;
+
+vec3 reinhard(vec3 color) {
+    return color / (color + vec3(1.0));
+}
+
+vec3 aces(vec3 color) {
+    const float a = 2.51;
+    const float b = 0.03;
+    const float c = 2.43;
+    const float d = 0.59;
+    const float e = 0.14;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), 0.0, 1.0);
+}
+
+void main() {
+    vec3 color = texture(hdrBuffer, texCoords).rgb * exposure;
+    // The result stays linear, it is converted to sRGB when written to the window
+    if (tonemapOperator == 1) {
+        color = aces(color);
+    } else {
+        color = reinhard(color);
+    }
+    Color = vec4(color, 1.0);
+}
@@ -0,0 +1,14 @@
+#version 330 core
+
+out vec4 Color;
+
+in VS_OUT {
+    vec3 color;
+} fs_in;
+
+#include "include/override_color.glsl"
+
+void main() {
+    // Without generated vertex colors, the attribute is white
+    Color = applyOverrideColor(vec4(fs_in.color, 1.0));
+}
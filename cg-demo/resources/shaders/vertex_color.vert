@@ -0,0 +1,28 @@
+#version 330 core
+
+layout (location = 0) in vec3  inPos;
+layout (location = 1) in vec3  inNormal;
+layout (location = 2) in vec2  inTexCoords;
+layout (location = 3) in vec4  inTangent;
+layout (location = 4) in vec3  inBitangent;
+layout (location = 5) in mat4  inInstanceModel;
+layout (location = 9) in vec3  inColor;
+
+out VS_OUT {
+    vec3 color;
+} vs_out;
+
+// Uniforms shared by all programs, updated once per render pass
+layout (std140) uniform PerFrame {
+    mat4 projection;
+    mat4 view;
+    vec3 viewPos;
+    vec3 lightPos;
+};
+
+uniform mat4 model;
+
+void main() {
+    vs_out.color = inColor;
+    gl_Position = projection * view * model * inInstanceModel * vec4(inPos, 1.0);
+}
@@ -0,0 +1,239 @@
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+
+use cg_demo::mesh::VertexAttribute;
+
+use crate::glhelper::CameraPose;
+use crate::Resources;
+
+const LOG_TARGET: &str = "Config";
+
+const DEFAULT_WINDOW_TITLE: &str = "Displacement Map Demo";
+const DEFAULT_WINDOW_ICON: &str = "icon.png";
+
+/// # Config
+/// User settings loaded from a TOML file in the resources.
+/// Settings that are missing from the file keep their default value.
+/// The current state of the demo can be exported in the same format (see [`Config::to_toml`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Title of the window
+    pub window_title: String,
+    /// Resource name of the image used as window icon; a missing image keeps the default icon
+    pub window_icon: String,
+    /// Invert the vertical mouse axis when looking around
+    pub invert_y: bool,
+    /// Fraction of the mouse movement applied per frame; values below 1.0 smooth the camera rotation
+    pub mouse_smoothing: f32,
+    /// Mouse movements of a frame up to this length in pixels are ignored
+    pub mouse_dead_zone: f32,
+    /// Factor applied to the camera movement while the sprint key (Left Shift by default) is held, between 1 and 20
+    pub sprint_factor: f32,
+    /// Up direction of the world, which the yaw and pitch of the camera (also of `initial_pose`) are measured against,
+    /// e.g. `[0.0, 0.0, 1.0]` for Z-up scenes
+    pub world_up: [f32; 3],
+    /// Axis the camera keeps its height along in the ground mode and looks down in the top-down view;
+    /// defaults to the normal of the mesh plane
+    pub lock_axis: [f32; 3],
+    /// Reload programs automatically when their shader sources in the resource folder are modified
+    pub auto_reload_shaders: bool,
+    /// Maximum amount of frames per second; 0 renders as fast as possible
+    pub frame_limit: u32,
+    /// Maximum amount of vertices the generated mesh may contain, to limit the memory usage
+    pub max_vertices: u64,
+    /// Attributes of the generated vertices in the order they are packed (`"position"`, `"normal"`, `"tex_coords"`,
+    /// `"tangent"`, `"bitangent"` and `"color"`); shaders that do not need all attributes can use a more compact layout.
+    /// The color is added and removed when toggling the vertex colors
+    pub vertex_attributes: Vec<VertexAttribute>,
+    /// Seconds between two steps of the demo reel
+    pub demo_reel_interval: f32,
+    /// Seconds the animated sun needs for a full day (including the night)
+    pub sun_cycle_time: f32,
+    /// Samples per pixel used to anti-alias screenshots; 0 or 1 disables multisampling
+    pub screenshot_samples: u32,
+    /// Distance to the camera where the linear fog starts
+    pub fog_start: f32,
+    /// Distance to the camera where the linear fog hides the surface completely
+    pub fog_end: f32,
+    /// Initial density of the exponential fog
+    pub fog_density: f32,
+    /// Color of the fog in sRGB; without a color, the fog matches the background
+    pub fog_color: Option<[f32; 3]>,
+    /// Offset subtracted from the depth of a fragment before the shadow test; too small values cause stripes ("shadow acne"),
+    /// too large values detach the shadows from their casters ("peter panning")
+    pub shadow_bias: f32,
+    /// Distance between two lines of the floor grid in world units
+    pub grid_cell_size: f32,
+    /// Distance to the camera at which the floor grid has faded out completely
+    pub grid_fade_distance: f32,
+    /// Color of the wireframe drawn on top of the mesh in sRGB
+    pub wireframe_color: [f32; 3],
+    /// Direction of the custom displacement axis of the terrain; its length scales the displacement
+    pub custom_displacement_axis: [f32; 3],
+    /// Name of the sample amount the mesh starts with (e.g. `"Medium"`)
+    pub sample_preset: String,
+    /// File name of the shader program to start with, without extension (e.g. `"parallax"`); the first program if not set
+    pub program: Option<String>,
+    /// Material selected by the material-ID map: a folder with a material definition or a texture name (e.g. `"textures/rock"`)
+    pub secondary_material: Option<String>,
+    /// Exponent of the specular highlight
+    pub shininess: f32,
+    /// Fraction of the surface color that is visible without direct light
+    pub ambient_strength: f32,
+    /// Factor for the slope of the normal map (`0.0` is flat, `1.0` as authored)
+    pub normal_strength: f32,
+    /// Tessellation level of the tessellating programs
+    pub tess_level: f32,
+    /// Name of the background color preset (e.g. `"gray"`)
+    pub clear_color: String,
+    /// Name of the light color preset (e.g. `"white"`)
+    pub light_color: String,
+    /// Fog mode (`"off"`, `"linear"` or `"exponential"`)
+    pub fog_mode: String,
+    /// Start with shadows enabled
+    pub shadows: bool,
+    /// Start with the light following the camera
+    pub headlamp: bool,
+    /// Start with HDR rendering and tone mapping enabled
+    pub hdr: bool,
+    /// Factor applied to the HDR colors before tone mapping
+    pub exposure: f32,
+    /// Factor the resolution of the scene is scaled by compared to the window; below 1.0 is faster, above 1.0 supersamples
+    pub render_scale: f32,
+    /// Inset the texture coordinates of the square, so filtering at its border does not blend in the opposite edge
+    pub clamp_uvs: bool,
+    /// Inset of the clamped texture coordinates on each side in texels of the base texture (0.5 = half a texel)
+    pub uv_inset: f32,
+    /// Axis the terrain is displaced along (`"normal"`, `"world_up"` or `"custom"`, see `custom_displacement_axis`)
+    pub displacement_axis: String,
+    /// Name of the terrain decimation level (`"Off"`, `"Low"`, `"Medium"` or `"High"`)
+    pub decimation: String,
+    /// Position of the light, unless it follows the camera
+    pub light_position: [f32; 3],
+    /// Show the controls at startup. It is set to `false` in the configuration file once they were shown, so they only
+    /// appear on the first launch; they can always be shown again with the `ShowHelp` action (`H`/`F1`)
+    pub show_controls: bool,
+    /// Pose the camera starts at and is reset to; the default looks at the mesh from one unit in front of it
+    pub initial_pose: Option<PoseConfig>,
+}
+
+/// # PoseConfig
+/// Camera pose as given in the configuration (see [`CameraPose`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoseConfig {
+    /// Position in world space
+    pub position: [f32; 3],
+    /// yaw in degrees
+    pub yaw: f32,
+    /// pitch in degrees
+    pub pitch: f32,
+    /// zoom (fovy-angle) in degrees
+    #[serde(default = "default_zoom")]
+    pub zoom: f32,
+}
+
+impl From<&PoseConfig> for CameraPose {
+    fn from(pose: &PoseConfig) -> Self {
+        CameraPose {
+            position: pose.position.into(),
+            yaw: pose.yaw,
+            pitch: pose.pitch,
+            zoom: pose.zoom,
+        }
+    }
+}
+
+impl From<&CameraPose> for PoseConfig {
+    fn from(pose: &CameraPose) -> Self {
+        PoseConfig {
+            position: pose.position.into(),
+            yaw: pose.yaw,
+            pitch: pose.pitch,
+            zoom: pose.zoom,
+        }
+    }
+}
+
+impl Config {
+    /// # Constructor
+    /// Loads the configuration from the given resource. If the file does not exist, the default configuration is used.
+    pub fn from_resources(res: &Resources, name: &str) -> Result<Config, String> {
+        let data = match res.load_bytes(name) {
+            Ok(data) => data,
+            Err(e) => {
+                debug!(target: LOG_TARGET, "No configuration loaded from \"{}\", using defaults: {:?}", name, e);
+                return Ok(Config::default());
+            }
+        };
+
+        let config: Config = toml::from_slice(&data)
+            .map_err(|e| format!("Invalid configuration in {}: {}", name, e))?;
+        info!(target: LOG_TARGET, "Loaded configuration from \"{}\": {:?}", name, config);
+        Ok(config)
+    }
+
+    /// Serializes the configuration in the format read by [`Config::from_resources`]
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|e| format!("Could not serialize the configuration: {}", e))
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            window_title: DEFAULT_WINDOW_TITLE.to_string(),
+            window_icon: DEFAULT_WINDOW_ICON.to_string(),
+            invert_y: false,
+            mouse_smoothing: 1.0,
+            mouse_dead_zone: 0.0,
+            sprint_factor: 4.0,
+            world_up: [0.0, 1.0, 0.0],
+            lock_axis: [0.0, 0.0, -1.0],
+            auto_reload_shaders: true,
+            frame_limit: 0,
+            // Allows 1024x1024 samples (6.3M vertices, ~360MiB with the 15 floats per vertex of the default layout),
+            // but not 2048x2048 samples (~1.4GiB)
+            max_vertices: 10_000_000,
+            vertex_attributes: VertexAttribute::DEFAULT.to_vec(),
+            demo_reel_interval: 8.0,
+            sun_cycle_time: 60.0,
+            screenshot_samples: 4,
+            fog_start: 1.0,
+            fog_end: 6.0,
+            fog_density: 0.3,
+            fog_color: None,
+            shadow_bias: 0.005,
+            grid_cell_size: 0.25,
+            grid_fade_distance: 8.0,
+            wireframe_color: [0.0, 0.0, 0.0],
+            custom_displacement_axis: [0.5, 0.5, 1.0],
+            sample_preset: "Medium".to_string(),
+            program: None,
+            secondary_material: None,
+            shininess: 32.0,
+            ambient_strength: 0.1,
+            normal_strength: 1.0,
+            tess_level: 4.0,
+            clear_color: "gray".to_string(),
+            light_color: "white".to_string(),
+            fog_mode: "off".to_string(),
+            shadows: false,
+            headlamp: false,
+            hdr: false,
+            exposure: 1.0,
+            render_scale: 1.0,
+            clamp_uvs: false,
+            uv_inset: 0.5,
+            displacement_axis: "normal".to_string(),
+            decimation: "Off".to_string(),
+            light_position: [1.0, 1.0, 1.0],
+            show_controls: true,
+            initial_pose: None,
+        }
+    }
+}
+
+fn default_zoom() -> f32 {
+    45.0
+}
@@ -0,0 +1,171 @@
+/// Point of a sample grid in cell units; the corners of the cells have integral coordinates
+pub type GridPoint = (f32, f32);
+
+/// Rectangle of cells `[x0, x1) x [y0, y1)` of the sample grid
+#[derive(Debug, Clone, Copy)]
+struct CellRect {
+    x0: u32,
+    y0: u32,
+    x1: u32,
+    y1: u32,
+}
+
+/// Triangulates a grid of `cells_x` by `cells_y` cells adaptively, using large triangles where the surface is flat
+/// and the full resolution where the height changes quickly.
+///
+/// The grid is split into a quadtree: a rectangle is kept as one leaf if the heights of all grid points inside it
+/// deviate at most `tolerance` from the bilinear interpolation of its corners, otherwise it is split into quarters.
+/// To avoid cracks between leaves of different size, every leaf is triangulated as a fan around its center through
+/// all corners of neighbouring leaves on its border. Leaves without such points are split into two triangles like
+/// the cells of the regular grid.
+///
+/// `height` is called with the integral grid coordinates of the cell corners (`0..=cells_x`, `0..=cells_y`).
+/// The triangles are returned in the winding order of the regular grid, i.e. `(x, y)`, `(x, y + 1)`, `(x + 1, y + 1)`.
+pub fn decimate_grid(cells_x: u32, cells_y: u32, height: impl Fn(u32, u32) -> f32, tolerance: f32) -> Vec<[GridPoint; 3]> {
+    let stride = cells_x as usize + 1;
+    let heights: Vec<f32> = (0..=cells_y)
+        .flat_map(|y| (0..=cells_x).map(move |x| (x, y)))
+        .map(|(x, y)| height(x, y))
+        .collect();
+    let height_at = |x: u32, y: u32| heights[y as usize * stride + x as usize];
+
+    // Step 1: Split the grid until the leaves are flat enough (or a single cell)
+    let mut leaves: Vec<CellRect> = Vec::new();
+    let mut pending = vec![CellRect { x0: 0, y0: 0, x1: cells_x, y1: cells_y }];
+    while let Some(rect) = pending.pop() {
+        let width = rect.x1 - rect.x0;
+        let depth = rect.y1 - rect.y0;
+        if width == 0 || depth == 0 {
+            continue;
+        }
+        if (width == 1 && depth == 1) || max_bilinear_error(rect, &height_at) <= tolerance {
+            leaves.push(rect);
+            continue;
+        }
+
+        let mid_x = rect.x0 + width / 2;
+        let mid_y = rect.y0 + depth / 2;
+        let xs = if width > 1 { vec![(rect.x0, mid_x), (mid_x, rect.x1)] } else { vec![(rect.x0, rect.x1)] };
+        let ys = if depth > 1 { vec![(rect.y0, mid_y), (mid_y, rect.y1)] } else { vec![(rect.y0, rect.y1)] };
+        for &(x0, x1) in &xs {
+            for &(y0, y1) in &ys {
+                pending.push(CellRect { x0, y0, x1, y1 });
+            }
+        }
+    }
+
+    // Step 2: Mark the corners of all leaves, which have to be part of every adjacent leaf
+    let mut corners = vec![false; heights.len()];
+    for leaf in &leaves {
+        for (x, y) in [(leaf.x0, leaf.y0), (leaf.x0, leaf.y1), (leaf.x1, leaf.y1), (leaf.x1, leaf.y0)] {
+            corners[y as usize * stride + x as usize] = true;
+        }
+    }
+
+    // Step 3: Triangulate the leaves
+    let mut triangles = Vec::new();
+    for leaf in &leaves {
+        let border: Vec<GridPoint> = border_points(*leaf)
+            .filter(|&(x, y)| corners[y as usize * stride + x as usize])
+            .map(|(x, y)| (x as f32, y as f32))
+            .collect();
+
+        if border.len() == 4 {
+            triangles.push([border[0], border[1], border[2]]);
+            triangles.push([border[0], border[2], border[3]]);
+        } else {
+            let center = ((leaf.x0 + leaf.x1) as f32 / 2.0, (leaf.y0 + leaf.y1) as f32 / 2.0);
+            for idx in 0..border.len() {
+                triangles.push([center, border[idx], border[(idx + 1) % border.len()]]);
+            }
+        }
+    }
+    triangles
+}
+
+/// Largest deviation of the heights inside the rectangle from the bilinear interpolation of its corners
+fn max_bilinear_error(rect: CellRect, height_at: &impl Fn(u32, u32) -> f32) -> f32 {
+    let (h00, h10) = (height_at(rect.x0, rect.y0), height_at(rect.x1, rect.y0));
+    let (h01, h11) = (height_at(rect.x0, rect.y1), height_at(rect.x1, rect.y1));
+    let width = (rect.x1 - rect.x0) as f32;
+    let depth = (rect.y1 - rect.y0) as f32;
+
+    let mut max_error: f32 = 0.0;
+    for y in rect.y0..=rect.y1 {
+        let ty = (y - rect.y0) as f32 / depth;
+        for x in rect.x0..=rect.x1 {
+            let tx = (x - rect.x0) as f32 / width;
+            let interpolated = (h00 * (1.0 - tx) + h10 * tx) * (1.0 - ty) + (h01 * (1.0 - tx) + h11 * tx) * ty;
+            max_error = max_error.max((height_at(x, y) - interpolated).abs());
+        }
+    }
+    max_error
+}
+
+/// All grid points on the border of the rectangle, starting at `(x0, y0)` in the winding order of the grid cells:
+/// along `x0` to `y1`, along `y1` to `x1`, back along `x1` to `y0` and along `y0` to the start
+fn border_points(rect: CellRect) -> impl Iterator<Item = (u32, u32)> {
+    let CellRect { x0, y0, x1, y1 } = rect;
+    (y0..y1).map(move |y| (x0, y))
+        .chain((x0..x1).map(move |x| (x, y1)))
+        .chain((y0 + 1..=y1).rev().map(move |y| (x1, y)))
+        .chain((x0 + 1..=x1).rev().map(move |x| (x, y0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Twice the signed area of the triangle; negative for the winding order of the regular grid
+    fn signed_area(triangle: &[GridPoint; 3]) -> f32 {
+        let [(ax, ay), (bx, by), (cx, cy)] = *triangle;
+        (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+    }
+
+    /// Heights with a rough bump in one corner of the grid and a flat plane everywhere else
+    fn bump(x: u32, y: u32) -> f32 {
+        if x <= 4 && y <= 4 { ((x * 7 + y * 13) % 5) as f32 } else { 0.0 }
+    }
+
+    #[test]
+    fn flat_grids_are_two_triangles() {
+        assert_eq!(decimate_grid(16, 12, |_, _| 1.0, 0.01).len(), 2);
+        assert_eq!(decimate_grid(16, 12, |x, y| x as f32 * 0.5 - y as f32 * 0.25, 0.01).len(), 2);
+    }
+
+    #[test]
+    fn full_detail_is_the_regular_grid() {
+        let triangles = decimate_grid(16, 12, |x, y| ((x * 7 + y * 13) % 5) as f32, 0.0);
+        assert_eq!(triangles.len(), 2 * 16 * 12);
+        let triangles = decimate_grid(16, 12, |_, _| 0.0, -1.0);
+        assert_eq!(triangles.len(), 2 * 16 * 12);
+    }
+
+    #[test]
+    fn triangles_keep_the_winding_and_cover_the_grid() {
+        let triangles = decimate_grid(16, 12, bump, 0.01);
+        assert!(triangles.len() > 2 && triangles.len() < 2 * 16 * 12);
+        assert!(triangles.iter().all(|triangle| signed_area(triangle) < 0.0), "{:?}", triangles);
+        let area: f32 = triangles.iter().map(|triangle| -signed_area(triangle) / 2.0).sum();
+        assert!((area - 16.0 * 12.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn no_vertex_lies_inside_an_edge() {
+        // A vertex inside the edge of another triangle is a T-junction, which cracks once the vertices are displaced
+        let triangles = decimate_grid(16, 12, bump, 0.01);
+        let vertices: Vec<GridPoint> = triangles.iter().flatten().copied().collect();
+        for triangle in &triangles {
+            for idx in 0..3 {
+                let ((ax, ay), (bx, by)) = (triangle[idx], triangle[(idx + 1) % 3]);
+                for &(px, py) in &vertices {
+                    let cross = (bx - ax) * (py - ay) - (by - ay) * (px - ax);
+                    let along = (px - ax) * (bx - ax) + (py - ay) * (by - ay);
+                    let length2 = (bx - ax).powi(2) + (by - ay).powi(2);
+                    assert!(cross.abs() > 1e-4 || along <= 1e-4 || along >= length2 - 1e-4,
+                            "({}, {}) lies inside the edge ({}, {}) - ({}, {})", px, py, ax, ay, bx, by);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+use cgmath::InnerSpace;
+
+use crate::glhelper::CameraPose;
+
+/// Distance of the orbiting camera to the center of the mesh
+const ORBIT_RADIUS: f32 = 1.5;
+/// Height of the orbiting camera above the center of the mesh
+const ORBIT_HEIGHT: f32 = 0.4;
+/// Largest angle in degrees the camera swings away from the front of the mesh (which is only visible from one side)
+const ORBIT_AMPLITUDE: f32 = 50.0;
+/// Time in seconds for one full swing of the camera from one side to the other and back
+const ORBIT_PERIOD: f32 = 30.0;
+
+/// # DemoReel
+/// Timer for an unattended presentation, which steps through the features in a fixed interval
+/// while the camera slowly swings around the mesh.
+#[derive(Debug)]
+pub struct DemoReel {
+    /// Time between two steps
+    interval: Duration,
+    /// Start of the reel, used for the camera animation
+    start: Instant,
+    /// Time of the last step
+    last_step: Instant,
+    /// Number of the last step
+    step: usize,
+}
+
+impl DemoReel {
+    /// # Constructor
+    /// Starts a new reel with the given time between two steps
+    pub fn new(interval: Duration) -> DemoReel {
+        let now = Instant::now();
+        DemoReel {
+            interval,
+            start: now,
+            last_step: now,
+            step: 0,
+        }
+    }
+
+    /// Returns the number of the next step (starting at 1) once the interval since the last step elapsed
+    pub fn next_step(&mut self) -> Option<usize> {
+        if self.last_step.elapsed() < self.interval {
+            return None;
+        }
+        self.last_step = Instant::now();
+        self.step += 1;
+        Some(self.step)
+    }
+
+    /// Calculates the pose of the camera swinging around the mesh, looking at its center.
+    /// The mesh lies in the xy-plane and is viewed from the negative z-side.
+    pub fn camera_pose(&self, zoom: f32) -> CameraPose {
+        let time = self.start.elapsed().as_secs_f32();
+        let angle = (ORBIT_AMPLITUDE * (time * std::f32::consts::TAU / ORBIT_PERIOD).sin()).to_radians();
+
+        let position = cgmath::vec3(ORBIT_RADIUS * angle.sin(), ORBIT_HEIGHT, -ORBIT_RADIUS * angle.cos());
+        let front = (-position).normalize();
+        CameraPose {
+            position,
+            yaw: front.z.atan2(front.x).to_degrees(),
+            pitch: front.y.asin().to_degrees(),
+            zoom,
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use log::info;
+
+use crate::config::Config;
+use crate::glhelper::{Program, ProgramSources};
+use crate::glhelper::utils::format_texture_path;
+use crate::keymap::KeyMap;
+use crate::material::MaterialDef;
+use crate::Resources;
+use crate::{CONFIG_NAME, KEYMAP_NAME, MATERIAL_FOLDER, SHADER_DIR, TEXTURE_EXTENSION, TEXTURE_NAME, UTIL_PROGRAMS};
+
+const LOG_TARGET: &str = "Diagnostics";
+
+/// Texture maps of the naming convention, which are required (`true`) or optional (`false`)
+const TEXTURE_MAPS: [(&str, bool); 6] = [
+    ("base", true), ("normal", true), ("height", true), ("detail", false), ("emissive", false), ("matid", false),
+];
+
+/// # CheckReport
+/// Results of the checked resources in the order they were checked
+#[derive(Debug, Default)]
+struct CheckReport {
+    /// Name of the resource with a short description of the passed check or the error
+    results: Vec<(String, Result<String, String>)>,
+}
+
+impl CheckReport {
+    fn add(&mut self, name: &str, result: Result<String, String>) {
+        info!(target: LOG_TARGET, "Checked \"{}\": {:?}", name, result);
+        self.results.push((name.to_string(), result));
+    }
+
+    /// Prints a line per resource to stdout and returns whether all checks passed
+    fn print(&self) -> bool {
+        let mut failed = 0;
+        println!("Resource check:");
+        for (name, result) in &self.results {
+            match result {
+                Ok(message) => println!("  [PASS] {}: {}", name, message),
+                Err(e) => {
+                    failed += 1;
+                    // Compiler logs span multiple lines, which are indented below the resource
+                    println!("  [FAIL] {}: {}", name, e.trim_end().replace('\n', "\n         "));
+                }
+            }
+        }
+        println!("{} of {} checks failed", failed, self.results.len());
+        failed == 0
+    }
+}
+
+/// Verifies that the configuration files parse, the textures decode and all programs compile and link.
+/// Prints a report to stdout and returns whether all checks passed. Needs a current OpenGL context for the programs.
+pub fn run_check(res: &Resources) -> bool {
+    let mut report = CheckReport::default();
+
+    report.add(CONFIG_NAME, check_optional_file(res, CONFIG_NAME)
+        .and_then(|exists| Config::from_resources(res, CONFIG_NAME).map(|_| exists)));
+    report.add(KEYMAP_NAME, check_optional_file(res, KEYMAP_NAME)
+        .and_then(|exists| KeyMap::from_resources(res, KEYMAP_NAME).map(|_| exists)));
+
+    match MaterialDef::from_resources(res, MATERIAL_FOLDER) {
+        Ok(Some(material)) => {
+            report.add(&format!("{}/{}", MATERIAL_FOLDER, crate::material::MATERIAL_FILE_NAME), Ok("parsed".to_string()));
+            let maps = [
+                Some(&material.base), Some(&material.normal), Some(&material.height),
+                material.detail.as_ref(), material.emissive.as_ref(), material.matid.as_ref(),
+            ];
+            for map in maps.into_iter().flatten() {
+                let path = material.map_path(map);
+                report.add(&path, check_image(res, &path));
+            }
+        }
+        Ok(None) => {
+            for (texture_type, required) in TEXTURE_MAPS {
+                let path = format_texture_path(TEXTURE_NAME, texture_type, TEXTURE_EXTENSION);
+                match check_image(res, &path) {
+                    Err(_) if !required && res.load_bytes(&path).is_err() => report.add(&path, Ok("optional, not present".to_string())),
+                    result => report.add(&path, result),
+                }
+            }
+        }
+        Err(e) => report.add(&format!("{}/{}", MATERIAL_FOLDER, crate::material::MATERIAL_FILE_NAME), Err(e)),
+    }
+
+    let mut program_names: Vec<String> = match res.list_dir(SHADER_DIR) {
+        Ok(files) => files.iter()
+            .filter_map(|file| file.strip_suffix(".vert"))
+            .map(|name| format!("{}/{}", SHADER_DIR, name))
+            .collect(),
+        Err(e) => {
+            report.add(SHADER_DIR, Err(format!("Error listing shaders: {:?}", e)));
+            Vec::new()
+        }
+    };
+    program_names.sort();
+    let programs = program_names.iter().map(|name| ProgramSources::Named(name)).chain(UTIL_PROGRAMS);
+    for sources in programs {
+        // Always compiled from the sources, a cached binary would hide compiler errors
+        let program = match sources {
+            ProgramSources::Named(name) => Program::from_res(res, name),
+            ProgramSources::Files(vert_name, frag_name) => Program::from_files(res, vert_name, frag_name),
+        };
+        report.add(&sources.to_string(), program.map(|_| "compiled and linked".to_string()));
+    }
+
+    report.print()
+}
+
+/// Describes whether an optional file exists for the report; its content is checked separately
+fn check_optional_file(res: &Resources, name: &str) -> Result<String, String> {
+    match res.load_bytes(name) {
+        Ok(_) => Ok("parsed".to_string()),
+        Err(_) => Ok("not present, using defaults".to_string()),
+    }
+}
+
+/// Checks that the image exists and can be decoded
+fn check_image(res: &Resources, name: &str) -> Result<String, String> {
+    let data = res.load_bytes(name)
+        .map_err(|e| format!("Error loading resource: {:?}", e))?;
+    let image = image::load_from_memory(&data)
+        .map_err(|e| format!("Could not decode image: {}", e))?;
+    Ok(format!("{}x{} {:?}", image.width(), image.height(), image.color()))
+}
@@ -1,10 +1,17 @@
-use cgmath::InnerSpace;
-use log::{info, trace};
+use cgmath::{Deg, InnerSpace, Quaternion, Rotation, Rotation3, VectorSpace};
+use log::{info, trace, warn};
 
 use crate::glhelper::utils::calc_look_at_matrix;
 
 const LOG_TARGET: &str = "Camera";
 
+/// Smallest allowed zoom (fovy-angle) in degrees
+const MIN_ZOOM: f32 = 10.0;
+/// Largest allowed zoom (fovy-angle) in degrees
+const MAX_ZOOM: f32 = 60.0;
+/// Factor the zoom is scaled by per unit of zoom-delta
+const ZOOM_STEP_FACTOR: f32 = 1.1;
+
 /// # Camera
 /// Contains all necessary values to calculate the view position, direction and zoom.
 #[derive(Debug)]
@@ -19,6 +26,10 @@ pub struct Camera {
     right: cgmath::Vector3<f32>,
     /// Relative up direction of the world. used to calculate the directional vectors
     world_up: cgmath::Vector3<f32>,
+    /// Horizontal direction (perpendicular to `world_up`) the camera faces at a yaw of 0°
+    yaw_axis_0: cgmath::Vector3<f32>,
+    /// Horizontal direction (perpendicular to `world_up`) the camera faces at a yaw of 90°
+    yaw_axis_90: cgmath::Vector3<f32>,
 
     /// yaw of the camera in degrees  (horizontal (xz) rotation)
     yaw: f32,
@@ -32,6 +43,77 @@ pub struct Camera {
     movement_speed: f32,
     /// Sensitivity to mouse movement
     mouse_sens: f32,
+    /// Flag to invert the vertical mouse movement
+    invert_y: bool,
+    /// Fraction of the remaining difference to the mouse movement the rotation catches up per frame (1.0 = no smoothing)
+    mouse_smoothing: f32,
+    /// Mouse movements of a frame shorter than this amount of pixels are ignored
+    mouse_dead_zone: f32,
+    /// Mouse movement received since the last frame as `(horizontal, vertical)`
+    mouse_delta: (f32, f32),
+    /// Smoothed mouse movement applied in the last frame as `(horizontal, vertical)`
+    smoothed_mouse_delta: (f32, f32),
+    /// Plane the camera can not move through, if set
+    ground_plane: Option<GroundPlane>,
+    /// Flag to keep the camera at its height along `lock_axis`: the movement stays in the plane perpendicular to it and up/down is ignored
+    lock_vertical: bool,
+    /// Unit direction the height is measured along while the movement is locked (`world_up` by default)
+    lock_axis: cgmath::Vector3<f32>,
+    /// Flag to ignore the vertical mouse movement, so the pitch stays unchanged
+    lock_pitch: bool,
+    /// Pose the camera starts at and is reset to
+    initial_pose: CameraPose,
+}
+
+/// # GroundPlane
+/// Plane that limits the movement of the camera to one side.
+/// Positions `p` with `dot(p, normal) >= distance` are allowed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroundPlane {
+    /// Unit normal of the plane, pointing to the allowed side
+    pub normal: cgmath::Vector3<f32>,
+    /// Signed distance of the plane to the origin along the normal
+    pub distance: f32,
+}
+
+/// # CameraPose
+/// Position, orientation and zoom of a camera, which can be stored and restored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    /// Position in cartesian coordinates
+    pub position: cgmath::Vector3<f32>,
+    /// yaw in degrees
+    pub yaw: f32,
+    /// pitch in degrees
+    pub pitch: f32,
+    /// zoom in degrees
+    pub zoom: f32,
+}
+
+impl CameraPose {
+    /// Interpolates between this pose (`t = 0`) and the other pose (`t = 1`).
+    /// Position and zoom are interpolated linearly, the orientation is interpolated spherically.
+    pub fn interpolate(&self, other: &CameraPose, t: f32) -> CameraPose {
+        let rotation = self.rotation().slerp(other.rotation(), t);
+        let front = rotation.rotate_vector(cgmath::Vector3::unit_x());
+
+        CameraPose {
+            position: self.position.lerp(other.position, t),
+            yaw: front.z.atan2(front.x).to_degrees(),
+            pitch: front.y.clamp(-1.0, 1.0).asin().to_degrees(),
+            zoom: self.zoom + (other.zoom - self.zoom) * t,
+        }
+    }
+
+    /// Rotation that turns the x-axis into the front direction of the pose
+    fn rotation(&self) -> Quaternion<f32> {
+        Quaternion::from_angle_y(Deg(-self.yaw)) * Quaternion::from_angle_z(Deg(self.pitch))
+    }
+}
+
+/// Checks that all components of the vector are finite
+fn is_finite(v: cgmath::Vector3<f32>) -> bool {
+    v.x.is_finite() && v.y.is_finite() && v.z.is_finite()
 }
 
 /// Possible movement directions to control the camera
@@ -50,18 +132,59 @@ impl Camera {
     /// Creates a new Camera with default values.
     /// The assumed `world_up` is `(0,1,0)`
     pub fn new() -> Camera {
+        Camera::with_world_up(cgmath::vec3(0.0, 1.0, 0.0))
+    }
+
+    /// # Constructor
+    /// Creates a new Camera with default values for a world with the given up direction (e.g. `(0,0,1)` for Z-up assets).
+    /// The pitch is measured against the plane perpendicular to `world_up`. A yaw of 0° faces the x-axis projected onto that plane
+    /// (the z-axis if `world_up` is parallel to the x-axis), increasing yaw turns towards `yaw_axis_0 × world_up`.
+    /// For the default `(0,1,0)` this is the same convention as [`Camera::new`], which is also used for a zero vector.
+    pub fn with_world_up(world_up: cgmath::Vector3<f32>) -> Camera {
+        let world_up = if world_up.magnitude2() > f32::EPSILON {
+            world_up.normalize()
+        } else {
+            warn!(target: LOG_TARGET, "Invalid world up direction {:?}, using (0,1,0)", world_up);
+            cgmath::vec3(0.0, 1.0, 0.0)
+        };
+        let reference = if world_up.x.abs() < 0.999 { cgmath::Vector3::unit_x() } else { cgmath::Vector3::unit_z() };
+        let yaw_axis_0 = (reference - world_up * reference.dot(world_up)).normalize();
+        let yaw_axis_90 = yaw_axis_0.cross(world_up);
+
         let mut to_return = Camera {
             pos: cgmath::Zero::zero(),
             front: cgmath::Zero::zero(),
             up: cgmath::Zero::zero(),
             right: cgmath::Zero::zero(),
-            world_up: cgmath::vec3(0.0, 1.0, 0.0),
+            world_up,
+            yaw_axis_0,
+            yaw_axis_90,
             yaw: 0.0,
             pitch: 0.0,
             movement_speed: 0.5,
             mouse_sens: 0.1,
+            invert_y: false,
+            mouse_smoothing: 1.0,
+            mouse_dead_zone: 0.0,
+            mouse_delta: (0.0, 0.0),
+            smoothed_mouse_delta: (0.0, 0.0),
+            ground_plane: None,
+            lock_vertical: false,
+            lock_axis: world_up,
+            lock_pitch: false,
             zoom: 0.0,
+            initial_pose: CameraPose {
+                position: cgmath::Zero::zero(),
+                yaw: 90.0,
+                pitch: 0.0,
+                zoom: 45.0,
+            },
         };
+        // By default the camera starts one unit in front of the origin, looking horizontally at it
+        to_return.yaw = to_return.initial_pose.yaw;
+        to_return.pitch = to_return.initial_pose.pitch;
+        to_return.recalculate_direction_vectors();
+        to_return.initial_pose.position = -to_return.front;
         to_return.reset_position();
         info!(target: LOG_TARGET, "Created new camera: {:?}", to_return);
         to_return
@@ -82,13 +205,132 @@ impl Camera {
         self.pos
     }
 
-    /// Resets the camera to the default position, direction and zoom
+    /// Check whether the vertical mouse movement is inverted
+    pub fn invert_y(&self) -> bool {
+        self.invert_y
+    }
+
+    /// Set whether the vertical mouse movement is inverted
+    pub fn set_invert_y(&mut self, invert_y: bool) {
+        self.invert_y = invert_y;
+    }
+
+    /// Set the fraction of the remaining mouse movement the rotation catches up with per frame.
+    /// Smaller values smooth the rotation over more frames, 1.0 applies the raw mouse movement.
+    pub fn set_mouse_smoothing(&mut self, smoothing: f32) {
+        self.mouse_smoothing = smoothing.clamp(0.01, 1.0);
+    }
+
+    /// Set the length (in pixels) the mouse movement of a frame needs to exceed to rotate the camera
+    pub fn set_mouse_dead_zone(&mut self, dead_zone: f32) {
+        self.mouse_dead_zone = dead_zone.max(0.0);
+    }
+
+    /// Get the plane the camera can not move through
+    pub fn ground_plane(&self) -> Option<GroundPlane> {
+        self.ground_plane
+    }
+
+    /// Set a plane the camera can not move through (`None` allows to fly freely)
+    pub fn set_ground_plane(&mut self, ground_plane: Option<GroundPlane>) {
+        self.ground_plane = ground_plane;
+    }
+
+    /// Check whether the movement is limited to the horizontal plane
+    pub fn lock_vertical(&self) -> bool {
+        self.lock_vertical
+    }
+
+    /// Set whether the movement is limited to the plane perpendicular to the lock axis (see [`Camera::with_lock_axis`])
+    pub fn set_lock_vertical(&mut self, lock_vertical: bool) {
+        self.lock_vertical = lock_vertical;
+    }
+
+    /// Sets the direction the height of the camera is kept along while the movement is locked, e.g. the normal of the
+    /// plane the scene lies in if it is not perpendicular to `world_up`. A zero vector keeps the previous axis.
+    pub fn with_lock_axis(mut self, axis: cgmath::Vector3<f32>) -> Camera {
+        if axis.magnitude2() > f32::EPSILON {
+            self.lock_axis = axis.normalize();
+        }
+        self
+    }
+
+    /// Check whether the pitch is locked
+    pub fn lock_pitch(&self) -> bool {
+        self.lock_pitch
+    }
+
+    /// Set whether the vertical mouse movement is ignored. The current pitch is kept, only the yaw can be changed.
+    pub fn set_lock_pitch(&mut self, lock_pitch: bool) {
+        self.lock_pitch = lock_pitch;
+        if lock_pitch {
+            self.mouse_delta.1 = 0.0;
+            self.smoothed_mouse_delta.1 = 0.0;
+        }
+    }
+
+    /// Get the current pose of the camera
+    pub fn pose(&self) -> CameraPose {
+        CameraPose {
+            position: self.pos,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            zoom: self.zoom,
+        }
+    }
+
+    /// Moves the camera to the given pose
+    pub fn set_pose(&mut self, pose: &CameraPose) {
+        self.pos = pose.position;
+        self.yaw = pose.yaw;
+        self.pitch = pose.pitch.clamp(-89.9, 89.9);
+        self.zoom = pose.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.recalculate_direction_vectors();
+    }
+
+    /// Sets the pose the camera starts at and is reset to, and moves the camera there.
+    /// By default the camera is placed one unit in front of the origin, looking horizontally at it with a yaw of 90°.
+    pub fn with_initial_pose(mut self, pose: CameraPose) -> Camera {
+        self.initial_pose = pose;
+        self.reset_position();
+        self
+    }
+
+    /// Resets the camera to the initial position, direction and zoom
     pub fn reset_position(&mut self) {
-        self.pos = cgmath::vec3(0.0, 0.0, -1.0);
-        self.yaw = 90.0;
-        self.pitch = 0.0;
-        self.zoom = 45.0;
+        let pose = self.initial_pose;
+        self.set_pose(&pose);
+    }
+
+    /// Places the camera `height` units above `center` along the lock axis, looking straight down onto the plane
+    /// perpendicular to it (see [`Camera::with_lock_axis`]). The zoom is kept.
+    ///
+    /// "Down" is the lock axis instead of `world_up`, because the scene plane does not have to be perpendicular to `world_up`:
+    /// the mesh of the application lies in the xy-plane with `world_up = (0,1,0)`, so its top-down view has a pitch of 0
+    /// and `world_up` stays a stable up vector. Only if the lock axis is parallel to `world_up` the pitch reaches
+    /// the clamped -89.9°, which tilts the view slightly; the camera is moved back along the resulting direction,
+    /// so it still looks at `center`.
+    pub fn set_top_down(&mut self, center: cgmath::Vector3<f32>, height: f32) {
+        self.front = -self.lock_axis;
+        self.restore_angles();
+        self.pitch = self.pitch.clamp(-89.9, 89.9);
         self.recalculate_direction_vectors();
+        self.pos = center - self.front * height;
+        info!(target: LOG_TARGET, "Moved camera to top-down view at height {} above {:?}", height, center);
+    }
+
+    /// Moves the camera back along its current viewing direction until the given axis-aligned box fits into the view.
+    /// The box is enclosed by a sphere, which is fit into the narrower of the vertical field of view (the zoom)
+    /// and the horizontal one, which follows from the aspect ratio (width / height) of the viewport.
+    pub fn frame_bounds(&mut self, min: cgmath::Vector3<f32>, max: cgmath::Vector3<f32>, aspect: f32) {
+        let center = (min + max) * 0.5;
+        let radius = (max - min).magnitude() * 0.5;
+        let half_vfov = self.zoom.to_radians() * 0.5;
+        let half_hfov = (half_vfov.tan() * aspect.max(f32::EPSILON)).atan();
+        let distance = radius / half_vfov.min(half_hfov).sin();
+
+        self.pos = center - self.front * distance;
+        info!(target: LOG_TARGET, "Framed bounds {:?} - {:?} from {:?}", min, max, self.pos);
     }
 
     /// Moves the camera in the given direction
@@ -96,18 +338,29 @@ impl Camera {
         let v = self.movement_speed * amount;
         trace!(target: LOG_TARGET, "Moving {:?} by {} units", dir, v);
 
+        // Without vertical movement, the camera moves along its directions projected onto the plane perpendicular to the lock axis
+        let (front, right) = if self.lock_vertical {
+            (self.along_lock_plane(self.front), self.along_lock_plane(self.right))
+        } else {
+            (self.front, self.right)
+        };
+
         match dir {
+            MovementDirection::UP | MovementDirection::DOWN if self.lock_vertical => {
+                trace!(target: LOG_TARGET, "Ignoring vertical movement");
+                return;
+            }
             MovementDirection::FORWARD => {
-                self.pos += self.front * v
+                self.pos += front * v
             }
             MovementDirection::BACKWARD => {
-                self.pos -= self.front * v
+                self.pos -= front * v
             }
             MovementDirection::LEFT => {
-                self.pos -= self.right * v
+                self.pos -= right * v
             }
             MovementDirection::RIGHT => {
-                self.pos += self.right * v
+                self.pos += right * v
             }
             MovementDirection::UP => {
                 self.pos += self.up * v
@@ -117,36 +370,240 @@ impl Camera {
             }
         }
 
+        if let Some(plane) = self.ground_plane {
+            let distance = self.pos.dot(plane.normal) - plane.distance;
+            if distance < 0.0 {
+                self.pos -= plane.normal * distance;
+                info!(target: LOG_TARGET, "Clamped camera to the ground plane at {:?}", self.pos);
+            }
+        }
+
         trace!(target: LOG_TARGET, "New position: {:?}", self.pos);
     }
 
-    /// Rotates the camera by the given amount
+    /// Projects the direction onto the plane perpendicular to the lock axis and normalizes it,
+    /// so the speed does not depend on the pitch. Directions along the lock axis result in no movement.
+    fn along_lock_plane(&self, direction: cgmath::Vector3<f32>) -> cgmath::Vector3<f32> {
+        let projected = direction - self.lock_axis * direction.dot(self.lock_axis);
+        if projected.magnitude2() > f32::EPSILON { projected.normalize() } else { cgmath::Zero::zero() }
+    }
+
+    /// Rotates the camera by the given mouse movement.
+    /// The movements are collected and applied smoothed by [`Camera::update_rotation`].
     pub fn rotate_camera(&mut self, horiz_amount: f32, vert_amount: f32) {
+        self.mouse_delta.0 += horiz_amount;
+        if !self.lock_pitch {
+            self.mouse_delta.1 += vert_amount;
+        }
+    }
+
+    /// Discards the mouse movement that was collected but not applied yet, so the camera stops rotating immediately
+    pub fn reset_mouse_delta(&mut self) {
+        self.mouse_delta = (0.0, 0.0);
+        self.smoothed_mouse_delta = (0.0, 0.0);
+    }
+
+    /// Applies the mouse movement collected since the last frame. Has to be called once per frame.
+    /// The applied movement approaches the collected one exponentially, so a single movement is spread across a few frames.
+    ///
+    /// The dead zone is applied to the movement of the whole frame instead of the single mouse events,
+    /// so slow movements are not lost when a mouse with a high polling rate splits them into many tiny events.
+    pub fn update_rotation(&mut self) {
+        let (mut target_h, mut target_v) = std::mem::take(&mut self.mouse_delta);
+        if target_h.hypot(target_v) <= self.mouse_dead_zone {
+            if target_h != 0.0 || target_v != 0.0 {
+                trace!(target: LOG_TARGET, "Ignoring mouse movement ({}, {}) inside the dead zone", target_h, target_v);
+            }
+            (target_h, target_v) = (0.0, 0.0);
+        }
+        let (smoothed_h, smoothed_v) = self.smoothed_mouse_delta;
+        let horiz_amount = smoothed_h + (target_h - smoothed_h) * self.mouse_smoothing;
+        let vert_amount = smoothed_v + (target_v - smoothed_v) * self.mouse_smoothing;
+
+        if horiz_amount.hypot(vert_amount) < 1e-3 {
+            self.smoothed_mouse_delta = (0.0, 0.0);
+            return;
+        }
+        self.smoothed_mouse_delta = (horiz_amount, vert_amount);
+        self.apply_rotation(horiz_amount, vert_amount);
+    }
+
+    /// Rotates the camera by the given amount
+    fn apply_rotation(&mut self, horiz_amount: f32, vert_amount: f32) {
         trace!(target: LOG_TARGET, "Rotating by {}° horizontally and {}° vertically.",
             horiz_amount * self.mouse_sens, vert_amount* self.mouse_sens);
 
+        let vert_amount = if self.invert_y { -vert_amount } else { vert_amount };
         self.yaw = self.yaw + horiz_amount * self.mouse_sens;
         self.pitch = (self.pitch + vert_amount * self.mouse_sens).clamp(-89.9, 89.9);
         self.recalculate_direction_vectors();
         trace!(target: LOG_TARGET, "New rotation: yaw: {}°, pitch: {}°. [front: {:?}; up: {:?}, right: {:?}]", self.yaw, self.pitch, self.front, self.up, self.right);
     }
 
-    /// Zooms the camera by the given amount
+    /// Zooms the camera by the given amount.
+    /// The zoom is scaled multiplicatively, so the same delta is perceived equally strong across the whole zoom range.
     pub fn zoom_camera(&mut self, delta: f32) {
-        trace!(target: LOG_TARGET, "Zooming by {}°.", delta);
-        self.zoom = (self.zoom - delta).clamp(10.0, 60.0);
+        trace!(target: LOG_TARGET, "Zooming by {} steps.", delta);
+        self.zoom = (self.zoom * ZOOM_STEP_FACTOR.powf(-delta)).clamp(MIN_ZOOM, MAX_ZOOM);
         trace!(target: LOG_TARGET, "New zoom: {}°", self.zoom);
     }
 
-    /// Used to recalculate the directional vectors from `yaw` and `pitch`
+    /// Used to recalculate the directional vectors from `yaw` and `pitch`.
+    /// `right` only depends on the yaw (it is `front × world_up` without the pitch), so it can not become zero
+    /// even if `front` is (nearly) parallel to `world_up`.
+    ///
+    /// If the result is not finite anyway (e.g. because of an invalid pose), the previous vectors are kept
+    /// and `yaw` and `pitch` are restored from them, so the camera does not get stuck with `NaN` values.
     fn recalculate_direction_vectors(&mut self) {
-        self.front = cgmath::vec3(
-            self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
-            self.pitch.to_radians().sin(),
-            self.yaw.to_radians().sin() * self.pitch.to_radians().cos(),
-        ).normalize();
-
-        self.right = self.front.cross(self.world_up).normalize();
-        self.up = self.right.cross(self.front).normalize();
+        let (yaw_sin, yaw_cos) = self.yaw.to_radians().sin_cos();
+        let (pitch_sin, pitch_cos) = self.pitch.to_radians().sin_cos();
+
+        let front = (self.yaw_axis_0 * (yaw_cos * pitch_cos)
+            + self.world_up * pitch_sin
+            + self.yaw_axis_90 * (yaw_sin * pitch_cos)).normalize();
+        let right = (self.yaw_axis_90 * yaw_cos - self.yaw_axis_0 * yaw_sin).normalize();
+        let up = right.cross(front).normalize();
+
+        if !(is_finite(front) && is_finite(right) && is_finite(up)) {
+            warn!(target: LOG_TARGET, "Invalid direction vectors for yaw {}° and pitch {}° [front: {:?}; up: {:?}, right: {:?}], keeping the previous direction",
+                self.yaw, self.pitch, front, up, right);
+            self.restore_angles();
+            return;
+        }
+
+        self.front = front;
+        self.right = right;
+        self.up = up;
+    }
+
+    /// Derives `yaw` and `pitch` from the current front vector (the inverse of [`Camera::recalculate_direction_vectors`])
+    fn restore_angles(&mut self) {
+        if !is_finite(self.front) || self.front.magnitude2() == 0.0 {
+            // There are no valid vectors yet, so the initial direction is used
+            self.yaw = self.initial_pose.yaw;
+            self.pitch = self.initial_pose.pitch;
+            return;
+        }
+        self.yaw = self.front.dot(self.yaw_axis_90).atan2(self.front.dot(self.yaw_axis_0)).to_degrees();
+        self.pitch = self.front.dot(self.world_up).clamp(-1.0, 1.0).asin().to_degrees();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_is_monotonic_and_clamped() {
+        for delta in [-5.0, -1.0, -0.25, 0.25, 1.0, 5.0] {
+            let mut camera = Camera::new();
+            let mut previous = camera.zoom();
+            for _ in 0..100 {
+                camera.zoom_camera(delta);
+                let zoom = camera.zoom();
+                assert!((MIN_ZOOM..=MAX_ZOOM).contains(&zoom), "zoom {} out of range for delta {}", zoom, delta);
+                if delta > 0.0 {
+                    assert!(zoom <= previous, "zooming in by {} increased the zoom from {} to {}", delta, previous, zoom);
+                } else {
+                    assert!(zoom >= previous, "zooming out by {} decreased the zoom from {} to {}", delta, previous, zoom);
+                }
+                previous = zoom;
+            }
+            let limit = if delta > 0.0 { MIN_ZOOM } else { MAX_ZOOM };
+            assert_eq!(camera.zoom(), limit);
+        }
+    }
+
+    #[test]
+    fn locked_movement_keeps_the_height_along_the_lock_axis() {
+        for axis in [cgmath::vec3(0.0, 1.0, 0.0), cgmath::vec3(0.0, 0.0, -1.0)] {
+            let mut camera = Camera::new().with_lock_axis(axis);
+            camera.set_lock_vertical(true);
+            camera.set_pose(&CameraPose { yaw: 60.0, pitch: -30.0, ..camera.pose() });
+            let start = camera.position();
+
+            for dir in [MovementDirection::FORWARD, MovementDirection::RIGHT, MovementDirection::UP, MovementDirection::BACKWARD, MovementDirection::LEFT] {
+                camera.move_camera(dir, 1.0);
+            }
+            let moved = camera.position() - start;
+            assert!(moved.dot(axis).abs() < 1e-5, "moved {:?} along the lock axis {:?}", moved, axis);
+        }
+    }
+
+    #[test]
+    fn dead_zone_applies_to_the_movement_of_a_frame() {
+        let mut camera = Camera::new();
+        camera.set_mouse_dead_zone(2.0);
+        let yaw = camera.pose().yaw;
+
+        // Many tiny events of a mouse with a high polling rate add up to a movement outside the dead zone
+        for _ in 0..10 {
+            camera.rotate_camera(0.5, 0.0);
+        }
+        camera.update_rotation();
+        assert!((camera.pose().yaw - yaw - 5.0 * camera.mouse_sens).abs() < 1e-4);
+
+        // A small movement of a whole frame is still ignored
+        let yaw = camera.pose().yaw;
+        camera.rotate_camera(1.5, 0.0);
+        camera.update_rotation();
+        assert_eq!(camera.pose().yaw, yaw);
+    }
+
+    #[test]
+    fn top_down_view_looks_at_the_center_along_the_lock_axis() {
+        let center = cgmath::vec3(0.5, -0.25, 0.1);
+        for (world_up, lock_axis) in [
+            (cgmath::vec3(0.0, 1.0, 0.0), cgmath::vec3(0.0, 0.0, -1.0)),
+            (cgmath::vec3(0.0, 1.0, 0.0), cgmath::vec3(0.0, 1.0, 0.0)),
+            (cgmath::vec3(0.0, 0.0, 1.0), cgmath::vec3(0.0, 0.0, 1.0)),
+        ] {
+            let mut camera = Camera::with_world_up(world_up).with_lock_axis(lock_axis);
+            camera.set_top_down(center, 2.0);
+
+            let to_center = center - camera.position();
+            assert!((to_center.magnitude() - 2.0).abs() < 1e-4, "camera not at height 2 for lock axis {:?}: {:?}", lock_axis, to_center);
+            assert!(to_center.normalize().dot(camera.front) > 0.9999, "camera does not look at the center for lock axis {:?}", lock_axis);
+            assert!(camera.front.dot(-lock_axis) > 0.9999, "camera does not look down the lock axis {:?}: {:?}", lock_axis, camera.front);
+            assert!(is_finite(camera.up) && camera.up.magnitude() > 0.5, "unstable up vector for lock axis {:?}", lock_axis);
+        }
+    }
+
+    #[test]
+    fn framed_bounds_are_inside_the_frustum() {
+        use crate::glhelper::utils::calc_projection_matrix;
+
+        let (min, max) = (cgmath::vec3(-0.5, -0.5, -0.5), cgmath::vec3(0.5, 0.5, 0.5));
+        for aspect in [0.25, 0.5, 1.0, 16.0 / 9.0, 4.0] {
+            for zoom in [MIN_ZOOM, 45.0, MAX_ZOOM] {
+                let mut camera = Camera::new();
+                camera.set_pose(&CameraPose { zoom, ..camera.pose() });
+                camera.frame_bounds(min, max, aspect);
+
+                let view_proj = calc_projection_matrix(zoom.to_radians(), aspect, 0.1, 100.0).unwrap() * camera.calc_view_matrix();
+                for corner in 0..8 {
+                    let pick = |bit: usize, axis: usize| if corner & bit == 0 { min[axis] } else { max[axis] };
+                    let clip = view_proj * cgmath::vec4(pick(1, 0), pick(2, 1), pick(4, 2), 1.0);
+                    let ndc = clip.truncate() / clip.w;
+                    assert!(clip.w > 0.0 && ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && ndc.z.abs() <= 1.0,
+                        "corner {} outside of the frustum for aspect {} and zoom {}: {:?}", corner, aspect, zoom, ndc);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_step_is_relative() {
+        let mut camera = Camera::new();
+        camera.zoom = 50.0;
+        camera.zoom_camera(1.0);
+        let far_ratio = camera.zoom() / 50.0;
+
+        camera.zoom = 15.0;
+        camera.zoom_camera(1.0);
+        let near_ratio = camera.zoom() / 15.0;
+
+        assert!((far_ratio - near_ratio).abs() < 1e-5);
+        assert!((far_ratio - 1.0 / ZOOM_STEP_FACTOR).abs() < 1e-5);
     }
 }
@@ -0,0 +1,143 @@
+use std::time::Instant;
+
+use log::{debug, info};
+
+use crate::glhelper::CameraPose;
+use crate::Resources;
+
+const LOG_TARGET: &str = "CameraPath";
+
+/// Time between two recorded keyframes in seconds
+const KEYFRAME_INTERVAL: f32 = 2.0;
+
+/// # CameraPath
+/// A list of timed camera poses (keyframes), which can be played back as an animated camera flight.
+#[derive(Debug, Default)]
+pub struct CameraPath {
+    /// Keyframes as `(timestamp in seconds, pose)`, ordered by their timestamp
+    keyframes: Vec<(f32, CameraPose)>,
+    /// Start of the current playback, if the path is playing
+    playback_start: Option<Instant>,
+}
+
+impl CameraPath {
+    /// # Constructor
+    /// Creates a new, empty path
+    pub fn new() -> CameraPath {
+        CameraPath::default()
+    }
+
+    /// # Constructor
+    /// Loads a path from the resources.
+    /// Every line of the file describes one keyframe as `time x y z yaw pitch zoom`.
+    pub fn from_resources(res: &Resources, name: &str) -> Result<CameraPath, String> {
+        let data = res.load_bytes(name)
+            .map_err(|e| format!("Error loading resource {}: {:?}", name, e))?;
+
+        let mut keyframes = Vec::new();
+        for (line_idx, line) in String::from_utf8_lossy(&data).lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let values = line.split_whitespace()
+                .map(|value| value.parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|e| format!("Invalid value in line {} of {}: {}", line_idx + 1, name, e))?;
+            if values.len() != 7 {
+                return Err(format!("Expected 7 values in line {} of {}, found {}", line_idx + 1, name, values.len()));
+            }
+
+            keyframes.push((values[0], CameraPose {
+                position: cgmath::vec3(values[1], values[2], values[3]),
+                yaw: values[4],
+                pitch: values[5],
+                zoom: values[6],
+            }));
+        }
+        keyframes.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        info!(target: LOG_TARGET, "Loaded camera path with {} keyframes from \"{}\"", keyframes.len(), name);
+        Ok(CameraPath { keyframes, playback_start: None })
+    }
+
+    /// Stores the path in the resources, using the format read by [`CameraPath::from_resources`]
+    pub fn save(&self, res: &Resources, name: &str) -> Result<(), String> {
+        let data: String = self.keyframes.iter()
+            .map(|(time, pose)| format!("{} {} {} {} {} {} {}\n",
+                                        time, pose.position.x, pose.position.y, pose.position.z, pose.yaw, pose.pitch, pose.zoom))
+            .collect();
+
+        res.write_bytes(name, data.as_bytes())
+            .map_err(|e| format!("Error writing resource {}: {:?}", name, e))?;
+        info!(target: LOG_TARGET, "Saved camera path with {} keyframes to \"{}\"", self.keyframes.len(), name);
+        Ok(())
+    }
+
+    /// Appends the given pose as new keyframe at the end of the path
+    pub fn record(&mut self, pose: CameraPose) {
+        let time = self.keyframes.last()
+            .map(|(time, _)| time + KEYFRAME_INTERVAL)
+            .unwrap_or(0.0);
+        debug!(target: LOG_TARGET, "Recorded keyframe at {}s: {:?}", time, pose);
+        self.keyframes.push((time, pose));
+    }
+
+    /// Removes all keyframes and stops the playback
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+        self.playback_start = None;
+    }
+
+    /// Amount of keyframes in the path
+    pub fn len(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    /// Check whether the path contains no keyframes
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Check whether the path is currently played back
+    pub fn is_playing(&self) -> bool {
+        self.playback_start.is_some()
+    }
+
+    /// Starts the playback from the first keyframe. At least two keyframes are needed.
+    pub fn play(&mut self) -> Result<(), String> {
+        if self.keyframes.len() < 2 {
+            return Err(format!("At least 2 keyframes are needed, but the path contains {}", self.keyframes.len()));
+        }
+        self.playback_start = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Stops the playback
+    pub fn stop(&mut self) {
+        self.playback_start = None;
+    }
+
+    /// Calculates the pose for the current playback time.
+    /// Returns `None` if the path is not playing. The playback stops automatically after the last keyframe.
+    pub fn current_pose(&mut self) -> Option<CameraPose> {
+        let time = self.playback_start?.elapsed().as_secs_f32();
+        let (end_time, end_pose) = *self.keyframes.last()?;
+        if time >= end_time {
+            info!(target: LOG_TARGET, "Finished playback of camera path");
+            self.stop();
+            return Some(end_pose);
+        }
+
+        let next_idx = self.keyframes.iter()
+            .position(|(keyframe_time, _)| *keyframe_time > time)
+            .unwrap_or(self.keyframes.len() - 1)
+            .max(1);
+        let (start_time, start_pose) = self.keyframes[next_idx - 1];
+        let (next_time, next_pose) = self.keyframes[next_idx];
+
+        let duration = next_time - start_time;
+        let t = if duration > 0.0 { ((time - start_time) / duration).clamp(0.0, 1.0) } else { 1.0 };
+        Some(start_pose.interpolate(&next_pose, t))
+    }
+}
@@ -0,0 +1,77 @@
+use gl::types::{GLenum, GLint, GLuint};
+use log::{info, trace};
+
+use crate::Resources;
+
+const LOG_TARGET: &str = "CubeMap";
+
+/// File names (without extension) of the faces in the order of the cube map targets `+X, -X, +Y, -Y, +Z, -Z`
+pub const FACE_NAMES: [&str; 6] = ["right", "left", "top", "bottom", "front", "back"];
+
+/// # CubeMap
+/// Handle for a cube map texture loaded in OpenGL from six sRGB images of the same quadratic size
+pub struct CubeMap {
+    id: GLuint,
+}
+
+impl CubeMap {
+    /// # Constructor
+    /// Loads the faces `<folder>/<face>.<extension>` (see [`FACE_NAMES`]) from the resources.
+    /// Fails if a face is missing or the faces do not have the same quadratic size.
+    pub fn from_resources(res: &Resources, folder: &str, extension: &str) -> Result<CubeMap, String> {
+        let mut faces = Vec::with_capacity(FACE_NAMES.len());
+        for face in FACE_NAMES {
+            let name = format!("{}/{}.{}", folder, face, extension);
+            let data = res.load_bytes(&name)
+                .map_err(|e| format!("Error loading resource {}: {:?}", name, e))?;
+            let img = image::load_from_memory(&data)
+                .map_err(|e| format!("Could not load cube map face \"{}\": {}", name, e))?
+                .to_rgb8();
+            let size = faces.first().map_or(img.width(), image::RgbImage::width);
+            if img.width() != size || img.height() != size {
+                return Err(format!("Cube map face \"{}\" has a size of {}x{}, but all faces need the same quadratic size", name, img.width(), img.height()));
+            }
+            faces.push(img);
+        }
+        let size = faces[0].width();
+
+        let mut id: GLuint = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, id);
+            for (idx, img) in faces.iter().enumerate() {
+                gl::TexImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + idx as GLenum, 0, gl::SRGB8 as GLint,
+                               size as GLint, size as GLint,
+                               0, gl::RGB, gl::UNSIGNED_BYTE,
+                               img.as_raw().as_ptr().cast());
+            }
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+        info!(target: LOG_TARGET, "Loaded cube map \"{}\" ({}x{} per face) from resources as texture {}", folder, size, size, id);
+
+        Ok(CubeMap { id })
+    }
+
+    /// Bind the cube map to the given texture_unit
+    pub fn bind_texture(&self, texture_unit: GLenum) {
+        trace!(target: LOG_TARGET, "Using cube map {} for unit {}", self.id, texture_unit - gl::TEXTURE0);
+        unsafe {
+            gl::ActiveTexture(texture_unit);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id);
+        }
+    }
+}
+
+impl Drop for CubeMap {
+    fn drop(&mut self) {
+        info!(target: LOG_TARGET, "Unloading cube map {}", self.id);
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
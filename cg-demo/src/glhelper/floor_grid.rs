@@ -0,0 +1,35 @@
+use log::info;
+
+use crate::glhelper::StaticMesh;
+
+const LOG_TARGET: &str = "FloorGrid";
+
+/// # FloorGrid
+/// A large quad in the xz-plane, on which a grid shader (`shaders/util/grid`) draws anti-aliased lines.
+/// The lines fade out with the distance to the camera, so the edge of the quad is not visible.
+pub struct FloorGrid {
+    quad: StaticMesh,
+}
+
+impl FloorGrid {
+    /// # Constructor
+    /// Creates the quad, reaching `extent` units from the origin in each direction
+    pub fn new(extent: f32) -> FloorGrid {
+        let quad = StaticMesh::new(&[
+            [-extent, 0.0, -extent],
+            [extent, 0.0, extent],
+            [extent, 0.0, -extent],
+            [-extent, 0.0, -extent],
+            [-extent, 0.0, extent],
+            [extent, 0.0, extent],
+        ]);
+        info!(target: LOG_TARGET, "Created floor grid with extent {}", extent);
+
+        FloorGrid { quad }
+    }
+
+    /// Draws the quad with the currently active program
+    pub fn draw(&self) {
+        self.quad.draw();
+    }
+}
@@ -0,0 +1,290 @@
+use gl::types::{GLenum, GLsizei, GLuint};
+use log::{info, warn};
+
+use crate::glhelper::utils::gl_query;
+
+const LOG_TARGET: &str = "Framebuffer";
+
+/// # Framebuffer
+/// Offscreen render target with a color texture and a depth buffer of the same size.
+/// The color texture is stored as sRGB, so the content matches the (sRGB-converted) output to the window.
+/// An HDR framebuffer stores linear floating point colors instead, which may exceed `1.0` and have to be tone mapped.
+/// A depth-only framebuffer (e.g. for shadow maps) stores the depth in a texture instead and has no color texture.
+/// A multisampled framebuffer stores the color in a renderbuffer, which has to be resolved with [`Framebuffer::blit_to`].
+pub struct Framebuffer {
+    fbo_id: GLuint,
+    /// OpenGL-Id of the color texture, `0` for depth-only and multisampled framebuffers
+    color_texture_id: GLuint,
+    /// OpenGL-Id of the color renderbuffer of multisampled framebuffers, `0` otherwise
+    color_rbo_id: GLuint,
+    /// OpenGL-Id of the depth renderbuffer, `0` for depth-only framebuffers
+    depth_rbo_id: GLuint,
+    /// OpenGL-Id of the depth texture of depth-only framebuffers, `0` otherwise
+    depth_texture_id: GLuint,
+    /// Size of the attachments in pixels as `(width, height)`
+    size: (u32, u32),
+    /// Samples per pixel of multisampled framebuffers, `0` otherwise
+    samples: u32,
+}
+
+impl Framebuffer {
+    /// # Constructor
+    /// Creates a framebuffer with attachments of the given size.
+    /// Fails if the size exceeds the limits of the driver or the framebuffer is incomplete.
+    pub fn new(width: u32, height: u32) -> Result<Framebuffer, String> {
+        Framebuffer::with_color_format(width, height, gl::SRGB8_ALPHA8, gl::UNSIGNED_BYTE)
+    }
+
+    /// # Constructor
+    /// Creates a framebuffer with a 16-bit floating point color texture (`GL_RGBA16F`) for HDR rendering
+    pub fn hdr(width: u32, height: u32) -> Result<Framebuffer, String> {
+        Framebuffer::with_color_format(width, height, gl::RGBA16F, gl::FLOAT)
+    }
+
+    /// Creates a framebuffer with a color texture of the given internal format and a depth renderbuffer
+    fn with_color_format(width: u32, height: u32, internal_format: GLenum, data_type: GLenum) -> Result<Framebuffer, String> {
+        let mut framebuffer = Framebuffer::empty(width, height)?;
+
+        let status = unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer.fbo_id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer.fbo_id);
+
+            gl::GenTextures(1, &mut framebuffer.color_texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, framebuffer.color_texture_id);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, internal_format as i32, width as GLsizei, height as GLsizei, 0, gl::RGBA, data_type, std::ptr::null());
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, framebuffer.color_texture_id, 0);
+
+            // The depth buffer needs exactly the size of the color attachment, otherwise the framebuffer is incomplete
+            gl::GenRenderbuffers(1, &mut framebuffer.depth_rbo_id);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, framebuffer.depth_rbo_id);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, width as GLsizei, height as GLsizei);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, framebuffer.depth_rbo_id);
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(format!("Framebuffer {} is incomplete (status 0x{:X})", framebuffer.fbo_id, status));
+        }
+        info!(target: LOG_TARGET, "Created {}x{} framebuffer {}", width, height, framebuffer.fbo_id);
+        Ok(framebuffer)
+    }
+
+    /// # Constructor
+    /// Creates a framebuffer with only a depth texture of the given size, which can be sampled afterwards.
+    /// Lookups outside of the texture return the largest depth, so they are never occluded.
+    pub fn depth_only(width: u32, height: u32) -> Result<Framebuffer, String> {
+        let mut framebuffer = Framebuffer::empty(width, height)?;
+
+        let status = unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer.fbo_id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer.fbo_id);
+
+            gl::GenTextures(1, &mut framebuffer.depth_texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, framebuffer.depth_texture_id);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::DEPTH_COMPONENT24 as i32, width as GLsizei, height as GLsizei, 0, gl::DEPTH_COMPONENT, gl::FLOAT, std::ptr::null());
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as i32);
+            let border = [1.0f32; 4];
+            gl::TexParameterfv(gl::TEXTURE_2D, gl::TEXTURE_BORDER_COLOR, border.as_ptr());
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::TEXTURE_2D, framebuffer.depth_texture_id, 0);
+
+            // Without a color attachment, no color may be written or read
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(format!("Framebuffer {} is incomplete (status 0x{:X})", framebuffer.fbo_id, status));
+        }
+        info!(target: LOG_TARGET, "Created {}x{} depth-only framebuffer {}", width, height, framebuffer.fbo_id);
+        Ok(framebuffer)
+    }
+
+    /// # Constructor
+    /// Creates a framebuffer with multisampled color and depth renderbuffers of the given size.
+    /// The sample count is clamped to the maximum of the driver. Fails if the driver does not support multisampling.
+    pub fn multisampled(width: u32, height: u32, samples: u32) -> Result<Framebuffer, String> {
+        let max_samples = gl_query::get_int(gl::MAX_SAMPLES).max(0) as u32;
+        if max_samples < 2 {
+            return Err("Multisampling is not supported by the driver".to_string());
+        }
+        let clamped_samples = samples.clamp(2, max_samples);
+        if clamped_samples != samples {
+            warn!(target: LOG_TARGET, "Requested {} samples, but the driver supports 2 to {}; using {}", samples, max_samples, clamped_samples);
+        }
+        let samples = clamped_samples;
+
+        let mut framebuffer = Framebuffer::empty(width, height)?;
+        framebuffer.samples = samples;
+
+        let status = unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer.fbo_id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer.fbo_id);
+
+            gl::GenRenderbuffers(1, &mut framebuffer.color_rbo_id);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, framebuffer.color_rbo_id);
+            gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, samples as GLsizei, gl::SRGB8_ALPHA8, width as GLsizei, height as GLsizei);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, framebuffer.color_rbo_id);
+
+            // All attachments need the same sample count, otherwise the framebuffer is incomplete
+            gl::GenRenderbuffers(1, &mut framebuffer.depth_rbo_id);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, framebuffer.depth_rbo_id);
+            gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, samples as GLsizei, gl::DEPTH_COMPONENT24, width as GLsizei, height as GLsizei);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, framebuffer.depth_rbo_id);
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(format!("Framebuffer {} is incomplete (status 0x{:X})", framebuffer.fbo_id, status));
+        }
+        info!(target: LOG_TARGET, "Created {}x{} framebuffer {} with {} samples", width, height, framebuffer.fbo_id, samples);
+        Ok(framebuffer)
+    }
+
+    /// Checks the size against the limits of the driver and creates a framebuffer without any OpenGL objects yet
+    fn empty(width: u32, height: u32) -> Result<Framebuffer, String> {
+        let max_size = gl_query::get_int(gl::MAX_RENDERBUFFER_SIZE).min(gl_query::get_int(gl::MAX_TEXTURE_SIZE)) as u32;
+        if width == 0 || height == 0 || width > max_size || height > max_size {
+            return Err(format!("Framebuffer size {}x{} is not within 1x1 and {}x{}", width, height, max_size, max_size));
+        }
+
+        Ok(Framebuffer {
+            fbo_id: 0,
+            color_texture_id: 0,
+            color_rbo_id: 0,
+            depth_rbo_id: 0,
+            depth_texture_id: 0,
+            size: (width, height),
+            samples: 0,
+        })
+    }
+
+    /// Binds the framebuffer as render target and sets the viewport to cover all of it
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo_id);
+            gl::Viewport(0, 0, self.size.0 as GLsizei, self.size.1 as GLsizei);
+        }
+    }
+
+    /// Binds the default framebuffer (the window) as render target again.
+    /// The viewport has to be restored by the caller.
+    pub fn unbind() {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    /// Copies the color and depth attachments that both framebuffers have into the target framebuffer.
+    /// This resolves the samples of a multisampled framebuffer, which requires both framebuffers to have the same size.
+    pub fn blit_to(&self, target: &Framebuffer) -> Result<(), String> {
+        if target.samples > 0 {
+            return Err("Cannot blit into a multisampled framebuffer".to_string());
+        }
+        if self.samples > 0 && self.size != target.size {
+            return Err(format!("Cannot resolve {}x{} samples into a {}x{} framebuffer",
+                self.size.0, self.size.1, target.size.0, target.size.1));
+        }
+
+        let mut mask = 0;
+        if self.has_color() && target.has_color() {
+            mask |= gl::COLOR_BUFFER_BIT;
+        }
+        if self.has_depth() && target.has_depth() {
+            mask |= gl::DEPTH_BUFFER_BIT;
+        }
+        // Depth values cannot be interpolated, so only scaled color blits may be filtered
+        let filter = if mask == gl::COLOR_BUFFER_BIT && self.size != target.size { gl::LINEAR } else { gl::NEAREST };
+
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo_id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target.fbo_id);
+            gl::BlitFramebuffer(
+                0, 0, self.size.0 as GLsizei, self.size.1 as GLsizei,
+                0, 0, target.size.0 as GLsizei, target.size.1 as GLsizei,
+                mask, filter,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        Ok(())
+    }
+
+    fn has_color(&self) -> bool {
+        self.color_texture_id != 0 || self.color_rbo_id != 0
+    }
+
+    fn has_depth(&self) -> bool {
+        self.depth_texture_id != 0 || self.depth_rbo_id != 0
+    }
+
+    /// Reads the color attachment as RGBA with 8 bits per channel.
+    /// The rows are ordered bottom to top, like OpenGL stores them.
+    /// Multisampled framebuffers have to be resolved into a plain framebuffer with [`Framebuffer::blit_to`] first.
+    pub fn read_pixels(&self) -> Vec<u8> {
+        let mut pixels = vec![0u8; self.size.0 as usize * self.size.1 as usize * 4];
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo_id);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(0, 0, self.size.0 as GLsizei, self.size.1 as GLsizei, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr().cast());
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        }
+        pixels
+    }
+
+    /// Binds the color texture to the given texture unit (e.g. `gl::TEXTURE0`), so it can be sampled after rendering
+    pub fn bind_color_texture(&self, texture_unit: GLenum) {
+        unsafe {
+            gl::ActiveTexture(texture_unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.color_texture_id);
+        }
+    }
+
+    /// Binds the depth texture of a depth-only framebuffer to the given texture unit (e.g. `gl::TEXTURE5`)
+    pub fn bind_depth_texture(&self, texture_unit: GLenum) {
+        unsafe {
+            gl::ActiveTexture(texture_unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.depth_texture_id);
+        }
+    }
+
+    /// Get the width of the attachments in pixels
+    pub fn width(&self) -> u32 {
+        self.size.0
+    }
+
+    /// Get the height of the attachments in pixels
+    pub fn height(&self) -> u32 {
+        self.size.1
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        info!(target: LOG_TARGET, "Deleting framebuffer {}", self.fbo_id);
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo_id);
+            gl::DeleteTextures(1, &self.color_texture_id);
+            gl::DeleteRenderbuffers(1, &self.color_rbo_id);
+            gl::DeleteRenderbuffers(1, &self.depth_rbo_id);
+            gl::DeleteTextures(1, &self.depth_texture_id);
+        }
+    }
+}
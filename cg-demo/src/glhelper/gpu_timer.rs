@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use gl::types::{GLint, GLuint, GLuint64};
+use log::{debug, info};
+
+const LOG_TARGET: &str = "GpuTimer";
+
+/// Amount of queries in flight. Results are read this many frames later, so reading them does not stall the pipeline.
+const QUERY_COUNT: usize = 3;
+/// Interval in which the average GPU time is logged
+const LOG_INTERVAL: Duration = Duration::from_secs(2);
+
+/// # GpuTimer
+/// Measures the GPU time of the commands between [`GpuTimer::begin`] and [`GpuTimer::end`] with `GL_TIME_ELAPSED` queries
+/// and periodically logs the average time per frame.
+pub struct GpuTimer {
+    /// Whether the average is logged periodically; otherwise it is only reset by [`GpuTimer::take_average`]
+    logging: bool,
+    queries: [GLuint; QUERY_COUNT],
+    /// Amount of started measurements
+    frame: usize,
+
+    /// Sum of the measured times since the last log in nanoseconds
+    elapsed_sum: u64,
+    /// Amount of measured times since the last log
+    elapsed_count: u32,
+    last_log: Instant,
+}
+
+impl GpuTimer {
+    /// # Constructor
+    /// Creates the queries. Fails if the driver does not support timer queries.
+    pub fn new() -> Result<GpuTimer, String> {
+        let mut counter_bits: GLint = 0;
+        unsafe {
+            gl::GetQueryiv(gl::TIME_ELAPSED, gl::QUERY_COUNTER_BITS, &mut counter_bits);
+        }
+        if counter_bits <= 0 {
+            return Err("Timer queries are not supported by the driver".to_string());
+        }
+
+        let mut queries = [0; QUERY_COUNT];
+        unsafe {
+            gl::GenQueries(QUERY_COUNT as gl::types::GLsizei, queries.as_mut_ptr());
+        }
+        debug!(target: LOG_TARGET, "Created timer queries {:?} with {} counter bits", queries, counter_bits);
+
+        Ok(GpuTimer {
+            logging: true,
+            queries,
+            frame: 0,
+            elapsed_sum: 0,
+            elapsed_count: 0,
+            last_log: Instant::now(),
+        })
+    }
+
+    /// Disables the periodic log, so the measured times are only collected for [`GpuTimer::take_average`]
+    pub fn without_logging(mut self) -> GpuTimer {
+        self.logging = false;
+        self
+    }
+
+    /// Returns the average GPU time per frame of the results collected since the last log or call, and starts a new average.
+    /// Returns `None` if no result was collected.
+    pub fn take_average(&mut self) -> Option<Duration> {
+        let count = std::mem::take(&mut self.elapsed_count);
+        let sum = std::mem::take(&mut self.elapsed_sum);
+        self.last_log = Instant::now();
+        (count > 0).then(|| Duration::from_nanos(sum / count as u64))
+    }
+
+    /// Starts the measurement of the current frame.
+    /// The result of the query that is reused is collected first, if it is available.
+    pub fn begin(&mut self) {
+        let query = self.queries[self.frame % QUERY_COUNT];
+        if self.frame >= QUERY_COUNT {
+            self.collect(query);
+        }
+
+        unsafe {
+            gl::BeginQuery(gl::TIME_ELAPSED, query);
+        }
+    }
+
+    /// Ends the measurement of the current frame
+    pub fn end(&mut self) {
+        unsafe {
+            gl::EndQuery(gl::TIME_ELAPSED);
+        }
+        self.frame += 1;
+
+        if self.logging && self.elapsed_count > 0 && self.last_log.elapsed() >= LOG_INTERVAL {
+            info!(target: LOG_TARGET, "GPU time: {:.3}ms per frame (average of {} frames)",
+                self.elapsed_sum as f64 / self.elapsed_count as f64 / 1_000_000.0, self.elapsed_count);
+            self.elapsed_sum = 0;
+            self.elapsed_count = 0;
+            self.last_log = Instant::now();
+        }
+    }
+
+    /// Adds the result of the given query to the statistics without waiting for it
+    fn collect(&mut self, query: GLuint) {
+        let mut available: GLint = 0;
+        unsafe {
+            gl::GetQueryObjectiv(query, gl::QUERY_RESULT_AVAILABLE, &mut available);
+        }
+        if available == 0 {
+            debug!(target: LOG_TARGET, "Result of timer query {} is not available yet, skipping it", query);
+            return;
+        }
+
+        let mut elapsed: GLuint64 = 0;
+        unsafe {
+            gl::GetQueryObjectui64v(query, gl::QUERY_RESULT, &mut elapsed);
+        }
+        self.elapsed_sum += elapsed;
+        self.elapsed_count += 1;
+    }
+}
+
+impl Drop for GpuTimer {
+    fn drop(&mut self) {
+        debug!(target: LOG_TARGET, "Deleting timer queries {:?}", self.queries);
+        unsafe {
+            gl::DeleteQueries(QUERY_COUNT as gl::types::GLsizei, self.queries.as_ptr());
+        }
+    }
+}
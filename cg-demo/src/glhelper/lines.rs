@@ -0,0 +1,89 @@
+use cgmath::Vector3;
+use gl::types::GLuint;
+use log::info;
+
+use crate::glhelper::utils::{calc_f32_offset, fill_vbo};
+
+const LOG_TARGET: &str = "Lines";
+
+/// Amount of `f32`-values per vertex (position + color)
+const FLOATS_PER_LINE_VERTEX: usize = 6;
+
+/// # Lines
+/// A set of colored line segments with their own VBO and VAO, drawn with an unlit line program (`shaders/util/line`).
+pub struct Lines {
+    vbo_id: GLuint,
+    vao_id: GLuint,
+    vertex_count: usize,
+}
+
+impl Lines {
+    /// # Constructor
+    /// Uploads the given segments as `(start, end, color)`
+    pub fn new(segments: &[(Vector3<f32>, Vector3<f32>, Vector3<f32>)]) -> Lines {
+        let data: Vec<f32> = segments.iter()
+            .flat_map(|(start, end, color)| [
+                start.x, start.y, start.z, color.x, color.y, color.z,
+                end.x, end.y, end.z, color.x, color.y, color.z,
+            ])
+            .collect();
+
+        let mut vbo_id: GLuint = 0;
+        let mut vao_id: GLuint = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut vbo_id);
+        }
+        fill_vbo(vbo_id, &data);
+
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao_id);
+            gl::BindVertexArray(vao_id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo_id);
+
+            // Configure the following layout:
+            //   layout (location = 0) in vec3  inPos;
+            //   layout (location = 1) in vec3  inColor;
+            let stride = (FLOATS_PER_LINE_VERTEX * std::mem::size_of::<f32>()) as gl::types::GLint;
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride, calc_f32_offset(3));
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+        info!(target: LOG_TARGET, "Created {} line segments in VBO {}", segments.len(), vbo_id);
+
+        Lines { vbo_id, vao_id, vertex_count: segments.len() * 2 }
+    }
+
+    /// # Constructor
+    /// Creates the coordinate axes at the origin with the given length, colored red/green/blue for x/y/z
+    pub fn axes(length: f32) -> Lines {
+        let origin = cgmath::vec3(0.0, 0.0, 0.0);
+        Lines::new(&[
+            (origin, cgmath::vec3(length, 0.0, 0.0), cgmath::vec3(1.0, 0.0, 0.0)),
+            (origin, cgmath::vec3(0.0, length, 0.0), cgmath::vec3(0.0, 1.0, 0.0)),
+            (origin, cgmath::vec3(0.0, 0.0, length), cgmath::vec3(0.0, 0.0, 1.0)),
+        ])
+    }
+
+    /// Draws the lines with the currently active program
+    pub fn draw(&self) {
+        unsafe {
+            gl::BindVertexArray(self.vao_id);
+            gl::DrawArrays(gl::LINES, 0, self.vertex_count as gl::types::GLsizei);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for Lines {
+    fn drop(&mut self) {
+        info!(target: LOG_TARGET, "Deleting line VBO {}", self.vbo_id);
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao_id);
+            gl::DeleteBuffers(1, &self.vbo_id);
+        }
+    }
+}
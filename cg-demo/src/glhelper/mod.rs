@@ -1,17 +1,41 @@
 extern crate image;
 
 pub mod camera;
+pub mod camera_path;
+pub mod cube_map;
+pub mod floor_grid;
+pub mod framebuffer;
+pub mod gpu_timer;
+pub mod lines;
 pub mod program;
+pub mod screen_quad;
 pub mod shader;
+pub mod skybox;
+pub mod static_mesh;
 pub mod tex_collection;
 pub mod tex_data;
+pub mod text_overlay;
+pub mod uniform_buffer;
 pub mod utils;
 
 pub use camera::{
     MovementDirection,
-    Camera
+    Camera,
+    CameraPose,
+    GroundPlane,
 };
-pub use program::Program;
+pub use camera_path::CameraPath;
+pub use cube_map::CubeMap;
+pub use floor_grid::FloorGrid;
+pub use framebuffer::Framebuffer;
+pub use gpu_timer::GpuTimer;
+pub use lines::Lines;
+pub use program::{Program, ProgramBuilder, ProgramSources};
+pub use screen_quad::ScreenQuad;
 pub use shader::Shader;
+pub use skybox::Skybox;
+pub use static_mesh::StaticMesh;
 pub use tex_collection::TextureCollection;
-pub use tex_data::TextureData;
+pub use tex_data::{TextureData, TextureOptions};
+pub use text_overlay::{TextLine, TextOverlay};
+pub use uniform_buffer::{PerFrame, UniformBuffer, PER_FRAME_BINDING, PER_FRAME_BLOCK};
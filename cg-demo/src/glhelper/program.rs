@@ -1,18 +1,46 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::time::{Duration, SystemTime};
+
 use cgmath::{Array, Matrix, Matrix4, Vector3};
-use gl::types::{GLint, GLuint};
-use log::{debug, info, trace};
+use gl::types::{GLenum, GLint, GLsizei, GLuint};
+use log::{debug, info, Level, log_enabled, trace, warn};
 
 use crate::glhelper::Shader;
-use crate::glhelper::utils::create_whitespace_cstring_with_len;
+use crate::glhelper::shader::ShaderSource;
+use crate::glhelper::utils::{create_whitespace_cstring_with_len, gl_query};
 use crate::Resources;
 
 const LOG_TARGET: &str = "GlProgram";
 
+/// Folder in the resources where linked program binaries are cached
+const CACHE_DIR: &str = "cache";
+/// Size of the cache file header: source hash (u64) + binary format (u32)
+const CACHE_HEADER_LEN: usize = 12;
+/// Offset basis and prime of the 64 bit FNV-1a hash
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Shader stages that every program consists of
+const REQUIRED_STAGES: [&str; 2] = [".vert", ".frag"];
+/// Shader stages that are added to a program, if they exist in the resources
+const OPTIONAL_STAGES: [&str; 2] = [".tesc", ".tese"];
+
+/// Time a changed source file has to stay unchanged before the program is reloaded,
+/// so a file is not compiled while it is still being written
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Resource name of a shader source with its modification time when the program was created (if known)
+type SourceFile = (String, Option<SystemTime>);
+
 /// # Program
 /// Handle for an OpenGL-Program.
 /// Used to select shaders and transfer data to the shaders
 pub struct Program {
     id: GLuint,
+    /// Whether the program contains tessellation stages and therefore has to be used with `gl::PATCHES`
+    tessellated: bool,
+    /// Shader sources the program was created from, empty if it was created from already compiled shaders
+    sources: Vec<SourceFile>,
 }
 
 impl Program {
@@ -28,7 +56,10 @@ impl Program {
         }
 
         debug!(target: LOG_TARGET, "Linking shaders in program {}", program_id);
-        unsafe { gl::LinkProgram(program_id); }
+        unsafe {
+            gl::ProgramParameteri(program_id, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as GLint);
+            gl::LinkProgram(program_id);
+        }
 
         let mut success: GLint = 1;
         unsafe {
@@ -59,25 +90,185 @@ impl Program {
             unsafe { gl::DetachShader(program_id, shader.id()); }
         }
 
-        Ok(Program { id: program_id })
+        let tessellated = shaders.iter()
+            .any(|shader| matches!(shader.kind(), gl::TESS_CONTROL_SHADER | gl::TESS_EVALUATION_SHADER));
+        Ok(Program { id: program_id, tessellated, sources: Vec::new() })
     }
 
     /// # Constructor
-    /// Creates a new program associated with all shaders that have the given name
+    /// Creates a new program associated with all shaders that have the given name.
+    /// A vertex and a fragment shader are required, tessellation shaders are optional.
+    /// Other combinations of stages can be created with a [`ProgramBuilder`].
     pub fn from_res(res: &Resources, name: &str) -> Result<Program, String> {
-        const POSSIBLE_EXT: [&str; 2] = [
-            ".vert",
-            ".frag",
-        ];
-
         info!(target: LOG_TARGET, "Creating program with shaders \"{}\"", name);
-        let shaders = POSSIBLE_EXT.iter()
-            .map(|file_extension| {
-                Shader::from_res(res, &format!("{}{}", name, file_extension))
-            })
-            .collect::<Result<Vec<Shader>, String>>()?;
+        program_stages(res, name).iter()
+            .try_fold(ProgramBuilder::new(), |builder, file_extension| {
+                builder.with_res(res, &format!("{}{}", name, file_extension))
+            })?
+            .build()
+    }
+
+    /// # Constructor
+    /// Creates a new program from a vertex and a fragment shader with independent resource names
+    /// (e.g. `shaders/common.vert` and `shaders/phong.frag`), so one shader can be shared by several programs.
+    pub fn from_files(res: &Resources, vert_name: &str, frag_name: &str) -> Result<Program, String> {
+        info!(target: LOG_TARGET, "Creating program with shaders \"{}\" and \"{}\"", vert_name, frag_name);
+        ProgramBuilder::new()
+            .with_res(res, vert_name)?
+            .with_res(res, frag_name)?
+            .build()
+    }
+
+    /// # Constructor
+    /// Creates a standalone compute program from the compute shader `<name>.comp`.
+    /// Compute shaders require OpenGL 4.3 or newer, so an error is returned for older contexts.
+    pub fn from_compute(res: &Resources, name: &str) -> Result<Program, String> {
+        let (major, minor) = gl_version();
+        if (major, minor) < (4, 3) {
+            return Err(format!("Compute shaders require OpenGL 4.3, but the context only supports {}.{}", major, minor));
+        }
+
+        info!(target: LOG_TARGET, "Creating compute program with shader \"{}\"", name);
+        ProgramBuilder::new()
+            .with_res(res, &format!("{}.comp", name))?
+            .build()
+    }
+
+    /// # Constructor
+    /// Creates a new program like [`Program::from_res`], but reuses a cached program binary if the shader sources did not change.
+    /// After a full compilation, the linked binary is written to the cache for the next start.
+    pub fn from_res_cached(res: &Resources, name: &str) -> Result<Program, String> {
+        if !program_binaries_supported() {
+            debug!(target: LOG_TARGET, "Program binaries are not supported by the driver");
+            return Program::from_res(res, name);
+        }
+
+        let stages = program_stages(res, name);
+        let tessellated = stages.iter().any(|stage| OPTIONAL_STAGES.contains(stage));
+        // The included files are part of the hash, so changing them invalidates the cached binary as well
+        let sources = stages.iter()
+            .map(|file_extension| ShaderSource::from_res(res, &format!("{}{}", name, file_extension)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let source_hash = binary_cache_key(sources.iter().map(|source| source.code.as_bytes()), &driver_description());
+        let cache_name = format!("{}/{}.bin", CACHE_DIR, name.replace('/', "_"));
+
+        if let Some(mut program) = Program::from_cached_binary(res, &cache_name, source_hash, tessellated) {
+            program.sources = sources.iter()
+                .flat_map(|source| &source.files)
+                .map(|file| source_file(res, file))
+                .collect();
+            return Ok(program);
+        }
+
+        let program = Program::from_res(res, name)?;
+        if let Err(e) = program.store_binary(res, &cache_name, source_hash) {
+            warn!(target: LOG_TARGET, "Could not cache binary of program {}: {}", program.id, e);
+        }
+        Ok(program)
+    }
+
+    /// # Constructor
+    /// Creates a new program from the given shaders, using a cached binary for programs with a common name
+    /// (see [`Program::from_res_cached`] and [`Program::from_files`]).
+    pub fn from_sources(res: &Resources, sources: ProgramSources) -> Result<Program, String> {
+        match sources {
+            ProgramSources::Named(name) => Program::from_res_cached(res, name),
+            ProgramSources::Files(vert_name, frag_name) => Program::from_files(res, vert_name, frag_name),
+        }
+    }
+
+    /// Recompiles the program from the resources (see [`Program::from_sources`]).
+    /// The current program is replaced only if the new one was compiled and linked successfully,
+    /// otherwise it is kept, so it stays usable.
+    pub fn reload(&mut self, res: &Resources, sources: ProgramSources) -> Result<(), String> {
+        let mut new_program = Program::from_sources(res, sources)?;
+        info!(target: LOG_TARGET, "Reloaded program \"{}\" as program {}, replacing program {}", sources, new_program.id, self.id);
+
+        // The previous program is deleted when `new_program` is dropped
+        std::mem::swap(self, &mut new_program);
+        Ok(())
+    }
+
+    /// Checks whether a shader source of the program was modified since the program was created.
+    /// Changes are only reported once the file stayed unchanged for a moment, so it is not read while being written.
+    pub fn needs_reload(&self, res: &Resources) -> bool {
+        self.sources.iter().any(|(name, known_time)| {
+            let modified = match res.modified_time(name) {
+                Ok(modified) => modified,
+                Err(_) => return false,
+            };
+            let settled = modified.elapsed().map_or(true, |age| age >= RELOAD_DEBOUNCE);
+            Some(modified) != *known_time && settled
+        })
+    }
+
+    /// Remembers the current modification times of the shader sources, so [`Program::needs_reload`] ignores the current changes.
+    /// Used after a failed reload, to only try again once the sources are changed again.
+    pub fn accept_source_changes(&mut self, res: &Resources) {
+        for (name, known_time) in self.sources.iter_mut() {
+            *known_time = res.modified_time(name).ok();
+        }
+    }
+
+    /// Load the program from the cached binary, if it exists and matches the given hash of the sources
+    fn from_cached_binary(res: &Resources, cache_name: &str, source_hash: u64, tessellated: bool) -> Option<Program> {
+        let data = res.load_bytes(cache_name).ok()?;
+        if data.len() <= CACHE_HEADER_LEN {
+            return None;
+        }
+
+        let cached_hash = u64::from_le_bytes(data[0..8].try_into().ok()?);
+        let format = u32::from_le_bytes(data[8..12].try_into().ok()?) as GLenum;
+        if cached_hash != source_hash {
+            debug!(target: LOG_TARGET, "Cached binary \"{}\" is outdated", cache_name);
+            return None;
+        }
+
+        let binary = &data[CACHE_HEADER_LEN..];
+        let program_id = unsafe { gl::CreateProgram() };
+        let mut success: GLint = 0;
+        unsafe {
+            gl::ProgramBinary(program_id, format, binary.as_ptr().cast(), binary.len() as GLsizei);
+            gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut success);
+        }
+
+        if success == 0 {
+            warn!(target: LOG_TARGET, "Driver rejected cached binary \"{}\", recompiling", cache_name);
+            unsafe { gl::DeleteProgram(program_id); }
+            return None;
+        }
+
+        info!(target: LOG_TARGET, "Loaded program {} from cached binary \"{}\"", program_id, cache_name);
+        Some(Program { id: program_id, tessellated, sources: Vec::new() })
+    }
+
+    /// Write the linked binary of the program to the cache
+    fn store_binary(&self, res: &Resources, cache_name: &str, source_hash: u64) -> Result<(), String> {
+        let mut len: GLint = 0;
+        unsafe {
+            gl::GetProgramiv(self.id, gl::PROGRAM_BINARY_LENGTH, &mut len);
+        }
+        if len <= 0 {
+            return Err("Program binary is empty".to_string());
+        }
 
-        Program::from_shaders(&shaders[..])
+        let mut binary: Vec<u8> = vec![0; len as usize];
+        let mut written: GLsizei = 0;
+        let mut format: GLenum = 0;
+        unsafe {
+            gl::GetProgramBinary(self.id, len, &mut written, &mut format, binary.as_mut_ptr().cast());
+        }
+        binary.truncate(written as usize);
+
+        let mut data = Vec::with_capacity(CACHE_HEADER_LEN + binary.len());
+        data.extend_from_slice(&source_hash.to_le_bytes());
+        data.extend_from_slice(&format.to_le_bytes());
+        data.extend_from_slice(&binary);
+
+        res.write_bytes(cache_name, &data)
+            .map_err(|e| format!("Error writing resource {}: {:?}", cache_name, e))?;
+        debug!(target: LOG_TARGET, "Cached binary of program {} as \"{}\"", self.id, cache_name);
+        Ok(())
     }
 
     /// Get id of the program
@@ -85,6 +276,11 @@ impl Program {
         self.id
     }
 
+    /// Check whether the program contains tessellation stages, so it has to be used with `gl::PATCHES`
+    pub fn is_tessellated(&self) -> bool {
+        self.tessellated
+    }
+
     /// Activate the program
     pub fn set_active(&self) {
         trace!(target: LOG_TARGET, "Setting program {} active.", self.id);
@@ -93,9 +289,20 @@ impl Program {
         }
     }
 
+    /// Runs the compute program with the given amount of work groups.
+    /// Waits for the image writes to finish, so the results can be sampled or read by the following commands.
+    pub fn dispatch(&self, groups_x: u32, groups_y: u32, groups_z: u32) {
+        debug!(target: LOG_TARGET, "Dispatching program {} with {}x{}x{} work groups", self.id, groups_x, groups_y, groups_z);
+        self.set_active();
+        unsafe {
+            gl::DispatchCompute(groups_x, groups_y, groups_z);
+            gl::MemoryBarrier(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT | gl::TEXTURE_FETCH_BARRIER_BIT);
+        }
+    }
+
     /// Setting an `int`-Property for the shaders
     pub fn set_property_int(&self, property_name: &str, value: i32) {
-        trace!(target: LOG_TARGET, "Setting property \"{}\" for program {} to {:?}.", property_name, self.id, value);
+        self.trace_property(property_name, &value);
         unsafe {
             gl::Uniform1i(self.get_uniform_location(property_name).unwrap(), value);
         }
@@ -103,15 +310,31 @@ impl Program {
 
     /// Setting an `uint`-Property for the shaders
     pub fn set_property_uint(&self, property_name: &str, value: u32) {
-        trace!(target: LOG_TARGET, "Setting property \"{}\" for program {} to {:?}.", property_name, self.id, value);
+        self.trace_property(property_name, &value);
         unsafe {
             gl::Uniform1ui(self.get_uniform_location(property_name).unwrap(), value);
         }
     }
 
+    /// Setting a `float`-Property for the shaders
+    pub fn set_property_float(&self, property_name: &str, value: f32) {
+        self.trace_property(property_name, &value);
+        unsafe {
+            gl::Uniform1f(self.get_uniform_location(property_name).unwrap(), value);
+        }
+    }
+
+    /// Setting a `bool`-Property for the shaders
+    pub fn set_property_bool(&self, property_name: &str, value: bool) {
+        self.trace_property(property_name, &value);
+        unsafe {
+            gl::Uniform1i(self.get_uniform_location(property_name).unwrap(), value as i32);
+        }
+    }
+
     /// Setting an `mat4`-Property for the shaders
     pub fn set_property_mat4(&self, property_name: &str, value: &Matrix4<f32>) {
-        trace!(target: LOG_TARGET, "Setting property \"{}\" for program {} to {:?}.", property_name, self.id, value);
+        self.trace_property(property_name, &value);
         unsafe {
             gl::UniformMatrix4fv(self.get_uniform_location(property_name).unwrap(), 1, gl::FALSE, value.as_ptr());
         }
@@ -119,12 +342,96 @@ impl Program {
 
     /// Setting an `vec3`-Property for the shaders
     pub fn set_property_vec3(&self, property_name: &str, value: &Vector3<f32>) {
-        trace!(target: LOG_TARGET, "Setting property \"{}\" for program {} to {:?}.", property_name, self.id, value);
+        self.trace_property(property_name, &value);
         unsafe {
             gl::Uniform3fv(self.get_uniform_location(property_name).unwrap(), 1, value.as_ptr());
         }
     }
 
+    /// Logs the new value of a property.
+    /// The setters are called for every frame, so the (matrix-)formatting is skipped entirely unless tracing is enabled.
+    fn trace_property(&self, property_name: &str, value: &dyn Debug) {
+        if log_enabled!(target: LOG_TARGET, Level::Trace) {
+            trace!(target: LOG_TARGET, "Setting property \"{}\" for program {} to {:?}.", property_name, self.id, value);
+        }
+    }
+
+    /// Query all active uniforms of the program as `(name, type, location)`.
+    /// Uniforms that are declared but unused in the shaders are optimized out by the driver and therefore not listed.
+    pub fn active_uniforms(&self) -> Vec<(String, GLenum, GLint)> {
+        let mut count: GLint = 0;
+        let mut max_len: GLint = 0;
+        unsafe {
+            gl::GetProgramiv(self.id, gl::ACTIVE_UNIFORMS, &mut count);
+            gl::GetProgramiv(self.id, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_len);
+        }
+
+        (0..count.max(0) as GLuint)
+            .map(|index| {
+                let mut buffer: Vec<u8> = vec![0; max_len.max(1) as usize];
+                let mut len: GLsizei = 0;
+                let mut size: GLint = 0;
+                let mut kind: GLenum = 0;
+                unsafe {
+                    gl::GetActiveUniform(self.id, index, max_len, &mut len, &mut size, &mut kind, buffer.as_mut_ptr().cast());
+                }
+                buffer.truncate(len.max(0) as usize);
+
+                let name = String::from_utf8_lossy(&buffer).into_owned();
+                let location = self.get_uniform_location(&name).unwrap_or(-1);
+                (name, kind, location)
+            })
+            .collect()
+    }
+
+    /// Check whether the shaders of the program declare (and use) the given property.
+    /// Members of uniform blocks have no location, but are active uniforms of the program as well.
+    pub fn has_property(&self, property_name: &str) -> bool {
+        let has_location = self.get_uniform_location(property_name)
+            .map(|location| location != -1)
+            .unwrap_or(false);
+        has_location || self.has_block_member(property_name)
+    }
+
+    /// Attaches the uniform block with the given name to a binding point of the uniform buffers.
+    /// Returns `false` if the shaders of the program do not declare (or use) the block.
+    pub fn bind_uniform_block(&self, block_name: &str, binding: GLuint) -> bool {
+        let name = match std::ffi::CString::new(block_name) {
+            Ok(name) => name,
+            Err(_) => return false,
+        };
+        unsafe {
+            let index = gl::GetUniformBlockIndex(self.id, name.as_bytes_with_nul().as_ptr().cast());
+            if index == gl::INVALID_INDEX {
+                return false;
+            }
+            gl::UniformBlockBinding(self.id, index, binding);
+        }
+        true
+    }
+
+    /// Check whether the given property is an active member of a uniform block
+    fn has_block_member(&self, property_name: &str) -> bool {
+        let name = match std::ffi::CString::new(property_name) {
+            Ok(name) => name,
+            Err(_) => return false,
+        };
+        let name_ptr: *const gl::types::GLchar = name.as_bytes_with_nul().as_ptr().cast();
+        let mut index: GLuint = gl::INVALID_INDEX;
+        unsafe {
+            gl::GetUniformIndices(self.id, 1, &name_ptr, &mut index);
+        }
+        index != gl::INVALID_INDEX
+    }
+
+    /// Filter the given property names for the ones that are not declared (or not used) by the shaders of the program
+    pub fn missing_properties<'a>(&self, property_names: &[&'a str]) -> Vec<&'a str> {
+        property_names.iter()
+            .filter(|property_name| !self.has_property(property_name))
+            .copied()
+            .collect()
+    }
+
     /// Resolve the property name to a memory-location
     fn get_uniform_location(&self, property_name: &str) -> Result<GLint, String> {
         let name = std::ffi::CString::new(property_name)
@@ -135,6 +442,160 @@ impl Program {
     }
 }
 
+/// # ProgramSources
+/// Resource names of the shaders a program is created from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramSources<'a> {
+    /// All shaders with the common name (see [`Program::from_res`])
+    Named(&'a str),
+    /// A vertex and a fragment shader with independent names (see [`Program::from_files`])
+    Files(&'a str, &'a str),
+}
+
+impl Display for ProgramSources<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProgramSources::Named(name) => write!(f, "{}", name),
+            ProgramSources::Files(vert_name, frag_name) => write!(f, "{} + {}", vert_name, frag_name),
+        }
+    }
+}
+
+/// # ProgramBuilder
+/// Collects the shaders of a program with an arbitrary set of stages.
+/// The stages are validated before linking: a program needs either a vertex and a fragment shader or a single compute shader.
+///
+/// ```ignore
+/// let program = ProgramBuilder::new()
+///     .with_res(res, "shaders/terrain.vert")?
+///     .with_res(res, "shaders/terrain.tese")?
+///     .with_res(res, "shaders/base.frag")?
+///     .build()?;
+/// ```
+#[derive(Default)]
+pub struct ProgramBuilder {
+    shaders: Vec<Shader>,
+    sources: Vec<SourceFile>,
+}
+
+impl ProgramBuilder {
+    /// # Constructor
+    /// Creates a builder without any shaders
+    pub fn new() -> ProgramBuilder {
+        ProgramBuilder::default()
+    }
+
+    /// Adds an already compiled shader
+    pub fn with_shader(mut self, shader: Shader) -> ProgramBuilder {
+        self.shaders.push(shader);
+        self
+    }
+
+    /// Compiles the shader with the given resource name and adds it.
+    /// The stage is determined by the file extension (see [`Shader::from_res`]).
+    /// The shader and all files it includes are watched for changes (see [`Program::needs_reload`]).
+    pub fn with_res(mut self, res: &Resources, name: &str) -> Result<ProgramBuilder, String> {
+        let source = ShaderSource::from_res(res, name)?;
+        // The times are determined before compiling, so changes during the compilation are detected afterwards
+        self.sources.extend(source.files.iter().map(|file| source_file(res, file)));
+        Ok(self.with_shader(Shader::from_shader_source(name, &source)?))
+    }
+
+    /// Validates the combination of stages and links the program
+    pub fn build(self) -> Result<Program, String> {
+        self.validate()?;
+        let mut program = Program::from_shaders(&self.shaders)?;
+        program.sources = self.sources;
+        Ok(program)
+    }
+
+    /// Checks that every stage is given at most once and the stages form a complete pipeline
+    fn validate(&self) -> Result<(), String> {
+        let has_stage = |kind| self.shaders.iter().any(|shader| shader.kind() == kind);
+
+        for (idx, shader) in self.shaders.iter().enumerate() {
+            if self.shaders[..idx].iter().any(|other| other.kind() == shader.kind()) {
+                return Err(format!("The program contains more than one {} shader", stage_name(shader.kind())));
+            }
+        }
+
+        if has_stage(gl::COMPUTE_SHADER) {
+            if self.shaders.len() > 1 {
+                return Err("A compute shader can not be combined with other stages".to_string());
+            }
+            return Ok(());
+        }
+        if !has_stage(gl::VERTEX_SHADER) || !has_stage(gl::FRAGMENT_SHADER) {
+            return Err("The program needs a vertex and a fragment shader (or a single compute shader)".to_string());
+        }
+        if has_stage(gl::TESS_CONTROL_SHADER) && !has_stage(gl::TESS_EVALUATION_SHADER) {
+            return Err("A tessellation control shader needs a tessellation evaluation shader".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Determines the current modification time of the given shader source
+fn source_file(res: &Resources, name: &str) -> SourceFile {
+    (name.to_string(), res.modified_time(name).ok())
+}
+
+/// Readable name of the given shader stage
+fn stage_name(kind: GLenum) -> &'static str {
+    match kind {
+        gl::VERTEX_SHADER => "vertex",
+        gl::TESS_CONTROL_SHADER => "tessellation control",
+        gl::TESS_EVALUATION_SHADER => "tessellation evaluation",
+        gl::GEOMETRY_SHADER => "geometry",
+        gl::FRAGMENT_SHADER => "fragment",
+        gl::COMPUTE_SHADER => "compute",
+        _ => "unknown",
+    }
+}
+
+/// Checks whether the driver supports at least one format for program binaries
+fn program_binaries_supported() -> bool {
+    gl_query::get_int(gl::NUM_PROGRAM_BINARY_FORMATS) > 0
+}
+
+/// Describes the driver (vendor, renderer and version), whose binaries are only valid for the same driver
+fn driver_description() -> String {
+    [gl::VENDOR, gl::RENDERER, gl::VERSION].into_iter()
+        .map(|name| gl_query::get_string(name).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Computes the key of a cached program binary from the shader sources and the driver description.
+/// Uses FNV-1a, which (unlike the hasher of the standard library) gives the same key across builds and Rust versions.
+fn binary_cache_key<'a>(sources: impl Iterator<Item=&'a [u8]>, driver: &'a str) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut add_bytes = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+    // The lengths separate the parts, so moving text from one stage to the next changes the key
+    for source in sources.chain(std::iter::once(driver.as_bytes())) {
+        add_bytes(&(source.len() as u64).to_le_bytes());
+        add_bytes(source);
+    }
+    hash
+}
+
+/// Determines the shader stages (file extensions) of the program with the given name
+fn program_stages(res: &Resources, name: &str) -> Vec<&'static str> {
+    let optional_stages = OPTIONAL_STAGES.into_iter()
+        .filter(|file_extension| res.load_bytes(&format!("{}{}", name, file_extension)).is_ok());
+    REQUIRED_STAGES.into_iter().chain(optional_stages).collect()
+}
+
+/// Queries the version of the current OpenGL context as `(major, minor)`
+fn gl_version() -> (i32, i32) {
+    (gl_query::get_int(gl::MAJOR_VERSION), gl_query::get_int(gl::MINOR_VERSION))
+}
+
 impl Drop for Program {
     fn drop(&mut self) {
         info!(target: LOG_TARGET, "Deleting program {}", self.id);
@@ -143,3 +604,27 @@ impl Drop for Program {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DRIVER: &str = "Vendor\nRenderer\n4.1";
+
+    fn key(sources: &[&str], driver: &str) -> u64 {
+        binary_cache_key(sources.iter().map(|source| source.as_bytes()), driver)
+    }
+
+    #[test]
+    fn binary_cache_key_is_stable() {
+        // The key is stored in the cache files, so it must not change between builds
+        assert_eq!(key(&["void main() {}", "void main() {}"], DRIVER), 0x87877842f5fe3636);
+    }
+
+    #[test]
+    fn binary_cache_key_depends_on_stages_and_driver() {
+        let ab_c = key(&["ab", "c"], DRIVER);
+        assert_ne!(ab_c, key(&["a", "bc"], DRIVER));
+        assert_ne!(ab_c, key(&["ab", "c"], "Vendor\nRenderer\n4.6"));
+    }
+}
@@ -0,0 +1,83 @@
+use gl::types::GLuint;
+use log::info;
+
+use crate::glhelper::utils::{calc_f32_offset, fill_vbo};
+
+const LOG_TARGET: &str = "ScreenQuad";
+
+/// # ScreenQuad
+/// Two triangles covering the whole viewport, used to draw images (e.g. textures or framebuffers) to the screen.
+/// The vertices only contain positions in normalized device coordinates and texture coordinates.
+pub struct ScreenQuad {
+    vbo_id: GLuint,
+    vao_id: GLuint,
+}
+
+impl ScreenQuad {
+    /// # Constructor
+    /// Creates the VBO and VAO of the quad
+    pub fn new() -> ScreenQuad {
+        let data: Vec<f32> = vec![
+            // position  // tex.-coords
+            -1.0, -1.0,  0.0, 0.0,
+            1.0, -1.0,   1.0, 0.0,
+            1.0, 1.0,    1.0, 1.0,
+            -1.0, -1.0,  0.0, 0.0,
+            1.0, 1.0,    1.0, 1.0,
+            -1.0, 1.0,   0.0, 1.0,
+        ];
+
+        let mut vbo_id: GLuint = 0;
+        let mut vao_id: GLuint = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut vbo_id);
+        }
+        fill_vbo(vbo_id, &data);
+
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao_id);
+            gl::BindVertexArray(vao_id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo_id);
+
+            // Configure the following layout:
+            //   layout (location = 0) in vec2  inPos;
+            //   layout (location = 1) in vec2  inTexCoords;
+            let stride = (4 * std::mem::size_of::<f32>()) as gl::types::GLint;
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, stride, calc_f32_offset(2));
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+        info!(target: LOG_TARGET, "Created screen quad in VBO {}", vbo_id);
+
+        ScreenQuad { vbo_id, vao_id }
+    }
+
+    /// Draws the quad with the currently active program
+    pub fn draw(&self) {
+        unsafe {
+            gl::BindVertexArray(self.vao_id);
+            gl::DrawArrays(gl::TRIANGLES, 0, 6);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Default for ScreenQuad {
+    fn default() -> Self {
+        ScreenQuad::new()
+    }
+}
+
+impl Drop for ScreenQuad {
+    fn drop(&mut self) {
+        info!(target: LOG_TARGET, "Deleting screen quad VBO {}", self.vbo_id);
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao_id);
+            gl::DeleteBuffers(1, &self.vbo_id);
+        }
+    }
+}
@@ -1,17 +1,102 @@
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 
 use gl::types::{GLenum, GLuint};
-use log::info;
+use log::{info, warn};
 
 use crate::glhelper::utils::compile_shader_from_source;
 use crate::Resources;
 
 const LOG_TARGET: &str = "Shader";
 
+/// Highest GLSL version supported by the OpenGL 4.5 core context
+const MAX_GLSL_VERSION: u32 = 450;
+
+/// Directive that inserts another source file, e.g. `#include "include/lighting.glsl"`
+const INCLUDE_DIRECTIVE: &str = "#include";
+
+/// # ShaderSource
+/// Source code of a shader with the content of all included files inserted.
+/// Included files are resolved relative to the folder of the file containing the `#include` directive.
+/// They may include further files and can be shared by several shaders (e.g. common lighting functions),
+/// or even be a complete shader of the same stage, so two programs use the same code.
+#[derive(Debug)]
+pub struct ShaderSource {
+    /// Complete source code, which can be compiled
+    pub code: String,
+    /// Resource names of the shader and of all files included by it
+    pub files: Vec<String>,
+}
+
+impl ShaderSource {
+    /// # Constructor
+    /// Loads the shader with the given resource name and inserts all included files
+    pub fn from_res(res: &Resources, name: &str) -> Result<ShaderSource, String> {
+        ShaderSource::load(name, &|file| {
+            let data = res.load_bytes(file)
+                .map_err(|e| format!("Error loading resource {}: {:?}", file, e))?;
+            String::from_utf8(data).map_err(|e| format!("Resource {} is not valid UTF-8: {}", file, e))
+        })
+    }
+
+    /// Loads the shader with the given name using the given function to read the content of a file
+    fn load(name: &str, load_file: &dyn Fn(&str) -> Result<String, String>) -> Result<ShaderSource, String> {
+        let mut source = ShaderSource { code: String::new(), files: Vec::new() };
+        source.append(name, load_file, &mut Vec::new())?;
+        Ok(source)
+    }
+
+    /// Appends the content of the given file and replaces its include directives recursively.
+    /// `including` contains the files that are currently inserted, so cyclic includes are detected.
+    fn append(&mut self, name: &str, load_file: &dyn Fn(&str) -> Result<String, String>, including: &mut Vec<String>) -> Result<(), String> {
+        if including.iter().any(|file| file == name) {
+            return Err(format!("Cyclic include of \"{}\" ({} -> {})", name, including.join(" -> "), name));
+        }
+        let content = load_file(name)?;
+        if !self.files.iter().any(|file| file == name) {
+            self.files.push(name.to_string());
+        }
+
+        including.push(name.to_string());
+        for (idx, line) in content.lines().enumerate() {
+            let Some(directive) = line.trim_start().strip_prefix(INCLUDE_DIRECTIVE) else {
+                self.code.push_str(line);
+                self.code.push('\n');
+                continue;
+            };
+            let include = directive.trim().strip_prefix('"').and_then(|path| path.strip_suffix('"'))
+                .ok_or_else(|| format!("{}:{}: Expected a quoted path after {}", name, idx + 1, INCLUDE_DIRECTIVE))?;
+            self.append(&resolve_include(name, include), load_file, including)
+                .map_err(|e| format!("{}:{}: {}", name, idx + 1, e))?;
+            // The compiler reports errors with the line numbers of the including file again after the included content
+            self.code.push_str(&format!("#line {}\n", idx + 2));
+        }
+        including.pop();
+        Ok(())
+    }
+}
+
+/// Determines the resource name of a file included by the given shader.
+/// The path is relative to the folder of the shader and may contain `..` to refer to parent folders.
+fn resolve_include(including_name: &str, include: &str) -> String {
+    let mut parts: Vec<&str> = including_name.split('/').collect();
+    parts.pop();
+    for part in include.split('/') {
+        match part {
+            "." | "" => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
 /// # Shader
 /// Handle for an OpenGL-Shader
 pub struct Shader {
     id: GLuint,
+    kind: GLenum,
 }
 
 impl Shader {
@@ -23,16 +108,26 @@ impl Shader {
     ) -> Result<Shader, String> {
         let id = compile_shader_from_source(souce, kind)?;
         info!("Compiled new shader {}", id);
-        Ok(Shader { id })
+        Ok(Shader { id, kind })
     }
 
     /// # Constructor
-    /// Compiles the given shader from the resources
+    /// Compiles the given shader from the resources, including the files referenced by its `#include` directives
     pub fn from_res(res: &Resources, name: &str) -> Result<Shader, String> {
+        Shader::from_shader_source(name, &ShaderSource::from_res(res, name)?)
+    }
+
+    /// # Constructor
+    /// Compiles the already loaded source of the shader with the given resource name
+    pub fn from_shader_source(name: &str, source: &ShaderSource) -> Result<Shader, String> {
         // Possible Shader extensions to look for
-        const POSSIBLE_EXT: [(&str, GLenum); 2] = [
+        const POSSIBLE_EXT: [(&str, GLenum); 6] = [
             (".vert", gl::VERTEX_SHADER),
+            (".tesc", gl::TESS_CONTROL_SHADER),
+            (".tese", gl::TESS_EVALUATION_SHADER),
+            (".geom", gl::GEOMETRY_SHADER),
             (".frag", gl::FRAGMENT_SHADER),
+            (".comp", gl::COMPUTE_SHADER),
         ];
 
         let shader_kind = POSSIBLE_EXT.iter()
@@ -42,17 +137,82 @@ impl Shader {
             .map(|&(_, kind)| kind)
             .ok_or_else(|| format!("Can not determine shader type for resource {}", name))?;
 
-        info!("Compiling shader \"{}\" as {:?}", name, shader_kind);
-        let source = res.load_cstring(name)
-            .map_err(|e| format!("Error loading resource {}: {:?}", name, e))?;
-
-        Shader::from_source(&source, shader_kind)
+        info!("Compiling shader \"{}\" as {:?} from {:?}", name, shader_kind, source.files);
+        for problem in check_source(&source.code, shader_kind) {
+            warn!(target: LOG_TARGET, "Shader \"{}\": {}", name, problem);
+        }
+        let code = CString::new(source.code.as_str())
+            .map_err(|_| format!("Shader {} contains a nul byte", name))?;
+        Shader::from_source(&code, shader_kind)
     }
 
     /// Get id of the shader
     pub fn id(&self) -> GLuint {
         self.id
     }
+
+    /// Get the type of the shader (e.g. `gl::VERTEX_SHADER`)
+    pub fn kind(&self) -> GLenum {
+        self.kind
+    }
+}
+
+/// Checks the source for common authoring mistakes before it is compiled, so they are reported with a clearer message
+/// than the log of the GLSL compiler: a missing or unsupported `#version` directive and code for a different stage
+/// (e.g. fragment shader code in a `.vert`-file). The stage is only guessed from obvious markers, so the result is a hint.
+fn check_source(source: &str, kind: GLenum) -> Vec<String> {
+    // Line comments are ignored, so commented out code does not count as marker
+    let lines: Vec<&str> = source.lines()
+        .map(|line| line.split("//").next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let contains = |marker: &str| lines.iter().any(|line| line.contains(marker));
+    let mut problems = Vec::new();
+
+    match lines.iter().find_map(|line| line.strip_prefix("#version")) {
+        None => problems.push("No #version directive found, the compiler assumes GLSL 1.10".to_string()),
+        Some(directive) => {
+            let mut parts = directive.split_whitespace();
+            match parts.next().and_then(|version| version.parse::<u32>().ok()) {
+                Some(version) if version > MAX_GLSL_VERSION => problems.push(format!(
+                    "#version {} is not supported by the OpenGL 4.5 core context (up to {})", version, MAX_GLSL_VERSION)),
+                Some(_) => {}
+                None => problems.push(format!("Invalid #version directive \"#version{}\"", directive)),
+            }
+            if parts.next() == Some("es") {
+                problems.push("#version uses the ES profile, which is not available in the OpenGL core context".to_string());
+            }
+        }
+    }
+
+    let declares_vec4_output = lines.iter().any(|line| {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        words.windows(2).any(|pair| pair == ["out", "vec4"])
+    });
+    let fragment_markers = contains("gl_FragCoord") || contains("gl_FragDepth") || contains("discard");
+    match kind {
+        // Vertex shaders followed by tessellation stages do not need to write gl_Position themselves
+        gl::VERTEX_SHADER if fragment_markers || (declares_vec4_output && !contains("gl_Position")) => {
+            problems.push("Vertex shader looks like a fragment shader (it uses gl_FragCoord, gl_FragDepth or discard, \
+                or declares an `out vec4` color without writing gl_Position)".to_string());
+        }
+        gl::FRAGMENT_SHADER => {
+            if !declares_vec4_output && !contains("gl_FragColor") {
+                problems.push("Fragment shader does not declare an `out vec4` color".to_string());
+            }
+            if contains("gl_Position =") || contains("gl_Position=") {
+                problems.push("Fragment shader writes gl_Position, is it a vertex shader?".to_string());
+            }
+        }
+        gl::TESS_CONTROL_SHADER if !contains("vertices") => {
+            problems.push("Tessellation control shader does not declare the output patch size (`layout (vertices = n) out`)".to_string());
+        }
+        gl::TESS_EVALUATION_SHADER if !(contains("triangles") || contains("quads") || contains("isolines")) => {
+            problems.push("Tessellation evaluation shader does not declare the primitive mode (triangles, quads or isolines)".to_string());
+        }
+        _ => {}
+    }
+    problems
 }
 
 impl Drop for Shader {
@@ -64,4 +224,39 @@ impl Drop for Shader {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Loads the shader with the given name from the files of the map
+    fn load(name: &str, files: &[(&str, &str)]) -> Result<ShaderSource, String> {
+        let files: HashMap<&str, &str> = files.iter().copied().collect();
+        ShaderSource::load(name, &|file| files.get(file).map(|content| content.to_string()).ok_or_else(|| format!("{} not found", file)))
+    }
+
+    #[test]
+    fn includes_are_inserted_relative_to_the_including_file() {
+        let source = load("shaders/a.frag", &[
+            ("shaders/a.frag", "#version 330 core\n#include \"include/b.glsl\"\nvoid main() {}\n"),
+            ("shaders/include/b.glsl", "#include \"../c.glsl\"\nfloat b;\n"),
+            ("shaders/c.glsl", "float c;\n"),
+        ]).unwrap();
 
+        assert_eq!(source.code, "#version 330 core\nfloat c;\n#line 2\nfloat b;\n#line 3\nvoid main() {}\n");
+        assert_eq!(source.files, ["shaders/a.frag", "shaders/include/b.glsl", "shaders/c.glsl"]);
+    }
+
+    #[test]
+    fn invalid_includes_are_reported() {
+        let cyclic = load("a.glsl", &[("a.glsl", "#include \"b.glsl\"\n"), ("b.glsl", "#include \"a.glsl\"\n")]);
+        assert!(cyclic.unwrap_err().contains("Cyclic include"));
+
+        let missing = load("a.glsl", &[("a.glsl", "float a;\n#include \"b.glsl\"\n")]);
+        assert_eq!(missing.unwrap_err(), "a.glsl:2: b.glsl not found");
+
+        let unquoted = load("a.glsl", &[("a.glsl", "#include b.glsl\n")]);
+        assert!(unquoted.is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use log::info;
+
+use crate::glhelper::{CubeMap, StaticMesh};
+
+const LOG_TARGET: &str = "Skybox";
+
+/// # Skybox
+/// A unit cube textured with a cube map, drawn around the camera behind the scene (`shaders/util/skybox`).
+/// The program removes the translation from the view matrix, so the cube stays centered on the camera,
+/// and places it at the far plane, so it only covers pixels without geometry when drawn with `GL_LEQUAL`.
+pub struct Skybox {
+    cube: StaticMesh,
+    cube_map: CubeMap,
+}
+
+impl Skybox {
+    /// # Constructor
+    /// Creates the cube around the given cube map
+    pub fn new(cube_map: CubeMap) -> Skybox {
+        // Two triangles per face; the cube is seen from the inside, so culling has to be disabled while drawing
+        let corners = [
+            [-1.0, -1.0, -1.0], [1.0, -1.0, -1.0], [1.0, 1.0, -1.0], [-1.0, 1.0, -1.0],
+            [-1.0, -1.0, 1.0], [1.0, -1.0, 1.0], [1.0, 1.0, 1.0], [-1.0, 1.0, 1.0],
+        ];
+        let faces: [[usize; 4]; 6] = [
+            [1, 5, 6, 2], [4, 0, 3, 7], [3, 2, 6, 7],
+            [4, 5, 1, 0], [5, 4, 7, 6], [0, 1, 2, 3],
+        ];
+        let positions: Vec<[f32; 3]> = faces.iter()
+            .flat_map(|[a, b, c, d]| [a, b, c, a, c, d])
+            .map(|&idx| corners[idx])
+            .collect();
+        let cube = StaticMesh::new(&positions);
+        info!(target: LOG_TARGET, "Created skybox");
+
+        Skybox { cube, cube_map }
+    }
+
+    /// Binds the cube map to the given texture unit and draws the cube with the currently active program
+    pub fn draw(&self, texture_unit: gl::types::GLenum) {
+        self.cube_map.bind_texture(texture_unit);
+        self.cube.draw();
+    }
+}
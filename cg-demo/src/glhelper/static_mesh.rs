@@ -0,0 +1,66 @@
+use gl::types::GLuint;
+use log::info;
+
+use crate::glhelper::utils::fill_vbo;
+
+const LOG_TARGET: &str = "StaticMesh";
+
+/// # StaticMesh
+/// Triangles that only consist of positions and are uploaded once, used for helpers like the skybox and the floor grid.
+/// The vertices are provided with the following layout:
+///   `layout (location = 0) in vec3  inPos;`
+pub struct StaticMesh {
+    vbo_id: GLuint,
+    vao_id: GLuint,
+    vertex_count: i32,
+}
+
+impl StaticMesh {
+    /// # Constructor
+    /// Creates the VBO and VAO of the triangles with the given corners (three positions per triangle)
+    pub fn new(positions: &[[f32; 3]]) -> StaticMesh {
+        let data: Vec<f32> = positions.iter().flatten().copied().collect();
+
+        let mut vbo_id: GLuint = 0;
+        let mut vao_id: GLuint = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut vbo_id);
+        }
+        fill_vbo(vbo_id, &data);
+
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao_id);
+            gl::BindVertexArray(vao_id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo_id);
+
+            let stride = (3 * std::mem::size_of::<f32>()) as gl::types::GLint;
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+        info!(target: LOG_TARGET, "Created static mesh with {} vertices in VBO {}", positions.len(), vbo_id);
+
+        StaticMesh { vbo_id, vao_id, vertex_count: positions.len() as i32 }
+    }
+
+    /// Draws the triangles with the currently active program
+    pub fn draw(&self) {
+        unsafe {
+            gl::BindVertexArray(self.vao_id);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.vertex_count);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for StaticMesh {
+    fn drop(&mut self) {
+        info!(target: LOG_TARGET, "Deleting static mesh VBO {}", self.vbo_id);
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao_id);
+            gl::DeleteBuffers(1, &self.vbo_id);
+        }
+    }
+}
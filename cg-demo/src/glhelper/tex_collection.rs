@@ -1,46 +1,374 @@
-use crate::glhelper::{Program, TextureData};
+use std::collections::HashMap;
+
+use gl::types::GLenum;
+use log::{debug, info, warn};
+
+use cg_demo::heightmap::{HeightChannel, HeightMap};
+
+use crate::glhelper::{Program, TextureData, TextureOptions};
 use crate::glhelper::utils::format_texture_path;
+use crate::material::MaterialDef;
 use crate::Resources;
 
+const LOG_TARGET: &str = "TextureCollection";
+
+/// Texture unit of the shadow map, which is not part of a collection but must not collide with its maps
+pub const SHADOW_MAP_UNIT: u32 = 5;
+
+/// Texture units of all maps as `(texture type, sampler uniform, unit)`.
+/// Both the samplers of the programs and the bound textures are configured from this table, so a new map only has to be added here.
+/// The maps of a secondary collection (see [`TextureCollection::set_active_secondary`]) use the type with the prefix `secondary_`.
+pub const TEXTURE_UNITS: [(&str, &str, u32); 10] = [
+    ("base", "baseMap", 0),
+    ("normal", "normalMap", 1),
+    ("height", "heightMap", 2),
+    ("detail", "detailMap", 3),
+    ("emissive", "emissiveMap", 4),
+    ("shadow", "shadowMap", SHADOW_MAP_UNIT),
+    ("matid", "materialIdMap", 6),
+    ("secondary_base", "secondaryBaseMap", 7),
+    ("secondary_normal", "secondaryNormalMap", 8),
+    ("secondary_height", "secondaryHeightMap", 9),
+];
+
+/// Types of the maps that are bound for a secondary collection
+const SECONDARY_TEXTURE_TYPES: [&str; 3] = ["base", "normal", "height"];
+
 /// # TextureCollection
 /// A collection of textures with different purposes for the same content.
 /// The following types of textures are contained within a collection:
 ///  - Basic texture (color/image)
 ///  - Normal Map
 ///  - Height Map
+///  - Detail Map (optional, tiled on top of the basic texture)
+///  - Emissive Map (optional, light emitted by the surface)
+///  - Material-ID Map (optional, selects between this and a secondary collection)
 pub struct TextureCollection {
+    /// Name of the collection in the resources (without type-suffix and extension) or folder of its material definition
+    name: String,
+    /// Resource names of the loaded textures by their type
+    paths: HashMap<&'static str, String>,
+    /// Amount of times the textures are repeated across the mesh
+    tiling: f32,
+    /// Whether the normal map uses the DirectX convention (green channel pointing down)
+    directx_normals: bool,
+    /// Magnification filter overriding the one of the texture options, kept across reloads
+    mag_filter: Option<GLenum>,
+
     base: TextureData,
     normal: TextureData,
     height: TextureData,
+    detail: Option<TextureData>,
+    emissive: Option<TextureData>,
+    matid: Option<TextureData>,
 }
 
 impl TextureCollection {
     /// # Static utility
-    /// Configure the given program to assign the textures to the correct samplers
+    /// Configure the given program to assign the textures to the correct samplers (see [`TEXTURE_UNITS`])
     pub fn configure_program(program: &Program) {
-        program.set_property_int("baseMap", 0);
-        program.set_property_int("normalMap", 1);
-        program.set_property_int("heightMap", 2);
+        for (_, sampler, unit) in TEXTURE_UNITS {
+            program.set_property_int(sampler, unit as i32);
+        }
+    }
+
+    /// # Static utility
+    /// Get the texture unit the map of the given type is bound to (e.g. `"normal"`)
+    pub fn texture_unit(texture_type: &str) -> Option<u32> {
+        TEXTURE_UNITS.iter()
+            .find(|(unit_type, _, _)| *unit_type == texture_type)
+            .map(|&(_, _, unit)| unit)
     }
 
     /// # Constructor
-    /// Load the given texture collection from the resources
+    /// Load the given texture collection from the resources.
+    /// The textures are named by convention as `<texture_name>_<type>.<extension>` (e.g. `wall_base.jpg`).
     pub fn from_resources(res: &Resources, texture_name: &str, extension: &str) -> Result<TextureCollection, String> {
-        let base = TextureData::from_resources(&res, format_texture_path(texture_name, "base", extension).as_str()).unwrap();
-        let normal = TextureData::from_resources(&res, format_texture_path(texture_name, "normal", extension).as_str()).unwrap();
-        let height = TextureData::from_resources(&res, format_texture_path(texture_name, "height", extension).as_str()).unwrap();
+        let base = TextureData::from_resources_with_options(&res, format_texture_path(texture_name, "base", extension).as_str(), texture_options("base")).unwrap();
+        let normal = TextureData::from_resources_with_options(&res, format_texture_path(texture_name, "normal", extension).as_str(), texture_options("normal")).unwrap();
+        let height = TextureData::from_resources_with_options(&res, format_texture_path(texture_name, "height", extension).as_str(), texture_options("height")).unwrap();
+        let detail = load_optional_texture(res, texture_name, "detail", extension);
+        let emissive = load_optional_texture(res, texture_name, "emissive", extension);
+        let matid = load_optional_texture(res, texture_name, "matid", extension);
+
+        let mut collection = TextureCollection {
+            name: texture_name.to_string(),
+            paths: HashMap::new(),
+            tiling: 1.0,
+            directx_normals: false,
+            mag_filter: None,
+            base,
+            normal,
+            height,
+            detail,
+            emissive,
+            matid,
+        };
+        collection.paths = collection.textures().into_iter()
+            .map(|(texture_type, _)| (texture_type, format_texture_path(texture_name, texture_type, extension)))
+            .collect();
+        Ok(collection)
+    }
+
+    /// # Constructor
+    /// Load the texture collection described by the given material definition from the resources
+    pub fn from_material(res: &Resources, material: &MaterialDef) -> Result<TextureCollection, String> {
+        let mut paths = HashMap::new();
+        let mut load_map = |texture_type: &'static str, map| {
+            let path = material.map_path(map);
+            let mut options = TextureOptions {
+                srgb: map.srgb.unwrap_or_else(|| texture_options(texture_type).srgb),
+                ..texture_options(texture_type)
+            };
+            if let Some(channels) = map.swizzle {
+                options = options.with_swizzle(channels)
+                    .map_err(|e| format!("Invalid {} map in the material definition: {}", texture_type, e))?;
+            }
+            let texture = TextureData::from_resources_with_options(res, &path, options);
+            paths.insert(texture_type, path);
+            texture
+        };
+
+        let base = load_map("base", &material.base)?;
+        let normal = load_map("normal", &material.normal)?;
+        let height = load_map("height", &material.height)?;
+        let detail = material.detail.as_ref().map(|map| load_map("detail", map)).transpose()?;
+        let emissive = material.emissive.as_ref().map(|map| load_map("emissive", map)).transpose()?;
+        let matid = material.matid.as_ref().map(|map| load_map("matid", map)).transpose()?;
 
         Ok(TextureCollection {
+            name: material.folder().to_string(),
+            paths,
+            tiling: material.tiling,
+            directx_normals: material.directx_normals,
+            mag_filter: None,
             base,
             normal,
             height,
+            detail,
+            emissive,
+            matid,
         })
     }
 
+    /// Reloads all textures of the collection from the resources.
+    /// Textures that fail to reload keep their previous content.
+    pub fn reload_all(&mut self, res: &Resources) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        let paths = self.paths.clone();
+        for (texture_type, texture) in self.textures_mut() {
+            let path = &paths[texture_type];
+            if let Err(e) = texture.reload(res, path) {
+                warn!(target: LOG_TARGET, "Could not reload texture \"{}\": {}", path, e);
+                errors.push(format!("{}: {}", path, e));
+            }
+        }
+
+        // Reloading applies the filter of the options again
+        if let Some(filter) = self.mag_filter {
+            self.set_mag_filter(filter);
+        }
+
+        if errors.is_empty() {
+            info!(target: LOG_TARGET, "Reloaded texture collection \"{}\"", self.name);
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// Enables or disables mipmapping for all textures of the collection and reloads them with the new setting
+    pub fn set_mipmaps(&mut self, res: &Resources, mipmaps: bool) -> Result<(), String> {
+        for (_, texture) in self.textures_mut() {
+            let mut options = texture.options();
+            options.mipmaps = mipmaps;
+            texture.set_options(options);
+        }
+        self.reload_all(res)
+    }
+
+    /// Changes the magnification filter of all textures without reloading them
+    pub fn set_mag_filter(&mut self, filter: GLenum) {
+        self.mag_filter = Some(filter);
+        for (_, texture) in self.textures() {
+            texture.set_mag_filter(filter);
+        }
+    }
+
+    /// Get the magnification filter of the textures
+    pub fn mag_filter(&self) -> GLenum {
+        self.mag_filter.unwrap_or(self.base.options().filter)
+    }
+
+    /// Check whether the collection contains a detail map
+    pub fn has_detail(&self) -> bool {
+        self.detail.is_some()
+    }
+
+    /// Check whether the collection contains an emissive map
+    pub fn has_emissive(&self) -> bool {
+        self.emissive.is_some()
+    }
+
+    /// Check whether the collection contains a material-ID map
+    pub fn has_material_ids(&self) -> bool {
+        self.matid.is_some()
+    }
+
+    /// Get the amount of times the textures are repeated across the mesh
+    pub fn tiling(&self) -> f32 {
+        self.tiling
+    }
+
+    /// Loads the height map of the collection from the resources into a [`HeightMap`] on the CPU,
+    /// which is repeated across the mesh the same way as the textures.
+    /// The heights are read from the channel the swizzle of the texture maps to red, which is the one the shaders sample.
+    pub fn load_height_map(&self, res: &Resources) -> Result<HeightMap, String> {
+        let path = &self.paths["height"];
+        let data = res.load_bytes(path)
+            .map_err(|e| format!("Error loading resource {}: {:?}", path, e))?;
+        let channel = match self.height.options().swizzle[0] {
+            gl::GREEN => HeightChannel::Green,
+            gl::BLUE => HeightChannel::Blue,
+            gl::ALPHA => HeightChannel::Alpha,
+            gl::ZERO => HeightChannel::Zero,
+            gl::ONE => HeightChannel::One,
+            _ => HeightChannel::Red,
+        };
+        Ok(HeightMap::from_memory(&data, channel)?.with_tiling(self.tiling))
+    }
+
+    /// Get the size of the base texture in pixels as `(width, height)`
+    pub fn base_size(&self) -> (u32, u32) {
+        (self.base.width(), self.base.height())
+    }
+
+    /// Get the name of the collection
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check whether the textures of the collection are using mipmaps
+    pub fn mipmaps(&self) -> bool {
+        self.base.options().mipmaps
+    }
+
+    /// Passes the settings of the material (tiling and normal map convention) to the given program, if it supports them
+    pub fn configure_material(&self, program: &Program) {
+        if program.has_property("tiling") {
+            program.set_property_float("tiling", self.tiling);
+        }
+        if program.has_property("directXNormals") {
+            program.set_property_bool("directXNormals", self.directx_normals);
+        }
+    }
+
     /// Loads all textures from the collection into the shader
     pub fn set_active(&self) {
-        self.base.bind_texture(gl::TEXTURE0);
-        self.normal.bind_texture(gl::TEXTURE1);
-        self.height.bind_texture(gl::TEXTURE2);
+        for (texture_type, texture) in self.textures() {
+            match TextureCollection::texture_unit(texture_type) {
+                Some(unit) => texture.bind_texture(gl::TEXTURE0 + unit),
+                None => warn!(target: LOG_TARGET, "No texture unit assigned to the {} map", texture_type),
+            }
+        }
+    }
+
+    /// Loads the base, normal and height map of the collection into the shader as secondary material,
+    /// which is selected by the material-ID map of the primary collection
+    pub fn set_active_secondary(&self) {
+        for (texture_type, texture) in self.textures() {
+            if !SECONDARY_TEXTURE_TYPES.contains(&texture_type) {
+                continue;
+            }
+            match TextureCollection::texture_unit(&format!("secondary_{}", texture_type)) {
+                Some(unit) => texture.bind_texture(gl::TEXTURE0 + unit),
+                None => warn!(target: LOG_TARGET, "No texture unit assigned to the secondary {} map", texture_type),
+            }
+        }
+    }
+
+    /// Logs the size of every map and warns about maps whose size differs from the base texture.
+    /// Maps with different resolutions often indicate mismatching files, which results in misaligned details.
+    pub fn log_resolutions(&self) {
+        let base_size = (self.base.width(), self.base.height());
+        for (texture_type, texture) in self.textures() {
+            let size = (texture.width(), texture.height());
+            if size == base_size {
+                info!(target: LOG_TARGET, "{} map of \"{}\": {}x{}", texture_type, self.name, size.0, size.1);
+            } else {
+                warn!(target: LOG_TARGET, "{} map of \"{}\": {}x{} differs from the base map ({}x{})",
+                    texture_type, self.name, size.0, size.1, base_size.0, base_size.1);
+            }
+        }
+    }
+
+    /// All loaded textures of the collection with their type
+    pub fn textures(&self) -> Vec<(&'static str, &TextureData)> {
+        let mut textures = vec![
+            ("base", &self.base),
+            ("normal", &self.normal),
+            ("height", &self.height),
+        ];
+        if let Some(detail) = self.detail.as_ref() {
+            textures.push(("detail", detail));
+        }
+        if let Some(emissive) = self.emissive.as_ref() {
+            textures.push(("emissive", emissive));
+        }
+        if let Some(matid) = self.matid.as_ref() {
+            textures.push(("matid", matid));
+        }
+        textures
+    }
+
+    /// All loaded textures of the collection with their type
+    fn textures_mut(&mut self) -> Vec<(&'static str, &mut TextureData)> {
+        let mut textures = vec![
+            ("base", &mut self.base),
+            ("normal", &mut self.normal),
+            ("height", &mut self.height),
+        ];
+        if let Some(detail) = self.detail.as_mut() {
+            textures.push(("detail", detail));
+        }
+        if let Some(emissive) = self.emissive.as_mut() {
+            textures.push(("emissive", emissive));
+        }
+        if let Some(matid) = self.matid.as_mut() {
+            textures.push(("matid", matid));
+        }
+        textures
+    }
+}
+
+/// Determines the options to load a texture of the given type with.
+/// Color maps are stored as sRGB, while data maps (normal, height, ambient occlusion, ...) are linear.
+fn texture_options(texture_type: &str) -> TextureOptions {
+    TextureOptions {
+        srgb: matches!(texture_type, "base" | "detail" | "emissive"),
+        ..TextureOptions::default()
+    }
+}
+
+/// Loads a texture of the collection that may be missing
+fn load_optional_texture(res: &Resources, texture_name: &str, texture_type: &str, extension: &str) -> Option<TextureData> {
+    TextureData::from_resources_with_options(res, format_texture_path(texture_name, texture_type, extension).as_str(), texture_options(texture_type))
+        .map_err(|e| debug!(target: LOG_TARGET, "No {} map for \"{}\": {}", texture_type, texture_name, e))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::glhelper::utils::UPLOAD_UNIT;
+
+    use super::*;
+
+    #[test]
+    fn maps_use_separate_units() {
+        for (idx, (texture_type, _, unit)) in TEXTURE_UNITS.iter().enumerate() {
+            assert_ne!(*unit, UPLOAD_UNIT, "The {} map uses the upload unit", texture_type);
+            assert!(TEXTURE_UNITS[idx + 1..].iter().all(|(_, _, other)| other != unit), "The {} map shares unit {}", texture_type, unit);
+        }
     }
 }
@@ -1,28 +1,112 @@
 use gl::types::{GLenum, GLuint};
 use log::{info, trace};
 
-use crate::glhelper::utils::load_texture_from_path;
+use crate::glhelper::utils::{load_texture_from_memory, UPLOAD_UNIT};
 use crate::Resources;
 
 const LOG_TARGET: &str = "Texture";
 
+/// # TextureOptions
+/// Sampling parameters that are applied when a texture is uploaded
+#[derive(Debug, Clone, Copy)]
+pub struct TextureOptions {
+    /// Wrap mode for both texture coordinates (e.g. `gl::REPEAT`)
+    pub wrap: GLenum,
+    /// Filter used for sampling the texture (`gl::LINEAR` or `gl::NEAREST`)
+    pub filter: GLenum,
+    /// Whether mipmaps are generated and used for minification
+    pub mipmaps: bool,
+    /// Whether the image contains sRGB-encoded colors, which are converted to linear values when sampling.
+    /// Should only be set for color data, not for data maps like normal or height maps.
+    pub srgb: bool,
+    /// Source of the red, green, blue and alpha values returned when sampling (e.g. `gl::ALPHA` to read the alpha channel as red)
+    pub swizzle: [GLenum; 4],
+}
+
+impl TextureOptions {
+    /// Determine the minification filter matching the `filter` and `mipmaps` settings
+    pub fn min_filter(&self) -> GLenum {
+        match (self.mipmaps, self.filter) {
+            (true, gl::NEAREST) => gl::NEAREST_MIPMAP_NEAREST,
+            (true, _) => gl::LINEAR_MIPMAP_LINEAR,
+            (false, filter) => filter,
+        }
+    }
+
+    /// Sets the swizzle from the channel names `r`, `g`, `b`, `a` or the constants `0` and `1`,
+    /// e.g. `['a', 'a', 'a', '1']` to sample a height stored in the alpha channel
+    pub fn with_swizzle(self, channels: [char; 4]) -> Result<TextureOptions, String> {
+        let mut swizzle = [gl::RED; 4];
+        for (target, channel) in swizzle.iter_mut().zip(channels) {
+            *target = match channel.to_ascii_lowercase() {
+                'r' => gl::RED,
+                'g' => gl::GREEN,
+                'b' => gl::BLUE,
+                'a' => gl::ALPHA,
+                '0' => gl::ZERO,
+                '1' => gl::ONE,
+                other => return Err(format!("Invalid swizzle channel '{}' in {:?}, expected one of r, g, b, a, 0 or 1", other, channels)),
+            };
+        }
+        Ok(TextureOptions { swizzle, ..self })
+    }
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        TextureOptions {
+            wrap: gl::REPEAT,
+            filter: gl::LINEAR,
+            mipmaps: true,
+            srgb: false,
+            swizzle: [gl::RED, gl::GREEN, gl::BLUE, gl::ALPHA],
+        }
+    }
+}
+
 /// # TextureData
 /// Handle for a texture loaded in OpenGL
 pub struct TextureData {
     id: GLuint,
+    /// Size of the loaded image in pixels as `(width, height)`
+    size: (u32, u32),
+    /// Options used when (re-)loading the texture
+    options: TextureOptions,
 }
 
 impl TextureData {
     /// # Constructor
     /// Load the given texture from the resources
     pub fn from_resources(res: &Resources, res_name: &str) -> Result<TextureData, String> {
-        let tex_id = load_texture_from_path(
-            res.construct_path(res_name)
-                .map_err(|_| "Could not create path to resource")?.as_path()
-        ).map_err(|e| e)?;
-        info!(target: LOG_TARGET, "Loaded texture \"{}\" from resources as texture {}", res_name, tex_id);
+        TextureData::from_resources_with_options(res, res_name, TextureOptions::default())
+    }
+
+    /// # Constructor
+    /// Load the given texture from the resources using the given options
+    pub fn from_resources_with_options(res: &Resources, res_name: &str, options: TextureOptions) -> Result<TextureData, String> {
+        let data = res.load_bytes(res_name)
+            .map_err(|e| format!("Error loading resource {}: {:?}", res_name, e))?;
+        let (tex_id, size) = load_texture_from_memory(&data, res_name, &options)?;
+        info!(target: LOG_TARGET, "Loaded texture \"{}\" ({}x{}) from resources as texture {} ({:?})", res_name, size.0, size.1, tex_id, options);
 
-        Ok(TextureData { id: tex_id })
+        Ok(TextureData { id: tex_id, size, options })
+    }
+
+    /// Reload the texture from the resources.
+    /// The new image is uploaded to a fresh texture, which replaces the current one only if loading succeeded.
+    /// Otherwise the previous texture is kept, so it stays usable.
+    pub fn reload(&mut self, res: &Resources, name: &str) -> Result<(), String> {
+        let data = res.load_bytes(name)
+            .map_err(|e| format!("Error loading resource {}: {:?}", name, e))?;
+        let (new_id, size) = load_texture_from_memory(&data, name, &self.options)?;
+        info!(target: LOG_TARGET, "Reloaded texture \"{}\" as texture {}, replacing texture {}", name, new_id, self.id);
+
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+        self.id = new_id;
+        self.size = size;
+        Ok(())
     }
 
     /// Bind the associated texture to the given texture_unit
@@ -34,6 +118,69 @@ impl TextureData {
         }
     }
 
+    /// Changes the magnification filter (`gl::LINEAR` or `gl::NEAREST`) of the loaded texture without uploading it again.
+    /// The change lasts until the texture is reloaded, which applies the filter of the options again.
+    pub fn set_mag_filter(&self, filter: GLenum) {
+        trace!(target: LOG_TARGET, "Setting magnification filter of texture {} to 0x{:X}", self.id, filter);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + UPLOAD_UNIT);
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as gl::types::GLint);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    /// Get the options used when loading the texture
+    pub fn options(&self) -> TextureOptions {
+        self.options
+    }
+
+    /// Set the options used for the next (re-)load of the texture
+    pub fn set_options(&mut self, options: TextureOptions) {
+        self.options = options;
+    }
+
+    /// Get the width of the image in pixels
+    pub fn width(&self) -> u32 {
+        self.size.0
+    }
+
+    /// Get the height of the image in pixels
+    pub fn height(&self) -> u32 {
+        self.size.1
+    }
+
+    /// Downloads level 0 of the texture as it is stored on the GPU, e.g. to compare it with the source file.
+    /// Single-channel textures are returned as grayscale, all others as RGBA. The swizzle of the options is not applied.
+    /// sRGB textures return the encoded values, like they are stored in the source file.
+    pub fn read_back(&self) -> image::DynamicImage {
+        let (width, height) = self.size;
+        let mut internal_format: gl::types::GLint = 0;
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + UPLOAD_UNIT);
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_INTERNAL_FORMAT, &mut internal_format);
+        }
+        let (format, channels) = if internal_format as GLenum == gl::R8 { (gl::RED, 1) } else { (gl::RGBA, 4) };
+
+        let mut pixels: Vec<u8> = vec![0; width as usize * height as usize * channels];
+        unsafe {
+            // Rows of single-channel images are not padded to 4 bytes
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::GetTexImage(gl::TEXTURE_2D, 0, format, gl::UNSIGNED_BYTE, pixels.as_mut_ptr().cast());
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        trace!(target: LOG_TARGET, "Read back {}x{} pixels of texture {} (internal format 0x{:X})", width, height, self.id, internal_format);
+
+        // The rows are uploaded in the order of the image, so they are read back without flipping
+        if channels == 1 {
+            image::DynamicImage::ImageLuma8(image::GrayImage::from_raw(width, height, pixels).unwrap())
+        } else {
+            image::DynamicImage::ImageRgba8(image::RgbaImage::from_raw(width, height, pixels).unwrap())
+        }
+    }
+
     /// Get the texture id in OpenGL
     pub fn id(&self) -> GLuint {
         self.id
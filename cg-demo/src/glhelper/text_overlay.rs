@@ -0,0 +1,430 @@
+use gl::types::{GLint, GLsizei, GLuint};
+use log::info;
+
+use crate::glhelper::utils::{calc_f32_offset, fill_vbo};
+
+const LOG_TARGET: &str = "TextOverlay";
+
+/// Size of a glyph of the font in pixels
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+/// Size of a character cell in pixels, including the spacing to the next character and line
+const CELL_WIDTH: usize = 6;
+const CELL_HEIGHT: usize = 9;
+/// Character cells per row of the font texture
+const ATLAS_COLUMNS: usize = 16;
+/// Character of the first glyph; characters without a glyph are drawn as `?`
+const FIRST_CHAR: u32 = ' ' as u32;
+/// Cell after the last glyph, which is filled completely and used for the background of the panel
+const SOLID_CELL: usize = GLYPHS.len();
+/// Distance of the panel to the edges of the viewport in pixels
+const MARGIN: usize = 8;
+/// Distance of the text to the edges of the panel in character cells
+const PADDING: usize = 1;
+/// Character cells between two columns of text
+const COLUMN_GAP: usize = 3;
+/// Largest integer scale of the font; smaller scales are used if the text does not fit
+const MAX_SCALE: usize = 3;
+/// Floats per vertex: position (normalized device coordinates), texture coordinates and color
+const FLOATS_PER_VERTEX: usize = 8;
+
+/// 5x7 pixel glyphs of the printable ASCII characters, starting at [`FIRST_CHAR`].
+/// Every row is stored in the lowest 5 bits, with the most significant bit being the left pixel.
+const GLYPHS: [[u8; GLYPH_HEIGHT]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // '!'
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // '#'
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // '$'
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // '%'
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // '&'
+    [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // '('
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // ')'
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // '*'
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ','
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // '.'
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // '/'
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // '0'
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // '1'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // '2'
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // '3'
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // '4'
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // '5'
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // '6'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // '7'
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // '8'
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ';'
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // '<'
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // '='
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // '>'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // '?'
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // '@'
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'A'
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // 'B'
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // 'C'
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // 'D'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // 'E'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // 'F'
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // 'G'
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'H'
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'I'
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // 'J'
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // 'K'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // 'L'
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // 'M'
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // 'N'
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'O'
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // 'P'
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // 'Q'
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // 'R'
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // 'S'
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // 'T'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'U'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'V'
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // 'W'
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // 'X'
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // 'Y'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // 'Z'
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // '['
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // '\\'
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ']'
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // '_'
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // 'a'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // 'b'
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // 'c'
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // 'd'
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // 'e'
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // 'f'
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'g'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // 'h'
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // 'i'
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // 'j'
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // 'k'
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'l'
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // 'm'
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // 'n'
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // 'o'
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // 'p'
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // 'q'
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // 'r'
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // 's'
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // 't'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // 'u'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'v'
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // 'w'
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // 'x'
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'y'
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // 'z'
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // '{'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // '|'
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // '}'
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00], // '~'
+];
+
+/// # TextLine
+/// Line of text on the [`TextOverlay`] with the (straight alpha) color of its glyphs
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLine {
+    pub text: String,
+    pub color: [f32; 4],
+}
+
+impl TextLine {
+    /// # Constructor
+    pub fn new(text: impl Into<String>, color: [f32; 4]) -> TextLine {
+        TextLine { text: text.into(), color }
+    }
+}
+
+/// # TextOverlay
+/// Panel with lines of text drawn on top of the scene with a built-in 5x7 bitmap font.
+/// The lines are arranged in columns that fit into the viewport, using the largest integer scale of the font at which they fit.
+/// The vertices are only rebuilt if the lines or the size of the viewport change.
+pub struct TextOverlay {
+    font_texture_id: GLuint,
+    vbo_id: GLuint,
+    vao_id: GLuint,
+    vertex_count: GLsizei,
+    lines: Vec<TextLine>,
+    background: [f32; 4],
+    /// Size of the viewport the current vertices were built for
+    layout_size: Option<(usize, usize)>,
+}
+
+impl TextOverlay {
+    /// # Constructor
+    /// Uploads the font texture and creates the (empty) VBO and VAO of the overlay.
+    /// `background` is the color of the panel behind the text.
+    pub fn new(background: [f32; 4]) -> TextOverlay {
+        let (atlas_width, atlas_height) = atlas_size();
+        let atlas = font_atlas();
+
+        let mut font_texture_id: GLuint = 0;
+        let mut vbo_id: GLuint = 0;
+        let mut vao_id: GLuint = 0;
+        unsafe {
+            gl::GenTextures(1, &mut font_texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, font_texture_id);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::R8 as GLint, atlas_width as GLsizei, atlas_height as GLsizei, 0,
+                           gl::RED, gl::UNSIGNED_BYTE, atlas.as_ptr() as *const gl::types::GLvoid);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            // The glyphs are only drawn at integer scales, so their pixels stay sharp
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+
+            gl::GenBuffers(1, &mut vbo_id);
+            gl::GenVertexArrays(1, &mut vao_id);
+            gl::BindVertexArray(vao_id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo_id);
+
+            // Configure the following layout:
+            //   layout (location = 0) in vec2  inPos;
+            //   layout (location = 1) in vec2  inTexCoords;
+            //   layout (location = 2) in vec4  inColor;
+            let stride = (FLOATS_PER_VERTEX * std::mem::size_of::<f32>()) as GLint;
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, stride, calc_f32_offset(2));
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribPointer(2, 4, gl::FLOAT, gl::FALSE, stride, calc_f32_offset(4));
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+        info!(target: LOG_TARGET, "Created text overlay with font texture {} ({}x{}) in VBO {}", font_texture_id, atlas_width, atlas_height, vbo_id);
+
+        TextOverlay {
+            font_texture_id,
+            vbo_id,
+            vao_id,
+            vertex_count: 0,
+            lines: Vec::new(),
+            background,
+            layout_size: None,
+        }
+    }
+
+    /// Replaces the displayed lines
+    pub fn set_lines(&mut self, lines: Vec<TextLine>) {
+        self.lines = lines;
+        self.layout_size = None;
+    }
+
+    /// Draws the overlay into a viewport of the given size with the currently active program.
+    /// The font texture is bound to texture unit 0; blending and the depth test have to be configured by the caller.
+    pub fn draw(&mut self, width: usize, height: usize) {
+        if self.layout_size != Some((width, height)) {
+            let vertices = layout_vertices(&self.lines, self.background, width, height);
+            self.vertex_count = (vertices.len() / FLOATS_PER_VERTEX) as GLsizei;
+            fill_vbo(self.vbo_id, &vertices);
+            self.layout_size = Some((width, height));
+        }
+
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.font_texture_id);
+            gl::BindVertexArray(self.vao_id);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.vertex_count);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for TextOverlay {
+    fn drop(&mut self) {
+        info!(target: LOG_TARGET, "Deleting text overlay VBO {}", self.vbo_id);
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao_id);
+            gl::DeleteBuffers(1, &self.vbo_id);
+            gl::DeleteTextures(1, &self.font_texture_id);
+        }
+    }
+}
+
+/// Size of the font texture in pixels, containing all glyphs and the solid cell
+fn atlas_size() -> (usize, usize) {
+    let rows = (SOLID_CELL + 1).div_ceil(ATLAS_COLUMNS);
+    (ATLAS_COLUMNS * CELL_WIDTH, rows * CELL_HEIGHT)
+}
+
+/// Coverage of the font texture (one byte per pixel), with every glyph in the top left corner of its cell
+fn font_atlas() -> Vec<u8> {
+    let (atlas_width, atlas_height) = atlas_size();
+    let mut atlas = vec![0u8; atlas_width * atlas_height];
+    for cell in 0..=SOLID_CELL {
+        let (cell_x, cell_y) = ((cell % ATLAS_COLUMNS) * CELL_WIDTH, (cell / ATLAS_COLUMNS) * CELL_HEIGHT);
+        for y in 0..CELL_HEIGHT {
+            for x in 0..CELL_WIDTH {
+                let covered = match GLYPHS.get(cell) {
+                    Some(glyph) => x < GLYPH_WIDTH && y < GLYPH_HEIGHT && glyph[y] & (1 << (GLYPH_WIDTH - 1 - x)) != 0,
+                    None => true,
+                };
+                if covered {
+                    atlas[(cell_y + y) * atlas_width + cell_x + x] = 255;
+                }
+            }
+        }
+    }
+    atlas
+}
+
+/// Cell of the font texture containing the glyph of the character
+fn glyph_cell(c: char) -> usize {
+    (c as u32).checked_sub(FIRST_CHAR)
+        .map(|index| index as usize)
+        .filter(|index| *index < GLYPHS.len())
+        .unwrap_or(('?' as u32 - FIRST_CHAR) as usize)
+}
+
+/// Splits the lines into columns of at most `rows` lines. Empty lines at the top of a column are dropped.
+fn split_columns(lines: &[TextLine], rows: usize) -> Vec<Vec<&TextLine>> {
+    let mut columns: Vec<Vec<&TextLine>> = vec![Vec::new()];
+    for line in lines {
+        if columns.last().unwrap().len() >= rows {
+            columns.push(Vec::new());
+        }
+        let column = columns.last_mut().unwrap();
+        if !(column.is_empty() && line.text.is_empty()) {
+            column.push(line);
+        }
+    }
+    columns
+}
+
+/// Width of the columns in character cells, including the gaps between them
+fn columns_width(columns: &[Vec<&TextLine>]) -> usize {
+    let text_width: usize = columns.iter()
+        .map(|column| column.iter().map(|line| line.text.chars().count()).max().unwrap_or(0))
+        .sum();
+    text_width + COLUMN_GAP * columns.len().saturating_sub(1)
+}
+
+/// Builds the triangles of the panel and the glyphs for a viewport of the given size.
+/// The lines are split into columns at the largest scale at which all of them fit into the viewport (falling back to scale 1).
+fn layout_vertices(lines: &[TextLine], background: [f32; 4], width: usize, height: usize) -> Vec<f32> {
+    let mut vertices = Vec::new();
+    if lines.is_empty() || width == 0 || height == 0 {
+        return vertices;
+    }
+
+    let rows_at = |scale: usize| (height.saturating_sub(2 * MARGIN) / (CELL_HEIGHT * scale)).saturating_sub(2 * PADDING).max(1);
+    let fits = |scale: usize, columns: &[Vec<&TextLine>]| (columns_width(columns) + 2 * PADDING) * CELL_WIDTH * scale + 2 * MARGIN <= width;
+    let (scale, columns) = (1..=MAX_SCALE).rev()
+        .map(|scale| (scale, split_columns(lines, rows_at(scale))))
+        .find(|(scale, columns)| fits(*scale, columns))
+        .unwrap_or_else(|| (1, split_columns(lines, rows_at(1))));
+
+    let (cell_width, cell_height) = (CELL_WIDTH * scale, CELL_HEIGHT * scale);
+    let rows = columns.iter().map(|column| column.len()).max().unwrap_or(0);
+    let panel_width = (columns_width(&columns) + 2 * PADDING) * cell_width;
+    let panel_height = (rows + 2 * PADDING) * cell_height;
+    let solid = cell_tex_coords(SOLID_CELL);
+    // Sample the center of the solid cell for the panel
+    let solid_center = [(solid[0] + solid[2]) / 2.0, (solid[1] + solid[3]) / 2.0];
+    push_quad(&mut vertices, [MARGIN, MARGIN, MARGIN + panel_width, MARGIN + panel_height], [solid_center[0], solid_center[1], solid_center[0], solid_center[1]], background, width, height);
+
+    let mut column_x = MARGIN + PADDING * cell_width;
+    for column in columns.iter() {
+        for (row, line) in column.iter().enumerate() {
+            let y = MARGIN + (PADDING + row) * cell_height;
+            for (i, c) in line.text.chars().enumerate().filter(|(_, c)| *c != ' ') {
+                let x = column_x + i * cell_width;
+                push_quad(&mut vertices, [x, y, x + cell_width, y + cell_height], cell_tex_coords(glyph_cell(c)), line.color, width, height);
+            }
+        }
+        let column_width = column.iter().map(|line| line.text.chars().count()).max().unwrap_or(0);
+        column_x += (column_width + COLUMN_GAP) * cell_width;
+    }
+    vertices
+}
+
+/// Texture coordinates `[left, top, right, bottom]` of a cell of the font texture
+fn cell_tex_coords(cell: usize) -> [f32; 4] {
+    let (atlas_width, atlas_height) = atlas_size();
+    let (x, y) = ((cell % ATLAS_COLUMNS) * CELL_WIDTH, (cell / ATLAS_COLUMNS) * CELL_HEIGHT);
+    [
+        x as f32 / atlas_width as f32,
+        y as f32 / atlas_height as f32,
+        (x + CELL_WIDTH) as f32 / atlas_width as f32,
+        (y + CELL_HEIGHT) as f32 / atlas_height as f32,
+    ]
+}
+
+/// Appends the two triangles of a quad given in pixels `[left, top, right, bottom]` from the top left corner of the viewport
+fn push_quad(vertices: &mut Vec<f32>, rect: [usize; 4], tex_coords: [f32; 4], color: [f32; 4], width: usize, height: usize) {
+    let to_ndc_x = |x: usize| x as f32 / width as f32 * 2.0 - 1.0;
+    let to_ndc_y = |y: usize| 1.0 - y as f32 / height as f32 * 2.0;
+    let (left, top, right, bottom) = (to_ndc_x(rect[0]), to_ndc_y(rect[1]), to_ndc_x(rect[2]), to_ndc_y(rect[3]));
+    let [u0, v0, u1, v1] = tex_coords;
+    // Counter-clockwise in normalized device coordinates
+    for (x, y, u, v) in [(left, bottom, u0, v1), (right, bottom, u1, v1), (right, top, u1, v0),
+                         (left, bottom, u0, v1), (right, top, u1, v0), (left, top, u0, v0)] {
+        vertices.extend_from_slice(&[x, y, u, v]);
+        vertices.extend_from_slice(&color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn help_lines(count: usize) -> Vec<TextLine> {
+        (0..count)
+            .map(|i| TextLine::new(if i % 10 == 0 { String::new() } else { format!("- Key {:<10}=> Action number {}", i, i) }, [1.0; 4]))
+            .collect()
+    }
+
+    #[test]
+    fn layout_stays_inside_the_viewport() {
+        let lines = help_lines(110);
+        for (width, height) in [(1920, 1080), (1280, 720), (800, 600)] {
+            let vertices = layout_vertices(&lines, [0.0, 0.0, 0.0, 0.5], width, height);
+            assert!(!vertices.is_empty());
+            for vertex in vertices.chunks(FLOATS_PER_VERTEX) {
+                assert!((-1.0..=1.0).contains(&vertex[0]) && (-1.0..=1.0).contains(&vertex[1]), "{:?} outside of {}x{}", vertex, width, height);
+            }
+        }
+    }
+
+    #[test]
+    fn larger_viewports_use_larger_glyphs() {
+        let lines = help_lines(20);
+        let glyph_width = |width: usize, height: usize| {
+            // The first quad is the panel, the second one the first glyph
+            let vertices = layout_vertices(&lines, [0.0; 4], width, height);
+            let glyph = &vertices[6 * FLOATS_PER_VERTEX..12 * FLOATS_PER_VERTEX];
+            (glyph[FLOATS_PER_VERTEX] - glyph[0]) * width as f32 / 2.0
+        };
+        assert_eq!(glyph_width(320, 240).round() as usize, CELL_WIDTH);
+        assert_eq!(glyph_width(3840, 2160).round() as usize, CELL_WIDTH * MAX_SCALE);
+    }
+
+    #[test]
+    fn columns_do_not_start_with_empty_lines() {
+        let lines = help_lines(35);
+        let columns = split_columns(&lines, 10);
+        assert!(columns.iter().all(|column| column.len() <= 10));
+        assert!(columns.iter().all(|column| !column[0].text.is_empty()));
+        let text = |lines: Vec<&TextLine>| lines.into_iter().filter(|line| !line.text.is_empty()).cloned().collect::<Vec<_>>();
+        assert_eq!(text(columns.into_iter().flatten().collect()), text(lines.iter().collect()));
+    }
+
+    #[test]
+    fn unknown_characters_use_the_question_mark() {
+        assert_eq!(glyph_cell('A'), ('A' as u32 - FIRST_CHAR) as usize);
+        assert_eq!(glyph_cell('ä'), glyph_cell('?'));
+        assert_eq!(glyph_cell('\n'), glyph_cell('?'));
+    }
+}
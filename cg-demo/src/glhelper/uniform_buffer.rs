@@ -0,0 +1,116 @@
+use std::ffi::c_void;
+use std::marker::PhantomData;
+
+use cgmath::{Matrix4, Vector3};
+use gl::types::{GLsizeiptr, GLuint};
+use log::info;
+
+const LOG_TARGET: &str = "UniformBuffer";
+
+/// Name of the uniform block holding the per-frame uniforms in the shaders
+pub const PER_FRAME_BLOCK: &str = "PerFrame";
+/// Binding point the per-frame uniform buffer is attached to
+pub const PER_FRAME_BINDING: GLuint = 0;
+
+/// # PerFrame
+/// Uniforms shared by all mesh programs. The layout matches the following `std140` block:
+/// ```glsl
+/// layout (std140) uniform PerFrame {
+///     mat4 projection; // offset   0
+///     mat4 view;       // offset  64
+///     vec3 viewPos;    // offset 128
+///     vec3 lightPos;   // offset 144
+/// };
+/// ```
+/// A `vec3` is aligned like a `vec4` in `std140`, so each of them is followed by a padding float.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PerFrame {
+    projection: [[f32; 4]; 4],
+    view: [[f32; 4]; 4],
+    view_pos: [f32; 3],
+    _padding0: f32,
+    light_pos: [f32; 3],
+    _padding1: f32,
+}
+
+const _: () = assert!(std::mem::size_of::<PerFrame>() == 160);
+
+impl PerFrame {
+    /// # Constructor
+    /// Packs the matrices and positions into the `std140` layout
+    pub fn new(projection: &Matrix4<f32>, view: &Matrix4<f32>, view_pos: &Vector3<f32>, light_pos: &Vector3<f32>) -> PerFrame {
+        PerFrame {
+            projection: (*projection).into(),
+            view: (*view).into(),
+            view_pos: (*view_pos).into(),
+            _padding0: 0.0,
+            light_pos: (*light_pos).into(),
+            _padding1: 0.0,
+        }
+    }
+}
+
+/// # UniformBuffer
+/// Buffer object holding a single value of `T`, which is attached to a fixed uniform block binding point.
+/// `T` has to be `#[repr(C)]` and match the `std140` layout of the block it is used for.
+pub struct UniformBuffer<T: Copy> {
+    id: GLuint,
+    binding: GLuint,
+    _data: PhantomData<T>,
+}
+
+impl<T: Copy> UniformBuffer<T> {
+    /// # Constructor
+    /// Allocates the buffer and attaches it to the given binding point
+    pub fn new(binding: GLuint) -> UniformBuffer<T> {
+        let mut id: GLuint = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, id);
+            gl::BufferData(
+                gl::UNIFORM_BUFFER,
+                std::mem::size_of::<T>() as GLsizeiptr,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, binding, id);
+        }
+        info!(target: LOG_TARGET, "Created uniform buffer {} ({} bytes) at binding {}", id, std::mem::size_of::<T>(), binding);
+
+        UniformBuffer {
+            id,
+            binding,
+            _data: PhantomData,
+        }
+    }
+
+    /// Get the binding point of the buffer
+    pub fn binding(&self) -> GLuint {
+        self.binding
+    }
+
+    /// Uploads a new value to the buffer
+    pub fn update(&self, data: &T) {
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.id);
+            gl::BufferSubData(
+                gl::UNIFORM_BUFFER,
+                0,
+                std::mem::size_of::<T>() as GLsizeiptr,
+                data as *const T as *const c_void,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        }
+    }
+}
+
+impl<T: Copy> Drop for UniformBuffer<T> {
+    fn drop(&mut self) {
+        info!(target: LOG_TARGET, "Deleting uniform buffer {}", self.id);
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
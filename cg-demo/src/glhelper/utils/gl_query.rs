@@ -0,0 +1,46 @@
+use std::ffi::CStr;
+
+use gl::types::{GLenum, GLfloat, GLint};
+
+/// Queries a string describing the current OpenGL context (e.g. `gl::VERSION` or `gl::RENDERER`).
+/// Returns `None` if the driver does not provide the string, e.g. for an unknown name.
+pub fn get_string(name: GLenum) -> Option<String> {
+    let value = unsafe {
+        gl::GetString(name)
+    };
+    if value.is_null() {
+        return None;
+    }
+
+    let value = unsafe {
+        CStr::from_ptr(value.cast())
+    };
+    Some(value.to_string_lossy().into_owned())
+}
+
+/// Queries an integer parameter of the current OpenGL context (e.g. `gl::MAX_TEXTURE_SIZE`)
+pub fn get_int(name: GLenum) -> i32 {
+    let mut value: GLint = 0;
+    unsafe {
+        gl::GetIntegerv(name, &mut value);
+    }
+    value
+}
+
+/// Queries an integer parameter with multiple values (e.g. `x`, `y`, `width` and `height` of `gl::VIEWPORT`)
+pub fn get_ints<const N: usize>(name: GLenum) -> [i32; N] {
+    let mut values: [GLint; N] = [0; N];
+    unsafe {
+        gl::GetIntegerv(name, values.as_mut_ptr());
+    }
+    values
+}
+
+/// Queries a float parameter of the current OpenGL context (e.g. `gl::MAX_TEXTURE_LOD_BIAS`)
+pub fn get_float(name: GLenum) -> f32 {
+    let mut value: GLfloat = 0.0;
+    unsafe {
+        gl::GetFloatv(name, &mut value);
+    }
+    value
+}
@@ -0,0 +1,383 @@
+use std::ffi::{CStr, CString};
+
+use cgmath::{InnerSpace, Matrix4, SquareMatrix, Vector3};
+use gl::types::{GLchar, GLenum, GLuint, GLvoid};
+use log::{info, warn};
+
+use cg_demo::mesh::{VertexAttribute, VertexLayout};
+
+use crate::glhelper::TextureOptions;
+
+pub mod gl_query;
+
+const LOG_TARGET: &str = "GlUtils";
+
+/// Texture unit textures are bound to while they are uploaded or modified. No map uses it, so uploading (e.g. reloading)
+/// a texture never replaces a map that is bound for drawing.
+pub const UPLOAD_UNIT: u32 = 10;
+
+/// Compiles shader source code and loads them in OpenGL
+pub fn compile_shader_from_source(source: &CStr, kind: GLuint) -> Result<GLuint, String> {
+    let id = unsafe {
+        gl::CreateShader(kind)
+    };
+
+    unsafe {
+        gl::ShaderSource(id, 1, &source.as_ptr(), std::ptr::null());
+        gl::CompileShader(id);
+    }
+
+    let mut success: gl::types::GLint = 1;
+    unsafe {
+        gl::GetShaderiv(id, gl::COMPILE_STATUS, &mut success);
+    }
+
+    if success == 0 {
+        let mut len: gl::types::GLint = 0;
+        unsafe {
+            gl::GetShaderiv(id, gl::INFO_LOG_LENGTH, &mut len);
+        }
+
+        let error: CString = create_whitespace_cstring_with_len(len as usize);
+
+        unsafe {
+            gl::GetShaderInfoLog(
+                id,
+                len,
+                std::ptr::null_mut(),
+                error.as_ptr() as *mut GLchar,
+            );
+        }
+
+        return Err(error.to_string_lossy().into_owned());
+    }
+
+    Ok(id)
+}
+
+/// Decodes an image from the given file content and creates an OpenGL texture for it.
+/// Returns the id of the texture and the size of the image as `(id, (width, height))`. The name is only used for logging.
+pub fn load_texture_from_memory(data: &[u8], name: &str, options: &TextureOptions) -> Result<(GLuint, (u32, u32)), String> {
+    let mut img = image::load_from_memory(data)
+        .map_err(|e| format!("Could not load texture: {}", e))?;
+
+    let gl_texture_format = match img.color() {
+        image::ColorType::L8 => gl::RED,
+        image::ColorType::Rgb8 => gl::RGB,
+        image::ColorType::Rgba8 => gl::RGBA,
+        other => {
+            // Convert all other formats (e.g. 16-bit or luma+alpha images) to a format that can be uploaded
+            warn!(target: LOG_TARGET, "Converting texture \"{}\" from unsupported color type {:?} to RGBA8", name, other);
+            img = image::DynamicImage::ImageRgba8(img.to_rgba8());
+            gl::RGBA
+        }
+    };
+    let (internal_format, internal_format_name) = match (gl_texture_format, options.srgb) {
+        (gl::RGB, true) => (gl::SRGB8, "SRGB8"),
+        (gl::RGBA, true) => (gl::SRGB8_ALPHA8, "SRGB8_ALPHA8"),
+        (gl::RGB, false) => (gl::RGB8, "RGB8"),
+        (gl::RGBA, false) => (gl::RGBA8, "RGBA8"),
+        // There is no single-channel sRGB format, so grayscale images are always linear
+        _ => (gl::R8, "R8"),
+    };
+    info!(target: LOG_TARGET, "Uploading texture \"{}\" with internal format {}", name, internal_format_name);
+
+    let mut texture_id: GLuint = 0;
+    unsafe {
+        gl::GenTextures(1, &mut texture_id);
+        gl::ActiveTexture(gl::TEXTURE0 + UPLOAD_UNIT);
+        gl::BindTexture(gl::TEXTURE_2D, texture_id);
+        gl::TexImage2D(gl::TEXTURE_2D, 0, internal_format as i32,
+                       img.width() as i32, img.height() as i32,
+                       0, gl_texture_format, gl::UNSIGNED_BYTE,
+                       img.as_bytes().as_ptr().cast());
+
+        if options.mipmaps {
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        }
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, options.wrap as gl::types::GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, options.wrap as gl::types::GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, options.min_filter() as gl::types::GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, options.filter as gl::types::GLint);
+        let swizzle = options.swizzle.map(|channel| channel as gl::types::GLint);
+        gl::TexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+    }
+    Ok((texture_id, (img.width(), img.height())))
+}
+
+/// Calculates a view matrix that looks from the given position at the target
+pub fn calc_look_at_matrix(eye_pos: Vector3<f32>, target: Vector3<f32>, up: Vector3<f32>) -> Matrix4<f32> {
+    let f = (target - eye_pos).normalize();
+    let mut u = up.normalize();
+    let s = f.cross(u).normalize();
+    u = s.cross(f);
+
+    let mut result: Matrix4<f32> = cgmath::One::one();
+    result.x.x = s.x;
+    result.y.x = s.y;
+    result.z.x = s.z;
+    result.x.y = u.x;
+    result.y.y = u.y;
+    result.z.y = u.z;
+    result.x.z = -1.0 * f.x;
+    result.y.z = -1.0 * f.y;
+    result.z.z = -1.0 * f.z;
+    result.w.x = -1.0 * s.dot(eye_pos);
+    result.w.y = -1.0 * u.dot(eye_pos);
+    result.w.z = f.dot(eye_pos);
+
+    result
+}
+
+/// Calculates a projection matrix with the given fovy and near/far planes
+pub fn calc_projection_matrix(fovy: f32, aspect: f32, z_near: f32, z_far: f32) -> Result<Matrix4<f32>, String> {
+    if aspect == 0.0 {
+        return Err("Aspect ratio may not be 0".to_string());
+    }
+    if z_far == z_near {
+        return Err("z-values may not be the same".to_string());
+    }
+
+    let tan_half_fovy = (fovy / 2.0).tan();
+    let mut result: Matrix4<f32> = cgmath::Zero::zero();
+    result.x.x = 1.0 / (aspect * tan_half_fovy);
+    result.y.y = 1.0 / tan_half_fovy;
+    result.z.z = -1.0 * (z_far + z_near) / (z_far - z_near);
+    result.z.w = -1.0;
+    result.w.z = (-2.0 * z_far * z_near) / (z_far - z_near);
+
+    Ok(result)
+}
+
+/// Calculates an orthographic projection matrix for the given box in view space
+pub fn calc_orthographic_matrix(left: f32, right: f32, bottom: f32, top: f32, z_near: f32, z_far: f32) -> Result<Matrix4<f32>, String> {
+    if left == right || bottom == top {
+        return Err("The box may not have a width or height of 0".to_string());
+    }
+    if z_far == z_near {
+        return Err("z-values may not be the same".to_string());
+    }
+
+    let mut result: Matrix4<f32> = cgmath::One::one();
+    result.x.x = 2.0 / (right - left);
+    result.y.y = 2.0 / (top - bottom);
+    result.z.z = -2.0 / (z_far - z_near);
+    result.w.x = -(right + left) / (right - left);
+    result.w.y = -(top + bottom) / (top - bottom);
+    result.w.z = -(z_far + z_near) / (z_far - z_near);
+
+    Ok(result)
+}
+
+/// Calculates the inverse of the given matrix, e.g. to transform from clip space back to world space
+pub fn calc_inverse_matrix(matrix: &Matrix4<f32>) -> Result<Matrix4<f32>, String> {
+    matrix.invert().ok_or_else(|| format!("Matrix {:?} is not invertible", matrix))
+}
+
+/// Transforms a point in window coordinates back to world space (like `gluUnProject`).
+/// `screen` contains the x- and y-coordinate in pixels (origin at the bottom left, like OpenGL) and the depth in `[0, 1]`;
+/// the viewport is given as `(x, y, width, height)`.
+pub fn unproject(screen: Vector3<f32>, view: &Matrix4<f32>, proj: &Matrix4<f32>, viewport: (i32, i32, u32, u32)) -> Result<Vector3<f32>, String> {
+    let (viewport_x, viewport_y, width, height) = viewport;
+    if width == 0 || height == 0 {
+        return Err("The viewport may not have a width or height of 0".to_string());
+    }
+
+    let inverse_view_proj = calc_inverse_matrix(&(proj * view))?;
+    let ndc = cgmath::vec4(
+        2.0 * (screen.x - viewport_x as f32) / width as f32 - 1.0,
+        2.0 * (screen.y - viewport_y as f32) / height as f32 - 1.0,
+        2.0 * screen.z - 1.0,
+        1.0,
+    );
+    let world = inverse_view_proj * ndc;
+    if world.w == 0.0 {
+        return Err(format!("Point {:?} can not be unprojected (w = 0)", screen));
+    }
+    Ok(world.truncate() / world.w)
+}
+
+/// Formats texture paths as `{name}_{type}.{ext}` to allow loading belonging textures at once
+pub fn format_texture_path(texture_name: &str, texture_type: &str, extension: &str) -> String {
+    format!("{}_{}.{}", texture_name, texture_type, extension)
+}
+
+/// Create a blank string, that can be used to copy data from C-libraries
+pub fn create_whitespace_cstring_with_len(len: usize) -> CString {
+    // allocate string buffer
+    let mut buffer: Vec<u8> = Vec::with_capacity(len as usize + 1);
+    buffer.extend([b' '].iter().cycle().take(len as usize));
+    unsafe { CString::from_vec_unchecked(buffer) }
+}
+
+/// Copies the given data to the VBO
+pub fn fill_vbo(vbo_id: GLuint, data: &Vec<f32>) {
+    unsafe {
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo_id);
+        gl::BufferData(
+            gl::ARRAY_BUFFER,
+            (data.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
+            data.as_ptr() as *const GLvoid,
+            gl::STATIC_DRAW,
+        );
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+    }
+}
+
+/// Configures the VAO for the given vertex layout and assigns it to the VBO.
+/// Every attribute of the layout is bound to its fixed location (see [`cg_demo::mesh::VertexAttribute::location`]),
+/// the locations of attributes missing from the layout stay disabled. Without colors, all vertices use white instead.
+pub fn configure_vao(vbo_id: GLuint, layout: &VertexLayout) -> GLuint {
+    let mut vao: GLuint = 0;
+    unsafe {
+        gl::GenVertexArrays(1, &mut vao);
+
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo_id);
+
+        // The default layout is:
+        //   layout (location = 0) in vec3  inPos;
+        //   layout (location = 1) in vec3  inNormal;
+        //   layout (location = 2) in vec2  inTexCoords;
+        //   layout (location = 3) in vec4  inTangent;  (w = handedness)
+        //   layout (location = 4) in vec3  inBitangent;
+        //
+        // since float / f32 is used, all the values are tightly packed
+        let stride = (layout.floats_per_vertex() * std::mem::size_of::<f32>()) as gl::types::GLint;
+
+        let mut offset = 0;
+        for attribute in layout.attributes() {
+            gl::EnableVertexAttribArray(attribute.location());
+            gl::VertexAttribPointer(
+                attribute.location(),
+                attribute.size() as gl::types::GLint, gl::FLOAT, gl::FALSE, // amount and type of data
+                stride, calc_f32_offset(offset),
+            );
+            offset += attribute.size();
+        }
+        if !layout.contains(VertexAttribute::Color) {
+            gl::VertexAttrib3f(VertexAttribute::Color.location(), 1.0, 1.0, 1.0);
+        }
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl::BindVertexArray(0);
+    }
+
+    vao
+}
+
+/// Configures the per-instance attributes of the given VAO and assigns them to the instance-VBO
+pub fn configure_instance_attributes(vao_id: GLuint, instance_vbo_id: GLuint) {
+    unsafe {
+        gl::BindVertexArray(vao_id);
+        gl::BindBuffer(gl::ARRAY_BUFFER, instance_vbo_id);
+
+        // Configure the following layout:
+        //   layout (location = 5) in mat4  inInstanceModel;
+        //
+        // A mat4 occupies 4 consecutive locations (one per column) with 4 components each.
+        // The attributes advance once per instance instead of once per vertex.
+        let stride = (16 * std::mem::size_of::<f32>()) as gl::types::GLint;
+
+        for column in 0..4 {
+            let location = 5 + column;
+            gl::EnableVertexAttribArray(location);
+            gl::VertexAttribPointer(
+                location,
+                4, gl::FLOAT, gl::FALSE,
+                stride, calc_f32_offset(4 * column as usize),
+            );
+            gl::VertexAttribDivisor(location, 1);
+        }
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl::BindVertexArray(0);
+    }
+}
+
+/// Offsets the depth of polygons rasterized in the given mode (`gl::POLYGON_OFFSET_FILL`, `_LINE` or `_POINT`)
+/// by `factor` times their depth slope plus `units` times the smallest resolvable depth difference.
+/// Negative values move them towards the camera. Without an offset, the offset is disabled for the mode.
+pub fn set_polygon_offset(mode: GLenum, offset: Option<(f32, f32)>) {
+    unsafe {
+        match offset {
+            Some((factor, units)) => {
+                gl::Enable(mode);
+                gl::PolygonOffset(factor, units);
+            }
+            None => gl::Disable(mode),
+        }
+    }
+}
+
+/// Calculates the byte-offset for the given amount of `f32`-values
+pub fn calc_f32_offset(amount: usize) -> *const GLvoid {
+    (amount * std::mem::size_of::<f32>()) as *const GLvoid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT: (i32, i32, u32, u32) = (100, 50, 800, 600);
+    const Z_NEAR: f32 = 0.1;
+    const Z_FAR: f32 = 100.0;
+
+    fn view_proj() -> (Matrix4<f32>, Matrix4<f32>) {
+        let view = calc_look_at_matrix(cgmath::vec3(0.5, 1.0, 2.0), cgmath::vec3(0.0, 0.0, 0.0), cgmath::vec3(0.0, 1.0, 0.0));
+        let proj = calc_projection_matrix(45.0f32.to_radians(), 800.0 / 600.0, Z_NEAR, Z_FAR).unwrap();
+        (view, proj)
+    }
+
+    /// Projects the point to window coordinates like OpenGL does with the given viewport
+    fn project(point: Vector3<f32>, view: &Matrix4<f32>, proj: &Matrix4<f32>, viewport: (i32, i32, u32, u32)) -> Vector3<f32> {
+        let clip = proj * view * point.extend(1.0);
+        let ndc = clip.truncate() / clip.w;
+        let (x, y, width, height) = viewport;
+        cgmath::vec3(
+            x as f32 + (ndc.x + 1.0) * 0.5 * width as f32,
+            y as f32 + (ndc.y + 1.0) * 0.5 * height as f32,
+            (ndc.z + 1.0) * 0.5,
+        )
+    }
+
+    #[test]
+    fn unproject_reverses_the_projection() {
+        let (view, proj) = view_proj();
+        for point in [cgmath::vec3(0.0, 0.0, 0.0), cgmath::vec3(0.3, -0.2, 0.1), cgmath::vec3(-1.0, 0.5, -2.0)] {
+            let screen = project(point, &view, &proj, VIEWPORT);
+            let unprojected = unproject(screen, &view, &proj, VIEWPORT).unwrap();
+            assert!((unprojected - point).magnitude() < 1e-3, "{:?} was unprojected to {:?}", point, unprojected);
+        }
+    }
+
+    #[test]
+    fn unproject_respects_the_viewport_offset() {
+        let (view, proj) = view_proj();
+        let (x, y, width, height) = VIEWPORT;
+        let center = cgmath::vec3(x as f32 + width as f32 * 0.5, y as f32 + height as f32 * 0.5, 0.5);
+        let unprojected = unproject(center, &view, &proj, VIEWPORT).unwrap();
+        // The center of the viewport lies on the viewing direction of the camera
+        let view_space = (view * unprojected.extend(1.0)).truncate();
+        assert!(view_space.x.abs() < 1e-4 && view_space.y.abs() < 1e-4, "center was unprojected to {:?}", view_space);
+    }
+
+    #[test]
+    fn unproject_maps_the_depth_to_the_clipping_planes() {
+        let (view, proj) = view_proj();
+        let corner = cgmath::vec3(VIEWPORT.0 as f32, VIEWPORT.1 as f32, 0.0);
+        for (depth, distance) in [(0.0, Z_NEAR), (1.0, Z_FAR)] {
+            let world = unproject(cgmath::vec3(corner.x, corner.y, depth), &view, &proj, VIEWPORT).unwrap();
+            let view_space = (view * world.extend(1.0)).truncate();
+            assert!((-view_space.z - distance).abs() < distance * 1e-3, "depth {} was unprojected to {:?}", depth, view_space);
+        }
+    }
+
+    #[test]
+    fn unproject_reports_invalid_input() {
+        let (view, proj) = view_proj();
+        let singular = Matrix4::from_nonuniform_scale(1.0, 1.0, 0.0);
+        assert!(unproject(cgmath::vec3(0.0, 0.0, 0.5), &singular, &proj, VIEWPORT).is_err());
+        assert!(unproject(cgmath::vec3(0.0, 0.0, 0.5), &view, &proj, (0, 0, 0, 600)).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::Path;
+
+/// # HeightChannel
+/// Channel of the image the heights are read from.
+/// The shaders read the red channel of the height map texture, so this is the source of the red channel after swizzling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightChannel {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+    /// Constant height of 0
+    Zero,
+    /// Constant height of 1
+    One,
+}
+
+/// # HeightMap
+/// Single channel of an image kept on the CPU, so the height values can be sampled when generating geometry.
+pub struct HeightMap {
+    width: u32,
+    height: u32,
+    /// Heights in the range `[0, 1]`, stored row by row
+    values: Vec<f32>,
+    /// Amount of times the map is repeated across the texture coordinates `[0, 1]`
+    tiling: f32,
+}
+
+impl HeightMap {
+    /// # Constructor
+    /// Loads the image at the given path and reads the heights from the given channel.
+    /// Grayscale images contain their value in the red, green and blue channel.
+    pub fn from_path(img_path: &Path, channel: HeightChannel) -> Result<HeightMap, String> {
+        let img = image::open(img_path)
+            .map_err(|e| format!("Could not load height map {:?}: {}", img_path, e))?;
+        HeightMap::from_image(img, channel)
+    }
+
+    /// # Constructor
+    /// Decodes the image from the given file content and reads the heights from the given channel.
+    /// Grayscale images contain their value in the red, green and blue channel.
+    pub fn from_memory(data: &[u8], channel: HeightChannel) -> Result<HeightMap, String> {
+        let img = image::load_from_memory(data)
+            .map_err(|e| format!("Could not load height map: {}", e))?;
+        HeightMap::from_image(img, channel)
+    }
+
+    fn from_image(img: image::DynamicImage, channel: HeightChannel) -> Result<HeightMap, String> {
+        let img = img.to_rgba8();
+        if img.width() == 0 || img.height() == 0 {
+            return Err("Height map is empty".to_string());
+        }
+
+        let value = |pixel: &image::Rgba<u8>| match channel {
+            HeightChannel::Red => pixel[0] as f32 / 255.0,
+            HeightChannel::Green => pixel[1] as f32 / 255.0,
+            HeightChannel::Blue => pixel[2] as f32 / 255.0,
+            HeightChannel::Alpha => pixel[3] as f32 / 255.0,
+            HeightChannel::Zero => 0.0,
+            HeightChannel::One => 1.0,
+        };
+        Ok(HeightMap {
+            width: img.width(),
+            height: img.height(),
+            values: img.pixels().map(value).collect(),
+            tiling: 1.0,
+        })
+    }
+
+    /// Repeats the map the given amount of times across the texture coordinates, like the tiling of the material
+    pub fn with_tiling(mut self, tiling: f32) -> HeightMap {
+        self.tiling = tiling.max(f32::EPSILON);
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Samples the height at the given texture coordinates with bilinear filtering.
+    /// The coordinates are clamped to `[0, 1]` and map to the image rows the same way as the uploaded texture.
+    /// With a tiling, the map is repeated across that range and the coordinates wrap between the repetitions.
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        let x = self.wrap(u) * (self.width - 1) as f32;
+        let y = self.wrap(v) * (self.height - 1) as f32;
+
+        let x0 = x.floor() as u32;
+        let y0 = y.floor() as u32;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let fx = x - x0 as f32;
+        let fy = y - y0 as f32;
+
+        let top = self.value(x0, y0) * (1.0 - fx) + self.value(x1, y0) * fx;
+        let bottom = self.value(x0, y1) * (1.0 - fx) + self.value(x1, y1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    /// Maps a texture coordinate to the position within a single repetition of the map.
+    /// The end of the last repetition stays at `1`, so a tiling of 1 samples the whole map without wrapping.
+    fn wrap(&self, coord: f32) -> f32 {
+        let tiled = coord.clamp(0.0, 1.0) * self.tiling;
+        if tiled >= self.tiling { 1.0 } else { tiled.fract() }
+    }
+
+    fn value(&self, x: u32, y: u32) -> f32 {
+        self.values[(y * self.width + x) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Horizontal gradient of two pixels, from 0 on the left to 1 on the right
+    fn gradient() -> HeightMap {
+        HeightMap { width: 2, height: 1, values: vec![0.0, 1.0], tiling: 1.0 }
+    }
+
+    #[test]
+    fn samples_are_interpolated_and_clamped() {
+        let height_map = gradient();
+        assert_eq!(height_map.sample(0.0, 0.5), 0.0);
+        assert_eq!(height_map.sample(0.25, 0.5), 0.25);
+        assert_eq!(height_map.sample(1.0, 0.5), 1.0);
+        assert_eq!(height_map.sample(-1.0, 0.5), 0.0);
+        assert_eq!(height_map.sample(2.0, 0.5), 1.0);
+    }
+
+    #[test]
+    fn heights_are_read_from_the_channel() {
+        let img = image::DynamicImage::ImageRgba8(image::RgbaImage::from_raw(1, 1, vec![0, 51, 102, 255]).unwrap());
+        let expected = [
+            (HeightChannel::Red, 0.0),
+            (HeightChannel::Green, 0.2),
+            (HeightChannel::Blue, 0.4),
+            (HeightChannel::Alpha, 1.0),
+            (HeightChannel::Zero, 0.0),
+            (HeightChannel::One, 1.0),
+        ];
+        for (channel, height) in expected {
+            let height_map = HeightMap::from_image(img.clone(), channel).unwrap();
+            assert_eq!(height_map.sample(0.5, 0.5), height, "wrong height for {:?}", channel);
+        }
+    }
+
+    #[test]
+    fn tiling_repeats_the_map() {
+        let height_map = gradient().with_tiling(2.0);
+        assert_eq!(height_map.sample(0.125, 0.5), 0.25);
+        assert_eq!(height_map.sample(0.625, 0.5), 0.25);
+        assert_eq!(height_map.sample(0.5, 0.5), 0.0);
+        assert_eq!(height_map.sample(1.0, 0.5), 1.0);
+    }
+}
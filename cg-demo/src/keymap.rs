@@ -0,0 +1,560 @@
+use std::collections::HashMap;
+
+use log::{debug, info};
+use sdl2::keyboard::Keycode;
+use serde::Deserialize;
+
+use crate::Resources;
+
+const LOG_TARGET: &str = "KeyMap";
+
+/// Declares the [`Action`] enum together with [`Action::ALL`], which lists the variants in declaration order
+macro_rules! actions {
+    ($($action:ident,)*) => {
+        /// # Action
+        /// Operations of the application that can be bound to a key.
+        /// Key map files refer to them by their snake case name (e.g. `move_forward`).
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        pub enum Action {
+            $($action,)*
+        }
+
+        impl Action {
+            /// All actions in the order they are listed in the instructions
+            pub const ALL: &'static [Action] = &[$(Action::$action,)*];
+        }
+    };
+}
+
+actions! {
+    Quit,
+    ShowHelp,
+    IncreaseSamples,
+    DecreaseSamples,
+    SamplePresetLow,
+    SamplePresetMedium,
+    SamplePresetHigh,
+    SamplePresetUltra,
+    ToggleShaderDetail,
+    IncreaseMinParallaxLayers,
+    DecreaseMinParallaxLayers,
+    ToggleParallaxRefinement,
+    IncreaseTessLevel,
+    DecreaseTessLevel,
+    MoveForward,
+    MoveLeft,
+    MoveBackward,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Sprint,
+    ResetCamera,
+    FrameMesh,
+    TopDownView,
+    PivotLeft,
+    PivotRight,
+    PivotDown,
+    PivotUp,
+    PivotBackward,
+    PivotForward,
+    CenterPivot,
+    ResetPivot,
+    ToggleInvertY,
+    ToggleGroundMode,
+    ToggleMouseCapture,
+    ToggleGroundPlane,
+    CyclePrograms,
+    ReloadTextures,
+    ReloadAll,
+    ToggleMipmaps,
+    ToggleMagFilter,
+    ToggleDetail,
+    ToggleMultiMaterial,
+    ExportObj,
+    ExportTextureReadback,
+    ExportConfig,
+    IncreaseShininess,
+    DecreaseShininess,
+    IncreaseAmbient,
+    DecreaseAmbient,
+    IncreaseNormalStrength,
+    DecreaseNormalStrength,
+    ToggleHdr,
+    ToggleSkybox,
+    ToggleGrid,
+    ToggleWireframe,
+    IncreaseExposure,
+    DecreaseExposure,
+    CycleTonemapOperator,
+    CycleRenderScale,
+    CycleEmissiveStrength,
+    ToggleWorldSpaceNormals,
+    ToggleSmoothHeight,
+    CycleTangentSpaceVector,
+    ToggleTerrain,
+    CycleDisplacementAxis,
+    CycleDecimation,
+    ToggleUvClamp,
+    ToggleFlatNormals,
+    ToggleFlatShading,
+    ToggleVertexColors,
+    ToggleDepthTest,
+    ToggleBackfaceCulling,
+    ToggleDoubleSided,
+    ToggleLetterbox,
+    CycleLetterboxAspect,
+    CycleClearColor,
+    CycleLightColor,
+    ToggleShadows,
+    ToggleHeadlamp,
+    TogglePlaceLight,
+    ToggleSunCycle,
+    SunCycleForward,
+    SunCycleBackward,
+    CycleFogMode,
+    IncreaseFogDensity,
+    DecreaseFogDensity,
+    ToggleAxes,
+    CycleMapView,
+    CycleMapInset,
+    ToggleGpuTimer,
+    Screenshot,
+    SupersampledScreenshot,
+    RecordKeyframe,
+    ToggleCameraPath,
+    ClearCameraPath,
+    SaveCameraPath,
+    LoadCameraPath,
+    ToggleDemoReel,
+    AddInstance,
+    RemoveInstance,
+}
+
+impl Action {
+    /// Heading the action is grouped under in the instructions
+    pub fn category(&self) -> &'static str {
+        match self {
+            Action::Quit | Action::ShowHelp | Action::ReloadAll | Action::ToggleGpuTimer | Action::ToggleDemoReel => "General",
+            Action::IncreaseSamples | Action::DecreaseSamples | Action::SamplePresetLow | Action::SamplePresetMedium
+            | Action::SamplePresetHigh | Action::SamplePresetUltra | Action::ToggleShaderDetail | Action::IncreaseTessLevel
+            | Action::DecreaseTessLevel | Action::ToggleTerrain | Action::CycleDisplacementAxis | Action::CycleDecimation
+            | Action::ToggleUvClamp | Action::ToggleFlatNormals | Action::ToggleVertexColors | Action::ExportObj
+            | Action::AddInstance | Action::RemoveInstance => "Mesh",
+            Action::MoveForward | Action::MoveLeft | Action::MoveBackward | Action::MoveRight | Action::MoveUp
+            | Action::MoveDown | Action::Sprint | Action::ResetCamera | Action::FrameMesh | Action::TopDownView
+            | Action::ToggleInvertY | Action::ToggleGroundMode | Action::ToggleMouseCapture | Action::ToggleGroundPlane => "Camera",
+            Action::PivotLeft | Action::PivotRight | Action::PivotDown | Action::PivotUp | Action::PivotBackward
+            | Action::PivotForward | Action::CenterPivot | Action::ResetPivot => "Scene pivot",
+            Action::IncreaseMinParallaxLayers | Action::DecreaseMinParallaxLayers | Action::ToggleParallaxRefinement
+            | Action::CyclePrograms | Action::ToggleWorldSpaceNormals | Action::CycleTangentSpaceVector
+            | Action::ToggleFlatShading | Action::ToggleDoubleSided | Action::IncreaseNormalStrength
+            | Action::DecreaseNormalStrength => "Shaders",
+            Action::ReloadTextures | Action::ToggleMipmaps | Action::ToggleMagFilter | Action::ToggleDetail
+            | Action::ToggleMultiMaterial | Action::ToggleSmoothHeight | Action::CycleEmissiveStrength
+            | Action::CycleMapView | Action::CycleMapInset | Action::ExportTextureReadback => "Textures",
+            Action::IncreaseShininess | Action::DecreaseShininess | Action::IncreaseAmbient | Action::DecreaseAmbient
+            | Action::CycleLightColor | Action::ToggleShadows | Action::ToggleHeadlamp | Action::TogglePlaceLight
+            | Action::ToggleSunCycle | Action::SunCycleForward | Action::SunCycleBackward => "Lighting",
+            Action::ToggleHdr | Action::ToggleSkybox | Action::ToggleGrid | Action::ToggleWireframe
+            | Action::IncreaseExposure | Action::DecreaseExposure | Action::CycleTonemapOperator
+            | Action::CycleRenderScale | Action::ToggleDepthTest | Action::ToggleBackfaceCulling | Action::ToggleLetterbox
+            | Action::CycleLetterboxAspect | Action::CycleClearColor | Action::CycleFogMode | Action::IncreaseFogDensity
+            | Action::DecreaseFogDensity | Action::ToggleAxes => "Display",
+            Action::ExportConfig | Action::Screenshot | Action::SupersampledScreenshot | Action::RecordKeyframe
+            | Action::ToggleCameraPath | Action::ClearCameraPath | Action::SaveCameraPath
+            | Action::LoadCameraPath => "Export and recording",
+        }
+    }
+
+    /// Readable description of the action for the instructions
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::ShowHelp => "Show these controls",
+            Action::IncreaseSamples => "Increase model vertices (or parallax layers)",
+            Action::DecreaseSamples => "Decrease model vertices (or parallax layers)",
+            Action::SamplePresetLow => "Use the \"Low\" model vertex preset",
+            Action::SamplePresetMedium => "Use the \"Medium\" model vertex preset",
+            Action::SamplePresetHigh => "Use the \"High\" model vertex preset",
+            Action::SamplePresetUltra => "Use the \"Ultra\" model vertex preset",
+            Action::ToggleShaderDetail => "Switch detail keys between model vertices and parallax layers",
+            Action::IncreaseMinParallaxLayers => "Increase parallax layers used when looking straight at the surface",
+            Action::DecreaseMinParallaxLayers => "Decrease parallax layers used when looking straight at the surface",
+            Action::ToggleParallaxRefinement => "Toggle the binary search refinement of parallax mapping",
+            Action::IncreaseTessLevel => "Increase tessellation level",
+            Action::DecreaseTessLevel => "Decrease tessellation level",
+            Action::MoveForward => "Move forward",
+            Action::MoveLeft => "Move left",
+            Action::MoveBackward => "Move backward",
+            Action::MoveRight => "Move right",
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::Sprint => "Move faster while held",
+            Action::ResetCamera => "Reset camera",
+            Action::FrameMesh => "Move camera to fit the mesh into the view",
+            Action::TopDownView => "Top-down view",
+            Action::PivotLeft => "Move the scene pivot along -x",
+            Action::PivotRight => "Move the scene pivot along +x",
+            Action::PivotDown => "Move the scene pivot along -y",
+            Action::PivotUp => "Move the scene pivot along +y",
+            Action::PivotBackward => "Move the scene pivot along -z",
+            Action::PivotForward => "Move the scene pivot along +z",
+            Action::CenterPivot => "Center the scene pivot on the mesh",
+            Action::ResetPivot => "Reset the scene pivot to the origin",
+            Action::ToggleInvertY => "Invert vertical mouse axis",
+            Action::ToggleGroundMode => "Toggle ground mode (only movement parallel to the mesh and no pitch)",
+            Action::ToggleMouseCapture => "Capture the mouse (hides the cursor and keeps it inside the window)",
+            Action::ToggleGroundPlane => "Toggle camera collision with the mesh plane",
+            Action::CyclePrograms => "Cycle shaders",
+            Action::ReloadTextures => "Reload textures",
+            Action::ReloadAll => "Reload shaders and textures",
+            Action::ToggleMipmaps => "Toggle mipmapping",
+            Action::ToggleMagFilter => "Toggle linear/nearest texture magnification",
+            Action::ToggleDetail => "Toggle detail map",
+            Action::ToggleMultiMaterial => "Toggle the secondary material selected by the material-ID map",
+            Action::ExportObj => "Export mesh as OBJ",
+            Action::ExportTextureReadback => "Save the texture maps as uploaded to the GPU (for debugging)",
+            Action::ExportConfig => "Export the current settings as configuration",
+            Action::IncreaseShininess => "Increase specular shininess",
+            Action::DecreaseShininess => "Decrease specular shininess",
+            Action::IncreaseAmbient => "Increase ambient light",
+            Action::DecreaseAmbient => "Decrease ambient light",
+            Action::IncreaseNormalStrength => "Increase normal map strength",
+            Action::DecreaseNormalStrength => "Decrease normal map strength (flat at zero)",
+            Action::ToggleHdr => "Toggle HDR rendering with tone mapping",
+            Action::ToggleSkybox => "Toggle the skybox",
+            Action::ToggleGrid => "Toggle the floor grid below the mesh",
+            Action::ToggleWireframe => "Toggle a wireframe on top of the shaded mesh",
+            Action::IncreaseExposure => "Increase exposure (HDR)",
+            Action::DecreaseExposure => "Decrease exposure (HDR)",
+            Action::CycleTonemapOperator => "Cycle through the tone mapping operators (HDR)",
+            Action::CycleRenderScale => "Cycle the render scale (lower resolution for speed, higher for supersampling)",
+            Action::CycleEmissiveStrength => "Cycle emissive strength",
+            Action::ToggleWorldSpaceNormals => "Toggle tangent-/world-space normal map",
+            Action::ToggleSmoothHeight => "Toggle smooth/nearest height sampling",
+            Action::CycleTangentSpaceVector => "Cycle tangent/bitangent/normal in the tangent space shader",
+            Action::ToggleTerrain => "Toggle height map terrain",
+            Action::CycleDisplacementAxis => "Cycle the displacement axis of the terrain (normal/world up/custom)",
+            Action::CycleDecimation => "Cycle the decimation of the terrain (fewer triangles in flat regions)",
+            Action::ToggleUvClamp => "Toggle inset (clamped) or full (wrapped) texture coordinates of the mesh",
+            Action::ToggleFlatNormals => "Toggle flat/smooth normals",
+            Action::ToggleFlatShading => "Toggle flat/smooth interpolation in the flat shading shader",
+            Action::ToggleVertexColors => "Toggle per-vertex colors (shown by the vertex color shader)",
+            Action::ToggleDepthTest => "Toggle depth test",
+            Action::ToggleBackfaceCulling => "Toggle backface culling",
+            Action::ToggleDoubleSided => "Toggle double-sided lighting (flipped normals on back faces)",
+            Action::ToggleLetterbox => "Toggle letterboxing to a fixed aspect ratio",
+            Action::CycleLetterboxAspect => "Cycle letterbox aspect ratio",
+            Action::CycleClearColor => "Cycle background color",
+            Action::CycleLightColor => "Cycle light color",
+            Action::ToggleShadows => "Toggle shadows",
+            Action::ToggleHeadlamp => "Toggle light following the camera (headlamp)",
+            Action::TogglePlaceLight => "Toggle placing the light by clicking the surface (instead of probing it)",
+            Action::ToggleSunCycle => "Toggle the sun moving across the sky (time of day)",
+            Action::SunCycleForward => "Move the sun forward in time",
+            Action::SunCycleBackward => "Move the sun back in time",
+            Action::CycleFogMode => "Cycle fog (off/linear/exponential)",
+            Action::IncreaseFogDensity => "Increase density of the exponential fog",
+            Action::DecreaseFogDensity => "Decrease density of the exponential fog",
+            Action::ToggleAxes => "Toggle coordinate axes",
+            Action::CycleMapView => "Cycle full-screen view of the texture maps",
+            Action::CycleMapInset => "Cycle the texture map shown in the corner of the scene",
+            Action::ToggleGpuTimer => "Toggle GPU time measurement",
+            Action::Screenshot => "Save screenshot",
+            Action::SupersampledScreenshot => "Save screenshot in 4x resolution",
+            Action::RecordKeyframe => "Record camera keyframe",
+            Action::ToggleCameraPath => "Play/Stop camera path",
+            Action::ClearCameraPath => "Clear camera path",
+            Action::SaveCameraPath => "Save camera path",
+            Action::LoadCameraPath => "Load camera path",
+            Action::ToggleDemoReel => "Start/Stop demo reel (stops on any input)",
+            Action::AddInstance => "Add model instance",
+            Action::RemoveInstance => "Remove model instance",
+        }
+    }
+}
+
+/// Prefix of key names that are bound together with the shift modifier (e.g. `Shift+P`)
+const SHIFT_PREFIX: &str = "Shift+";
+
+/// A key together with the state of the shift modifier
+type KeyBinding = (Keycode, bool);
+
+/// # KeyMap
+/// Assignment of keys (optionally combined with shift) to the actions they trigger
+pub struct KeyMap {
+    bindings: HashMap<KeyBinding, Action>,
+}
+
+/// # KeyMapOverrides
+/// Changes to the default bindings as stored in the key map file, e.g.:
+/// ```toml
+/// unbind = ["H"]
+///
+/// [bindings]
+/// Up = "move_forward"
+/// "Shift+P" = "supersampled_screenshot"
+/// ```
+/// Keys prefixed with `Shift+` are only triggered while shift is held.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeyMapOverrides {
+    /// Keys whose default binding is removed
+    unbind: Vec<String>,
+    /// Actions bound to the keys, replacing their default binding
+    bindings: HashMap<String, Action>,
+}
+
+impl KeyMap {
+    /// # Constructor
+    /// Loads the default bindings and applies the overrides from the given TOML resource (see `KeyMapOverrides`).
+    /// Returns the default bindings if the resource does not exist.
+    pub fn from_resources(res: &Resources, name: &str) -> Result<KeyMap, String> {
+        let mut keymap = KeyMap::default();
+        let data = match res.load_bytes(name) {
+            Ok(data) => data,
+            Err(e) => {
+                debug!(target: LOG_TARGET, "No key bindings loaded from \"{}\", using defaults: {:?}", name, e);
+                return Ok(keymap);
+            }
+        };
+
+        let overrides: KeyMapOverrides = toml::from_slice(&data)
+            .map_err(|e| format!("Invalid key bindings in {}: {}", name, e))?;
+        keymap.apply(overrides, Keycode::from_name)
+            .map_err(|e| format!("Invalid key bindings in {}: {}", name, e))?;
+        info!(target: LOG_TARGET, "Loaded key bindings from \"{}\"", name);
+        Ok(keymap)
+    }
+
+    /// Applies the overrides, resolving key names with the given lookup (SDL's key names in the application)
+    fn apply(&mut self, overrides: KeyMapOverrides, key_from_name: impl Fn(&str) -> Option<Keycode>) -> Result<(), String> {
+        for key_name in &overrides.unbind {
+            let key = parse_binding(key_name, &key_from_name)
+                .ok_or_else(|| format!("Unknown key \"{}\"", key_name))?;
+            self.bindings.remove(&key);
+        }
+        for (key_name, action) in overrides.bindings {
+            let key = parse_binding(&key_name, &key_from_name)
+                .ok_or_else(|| format!("Unknown key \"{}\"", key_name))?;
+            self.bindings.insert(key, action);
+        }
+        Ok(())
+    }
+
+    /// Get the action bound to the given key.
+    /// While shift is held, keys without a shifted binding trigger their regular action.
+    pub fn action(&self, key: Keycode, shift: bool) -> Option<Action> {
+        if shift {
+            if let Some(action) = self.bindings.get(&(key, true)) {
+                return Some(*action);
+            }
+        }
+        self.bindings.get(&(key, false)).copied()
+    }
+
+    /// Get the names of all keys bound to the given action
+    pub fn keys(&self, action: Action) -> Vec<String> {
+        let mut keys: Vec<String> = self.bindings.iter()
+            .filter(|(_, bound_action)| **bound_action == action)
+            .map(|((key, shift), _)| if *shift { format!("{}{}", SHIFT_PREFIX, key.name()) } else { key.name() })
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let bindings = [
+            (Keycode::Escape, Action::Quit),
+            (Keycode::H, Action::ShowHelp),
+            (Keycode::F1, Action::ShowHelp),
+            (Keycode::Plus, Action::IncreaseSamples),
+            (Keycode::KpPlus, Action::IncreaseSamples),
+            (Keycode::Minus, Action::DecreaseSamples),
+            (Keycode::KpMinus, Action::DecreaseSamples),
+            (Keycode::Num1, Action::SamplePresetLow),
+            (Keycode::Num2, Action::SamplePresetMedium),
+            (Keycode::Num3, Action::SamplePresetHigh),
+            (Keycode::Num4, Action::SamplePresetUltra),
+            (Keycode::Y, Action::ToggleShaderDetail),
+            (Keycode::RightBracket, Action::IncreaseTessLevel),
+            (Keycode::LeftBracket, Action::DecreaseTessLevel),
+            (Keycode::W, Action::MoveForward),
+            (Keycode::Up, Action::MoveForward),
+            (Keycode::A, Action::MoveLeft),
+            (Keycode::Left, Action::MoveLeft),
+            (Keycode::S, Action::MoveBackward),
+            (Keycode::Down, Action::MoveBackward),
+            (Keycode::D, Action::MoveRight),
+            (Keycode::Right, Action::MoveRight),
+            (Keycode::Space, Action::MoveUp),
+            (Keycode::PageUp, Action::MoveUp),
+            (Keycode::LCtrl, Action::MoveDown),
+            (Keycode::PageDown, Action::MoveDown),
+            (Keycode::LShift, Action::Sprint),
+            (Keycode::Kp0, Action::ResetCamera),
+            (Keycode::Home, Action::ResetCamera),
+            (Keycode::KpPeriod, Action::FrameMesh),
+            (Keycode::Kp7, Action::TopDownView),
+            (Keycode::End, Action::TopDownView),
+            (Keycode::Kp4, Action::PivotLeft),
+            (Keycode::Kp6, Action::PivotRight),
+            (Keycode::Kp2, Action::PivotDown),
+            (Keycode::Kp8, Action::PivotUp),
+            (Keycode::Kp3, Action::PivotBackward),
+            (Keycode::Kp9, Action::PivotForward),
+            (Keycode::Kp5, Action::CenterPivot),
+            (Keycode::Kp1, Action::ResetPivot),
+            (Keycode::I, Action::ToggleInvertY),
+            (Keycode::C, Action::ToggleGroundPlane),
+            (Keycode::M, Action::CyclePrograms),
+            (Keycode::R, Action::ReloadTextures),
+            (Keycode::F5, Action::ReloadAll),
+            (Keycode::N, Action::ToggleMipmaps),
+            (Keycode::F4, Action::ToggleMagFilter),
+            (Keycode::T, Action::ToggleDetail),
+            (Keycode::O, Action::ExportObj),
+            (Keycode::Period, Action::IncreaseShininess),
+            (Keycode::Comma, Action::DecreaseShininess),
+            (Keycode::E, Action::CycleEmissiveStrength),
+            (Keycode::G, Action::ToggleWorldSpaceNormals),
+            (Keycode::J, Action::ToggleSmoothHeight),
+            (Keycode::Q, Action::CycleTangentSpaceVector),
+            (Keycode::V, Action::ToggleTerrain),
+            (Keycode::F, Action::ToggleFlatNormals),
+            (Keycode::Num9, Action::ToggleVertexColors),
+            (Keycode::Z, Action::ToggleDepthTest),
+            (Keycode::L, Action::ToggleLetterbox),
+            (Keycode::K, Action::CycleLetterboxAspect),
+            (Keycode::B, Action::CycleClearColor),
+            (Keycode::U, Action::CycleLightColor),
+            (Keycode::F11, Action::ToggleShadows),
+            (Keycode::Num8, Action::ToggleHeadlamp),
+            (Keycode::Num5, Action::CycleFogMode),
+            (Keycode::Num6, Action::DecreaseFogDensity),
+            (Keycode::Num7, Action::IncreaseFogDensity),
+            (Keycode::Num0, Action::ToggleHdr),
+            (Keycode::KpMultiply, Action::IncreaseExposure),
+            (Keycode::KpDivide, Action::DecreaseExposure),
+            (Keycode::F12, Action::ToggleSkybox),
+            (Keycode::X, Action::ToggleAxes),
+            (Keycode::Tab, Action::CycleMapView),
+            (Keycode::F3, Action::ToggleGpuTimer),
+            (Keycode::P, Action::Screenshot),
+            (Keycode::F6, Action::RecordKeyframe),
+            (Keycode::F7, Action::ToggleCameraPath),
+            (Keycode::F8, Action::ClearCameraPath),
+            (Keycode::F9, Action::SaveCameraPath),
+            (Keycode::F10, Action::LoadCameraPath),
+            (Keycode::F2, Action::ToggleDemoReel),
+            (Keycode::Insert, Action::AddInstance),
+            (Keycode::Delete, Action::RemoveInstance),
+        ];
+        let shifted_bindings = [
+            (Keycode::P, Action::SupersampledScreenshot),
+            (Keycode::F, Action::ToggleFlatShading),
+            (Keycode::Period, Action::IncreaseAmbient),
+            (Keycode::Comma, Action::DecreaseAmbient),
+            (Keycode::Num0, Action::CycleTonemapOperator),
+            (Keycode::X, Action::ToggleGrid),
+            (Keycode::O, Action::ExportConfig),
+            (Keycode::V, Action::CycleDisplacementAxis),
+            (Keycode::T, Action::CycleDecimation),
+            (Keycode::C, Action::ToggleMultiMaterial),
+            (Keycode::Tab, Action::CycleMapInset),
+            (Keycode::Z, Action::ToggleBackfaceCulling),
+            (Keycode::G, Action::ToggleDoubleSided),
+            (Keycode::M, Action::ToggleMouseCapture),
+            (Keycode::I, Action::ToggleGroundMode),
+            (Keycode::Q, Action::ExportTextureReadback),
+            (Keycode::E, Action::ToggleUvClamp),
+            (Keycode::Num8, Action::TogglePlaceLight),
+            (Keycode::L, Action::ToggleWireframe),
+            (Keycode::U, Action::ToggleSunCycle),
+            (Keycode::R, Action::CycleRenderScale),
+            (Keycode::K, Action::IncreaseMinParallaxLayers),
+            (Keycode::J, Action::DecreaseMinParallaxLayers),
+            (Keycode::Y, Action::ToggleParallaxRefinement),
+            (Keycode::N, Action::IncreaseNormalStrength),
+            (Keycode::B, Action::DecreaseNormalStrength),
+            (Keycode::RightBracket, Action::SunCycleForward),
+            (Keycode::LeftBracket, Action::SunCycleBackward),
+        ];
+
+        let bindings = bindings.into_iter().map(|(key, action)| ((key, false), action));
+        let shifted_bindings = shifted_bindings.into_iter().map(|(key, action)| ((key, true), action));
+        KeyMap {
+            bindings: bindings.chain(shifted_bindings).collect(),
+        }
+    }
+}
+
+/// Parses a key name, which may be prefixed with `Shift+`
+fn parse_binding(name: &str, key_from_name: impl Fn(&str) -> Option<Keycode>) -> Option<KeyBinding> {
+    match name.strip_prefix(SHIFT_PREFIX) {
+        Some(key_name) => key_from_name(key_name.trim()).map(|key| (key, true)),
+        None => key_from_name(name.trim()).map(|key| (key, false)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-in for SDL's key name lookup, which is not available without the SDL library
+    fn key_from_name(name: &str) -> Option<Keycode> {
+        match name {
+            "Up" => Some(Keycode::Up),
+            "M" => Some(Keycode::M),
+            _ => None,
+        }
+    }
+
+    fn parse(file: &str) -> Result<KeyMapOverrides, String> {
+        toml::from_str(file).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn parses_actions_by_snake_case_name() {
+        let overrides = parse("[bindings]\nUp = \"move_forward\"\nM = \"export_obj\"\n\"Shift+P\" = \"supersampled_screenshot\"\n").unwrap();
+        assert_eq!(overrides.bindings["Up"], Action::MoveForward);
+        assert_eq!(overrides.bindings["M"], Action::ExportObj);
+        assert_eq!(overrides.bindings["Shift+P"], Action::SupersampledScreenshot);
+        assert!(overrides.unbind.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_actions_and_fields() {
+        assert!(parse("[bindings]\nUp = \"MoveForward\"\n").is_err());
+        assert!(parse("[bindings]\nUp = \"fly\"\n").is_err());
+        assert!(parse("unbound = [\"M\"]\n").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let mut keymap = KeyMap::default();
+        let overrides = parse("[bindings]\nNoSuchKey = \"quit\"\n").unwrap();
+        assert!(keymap.apply(overrides, key_from_name).is_err());
+
+        let overrides = parse("unbind = [\"Shift+NoSuchKey\"]\n").unwrap();
+        assert!(keymap.apply(overrides, key_from_name).is_err());
+    }
+
+    #[test]
+    fn overrides_replace_default_bindings() {
+        let mut keymap = KeyMap::default();
+        assert_eq!(keymap.action(Keycode::M, false), Some(Action::CyclePrograms));
+
+        let overrides = parse("unbind = [\"M\"]\n\n[bindings]\nUp = \"quit\"\n\"Shift+Up\" = \"export_obj\"\n").unwrap();
+        keymap.apply(overrides, key_from_name).unwrap();
+
+        assert_eq!(keymap.action(Keycode::M, false), None);
+        assert_eq!(keymap.action(Keycode::Up, false), Some(Action::Quit));
+        assert_eq!(keymap.action(Keycode::Up, true), Some(Action::ExportObj));
+        // Untouched defaults are kept
+        assert_eq!(keymap.action(Keycode::Escape, false), Some(Action::Quit));
+    }
+}
@@ -0,0 +1,4 @@
+//! Context-free parts of the demo, which can be used from benchmarks as well as from the application
+pub mod decimation;
+pub mod heightmap;
+pub mod mesh;
@@ -4,28 +4,71 @@ extern crate image;
 extern crate sdl2;
 
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
 
-use cgmath::{Matrix4, Vector3};
+use cgmath::{InnerSpace, Matrix4, Vector2, Vector3, Zero};
 use gl::types::GLuint;
 use log::{debug, info, Level, warn};
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Mod;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
 use sdl2::video::WindowBuildError;
 use sdl2::VideoSubsystem;
 
-use crate::glhelper::{Camera, MovementDirection::{BACKWARD, FORWARD, LEFT, RIGHT}, Program, TextureCollection, utils::{calc_projection_matrix, configure_vao, fill_vbo}};
+use crate::glhelper::{Camera, CameraPath, CubeMap, FloorGrid, Framebuffer, GpuTimer, GroundPlane, Lines, MovementDirection::{self, BACKWARD, FORWARD, LEFT, RIGHT}, PerFrame, PER_FRAME_BINDING, PER_FRAME_BLOCK, Program, ProgramSources, ScreenQuad, Skybox, TextLine, TextOverlay, TextureCollection, UniformBuffer, utils::{calc_inverse_matrix, calc_look_at_matrix, calc_orthographic_matrix, calc_projection_matrix, configure_instance_attributes, configure_vao, fill_vbo, gl_query, set_polygon_offset, unproject}};
+use cg_demo::decimation;
+use cg_demo::heightmap::HeightMap;
+use cg_demo::mesh::{self, VertexAttribute, VertexLayout};
+
 use crate::glhelper::MovementDirection::{DOWN, UP};
+use crate::glhelper::tex_collection::SHADOW_MAP_UNIT;
+use crate::config::{Config, PoseConfig};
+use crate::demo_reel::DemoReel;
+use crate::keymap::{Action, KeyMap};
+use crate::material::MaterialDef;
 use crate::resources::Resources;
+use crate::sun_cycle::SunCycle;
 
+pub mod config;
+pub mod demo_reel;
+pub mod diagnostics;
 pub mod glhelper;
+pub mod keymap;
+pub mod material;
 pub mod resources;
+pub mod sun_cycle;
 
 const LOG_TARGET: &str = "Main";
-
-const WINDOW_TITLE: &str = "Displacement Map Demo";
+/// Environment variable to set the log level (`error`, `warn`, `info`, `debug` or `trace`)
+const LOG_LEVEL_VAR: &str = "CG_DEMO_LOG";
+const DEFAULT_LOG_LEVEL: Level = Level::Info;
+
+/// Command line argument followed by a window title, which overrides the configured title
+const TITLE_ARGUMENT: &str = "--title";
+/// Command line argument to measure the frame times at all sample amounts instead of starting interactively
+const BENCHMARK_ARGUMENT: &str = "--benchmark";
+/// Command line argument to validate the resources with a hidden window, print a report and exit
+const CHECK_ARGUMENT: &str = "--check";
+/// Amount of frames rendered at every sample amount in the benchmark
+const BENCHMARK_FRAMES: u32 = 100;
+/// Highest sample amount measured by the benchmark (1024x1024)
+const BENCHMARK_MAX_SAMPLES_IDX: usize = 5;
 const WINDOW_WIDTH: u32 = 900;
 const WINDOW_HEIGHT: u32 = 700;
 
+/// Aspect ratios (`width / height`) that can be kept by letterboxing the viewport
+const LETTERBOX_ASPECTS: [(&str, f32); 4] = [
+    ("9:7", WINDOW_WIDTH as f32 / WINDOW_HEIGHT as f32),
+    ("4:3", 4.0 / 3.0),
+    ("16:9", 16.0 / 9.0),
+    ("1:1", 1.0),
+];
+
 const MIN_X: f32 = -1.0;
 const MAX_X: f32 = 1.0;
 const MIN_Y: f32 = -1.0;
@@ -33,50 +76,260 @@ const MAX_Y: f32 = 1.0;
 
 const SAMPLE_STEPS_X: [f32; 7] = [2.0, 4.0, 16.0, 64.0, 256.0, 1024.0, 2048.0];
 const SAMPLE_STEPS_Y: [f32; 7] = [2.0, 4.0, 16.0, 64.0, 256.0, 1024.0, 2048.0];
+/// Readable names of the sample amounts
+const SAMPLE_PRESET_NAMES: [&str; 7] = ["Low", "Very Coarse", "Coarse", "Medium", "High", "Ultra", "Extreme"];
 const SAMPLE_START_IDX: usize = 3;
+/// Appended to the window title while the mesh is generated in the background
+const GENERATING_TITLE_SUFFIX: &str = " (generating mesh...)";
+/// Shown in the status overlay while the mesh is generated in the background
+const GENERATING_STATUS_TEXT: &str = "Generating mesh...";
+/// Levels of the terrain decimation with the maximum deviation of the height from the full-resolution surface
+const DECIMATION_LEVELS: [(&str, f32); 4] = [("Off", 0.0), ("Low", 0.0005), ("Medium", 0.002), ("High", 0.008)];
+
+/// Distance the camera moves with one key event (scaled by the movement speed of the camera)
+const MOVEMENT_AMOUNT: f32 = 0.1;
+
+/// Height of the camera above the center of the mesh in the top-down view
+const TOP_DOWN_HEIGHT: f32 = 2.5;
+/// Distance the scene pivot is moved per key press
+const PIVOT_STEP: f32 = 0.05;
+
+/// Height of the map inset and its distance to the corner as fraction of the viewport height
+const MAP_INSET_SIZE: f32 = 0.3;
+const MAP_INSET_MARGIN: f32 = 0.02;
+
+/// Length of the coordinate axes drawn at the origin
+const AXES_LENGTH: f32 = 1.5;
+/// Distance of the edges of the floor grid to the origin; the grid fades out long before
+const GRID_EXTENT: f32 = 100.0;
+/// Color of the floor grid lines (linear)
+const GRID_COLOR: Vector3<f32> = Vector3::new(0.5, 0.5, 0.5);
+/// Depth offset `(factor, units)` of the wireframe overlay, which pulls the lines in front of the filled triangles
+const WIREFRAME_POLYGON_OFFSET: (f32, f32) = (-1.0, -1.0);
+
+/// Minimum distance of the camera to the plane of the mesh, if the camera collides with it
+const GROUND_PLANE_OFFSET: f32 = 0.05;
+/// Normal of the xy-plane the mesh lies in, pointing to the negative z-side it is viewed from.
+/// The ground mode keeps the height of the camera along it.
+const MESH_PLANE_NORMAL: Vector3<f32> = Vector3::new(0.0, 0.0, -1.0);
+
+/// Initial tessellation level of tessellated programs
+const TESS_LEVEL_START: f32 = 4.0;
+
+/// Initial amount of layers for parallax programs (1 = a single offset step)
+const PARALLAX_LAYERS_START: i32 = 1;
+/// Largest amount of layers for parallax programs
+const MAX_PARALLAX_LAYERS: i32 = 64;
+/// Initial amount of layers for parallax programs when looking straight at the surface
+const MIN_PARALLAX_LAYERS_START: i32 = 8;
+
+const MAX_INSTANCES: usize = 64;
+const INSTANCE_SPACING: f32 = 2.2;
+
+/// Height of the terrain at the maximum value of the height map
+const TERRAIN_HEIGHT_SCALE: f32 = 0.3;
+
+const DETAIL_SCALE: f32 = 8.0;
+
+/// Change of the ambient strength with one step; the ambient light lights all surfaces regardless of the light position
+const AMBIENT_STEP: f32 = 0.05;
+/// Change of the normal map strength with one step and its upper limit
+const NORMAL_STRENGTH_STEP: f32 = 0.25;
+const MAX_NORMAL_STRENGTH: f32 = 4.0;
+/// Names of the tone mapping operators, the index is passed to the tone mapping shader
+const TONEMAP_OPERATORS: [&str; 2] = ["Reinhard", "ACES"];
+/// Render scales that can be cycled through; the configured scale is limited to the range of these
+const RENDER_SCALES: [f32; 6] = [0.25, 0.5, 0.75, 1.0, 1.5, 2.0];
+/// Factor to change the exposure of the HDR image with one step
+const EXPOSURE_STEP: f32 = 1.25;
+const MIN_EXPOSURE: f32 = 0.05;
+const MAX_EXPOSURE: f32 = 20.0;
+/// Range of the factor applied to the camera movement while sprinting
+const MIN_SPRINT_FACTOR: f32 = 1.0;
+const MAX_SPRINT_FACTOR: f32 = 20.0;
+
+const MIN_SHININESS: f32 = 1.0;
+const MAX_SHININESS: f32 = 256.0;
+
+const EMISSIVE_STRENGTHS: [f32; 5] = [0.0, 0.5, 1.0, 2.0, 4.0];
+const EMISSIVE_START_IDX: usize = 2;
+
+const CLEAR_COLORS: [(&str, [f32; 3]); 4] = [
+    ("gray", [0.8, 0.8, 0.8]),
+    ("black", [0.0, 0.0, 0.0]),
+    ("white", [1.0, 1.0, 1.0]),
+    ("blue", [0.2, 0.3, 0.45]),
+];
+
+const LIGHT_COLORS: [(&str, [f32; 3]); 6] = [
+    ("white", [1.0, 1.0, 1.0]),
+    ("warm", [1.0, 0.85, 0.65]),
+    ("cool", [0.7, 0.85, 1.0]),
+    ("red", [1.0, 0.2, 0.2]),
+    ("green", [0.2, 1.0, 0.2]),
+    ("blue", [0.2, 0.2, 1.0]),
+];
+
+/// Highest sample amount the demo reel steps through, to keep regenerating the mesh fast
+const DEMO_REEL_MAX_SAMPLES_IDX: usize = 4;
+
+/// Distance above the clicked surface the light is placed at
+const LIGHT_PLACEMENT_HEIGHT: f32 = 0.2;
+/// Fraction of a full day the sun is moved per key press
+const SUN_SCRUB_STEP: f32 = 1.0 / 48.0;
+
+/// Fog modes as implemented by the fragment shaders (the index is passed as `fogMode`)
+const FOG_MODES: [&str; 3] = ["off", "linear", "exponential"];
+const MIN_FOG_DENSITY: f32 = 0.01;
+const MAX_FOG_DENSITY: f32 = 10.0;
+
+/// Width and height of the shadow map in pixels
+const SHADOW_MAP_SIZE: u32 = 2048;
+/// Half of the width and height of the area around the origin covered by the shadow map
+const SHADOW_EXTENT: f32 = 2.0;
+/// Distance of the directional light to the origin when rendering the shadow map
+const SHADOW_LIGHT_DISTANCE: f32 = 4.0;
+
+/// Vectors of the tangent space that can be visualized
+const TANGENT_SPACE_VECTORS: [&str; 3] = ["tangent", "bitangent", "normal"];
+
+const OBJ_EXPORT_NAME: &str = "export/mesh.obj";
+/// Exported settings, which can be used as configuration by renaming the file to [`CONFIG_NAME`]
+const CONFIG_EXPORT_NAME: &str = "export/config.toml";
+/// Folder the screenshots are saved in
+const SCREENSHOT_DIR: &str = "export";
+/// Factor the resolution of supersampled screenshots is scaled by, compared to the window
+const SUPERSAMPLE_FACTOR: u32 = 4;
+
+const SHADER_DIR: &str = "shaders";
+/// Time between two checks whether shader sources were modified, if programs are reloaded automatically
+const SHADER_RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+/// Program for unlit, colored lines (in a subfolder, so it is not listed as a program for the mesh)
+const LINE_PROGRAM_NAME: &str = "shaders/util/line";
+/// Program to show a single texture on the whole screen
+const MAP_VIEW_PROGRAM_NAME: &str = "shaders/util/map_view";
+/// Program to tone map the HDR buffer onto the screen, sharing the vertex shader of the map view
+const TONEMAP_PROGRAM: ProgramSources = ProgramSources::Files("shaders/util/map_view.vert", "shaders/util/tonemap.frag");
+const SKYBOX_PROGRAM_NAME: &str = "shaders/util/skybox";
+const GRID_PROGRAM_NAME: &str = "shaders/util/grid";
+/// Program to draw the text of overlays with the built-in bitmap font
+const TEXT_PROGRAM_NAME: &str = "shaders/util/text";
+/// Programs drawing helpers (lines, overlays, ...), which are loaded in addition to the programs in the shader folder.
+/// [`AppState::programs_mut`] pairs them with the programs of the state in this order.
+const UTIL_PROGRAMS: [ProgramSources; 6] = [
+    ProgramSources::Named(LINE_PROGRAM_NAME),
+    ProgramSources::Named(MAP_VIEW_PROGRAM_NAME),
+    TONEMAP_PROGRAM,
+    ProgramSources::Named(SKYBOX_PROGRAM_NAME),
+    ProgramSources::Named(GRID_PROGRAM_NAME),
+    ProgramSources::Named(TEXT_PROGRAM_NAME),
+];
+/// Colors (straight alpha) of the panel, the category headings and the entries of the help overlay
+const HELP_BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.75];
+const HELP_HEADING_COLOR: [f32; 4] = [1.0, 0.8, 0.3, 1.0];
+const HELP_TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+/// Resource folder of the skybox faces (see [`glhelper::cube_map::FACE_NAMES`]) and their file extension
+const SKYBOX_FOLDER: &str = "textures/skybox";
+const SKYBOX_EXTENSION: &str = "jpg";
+/// Uniforms that are set for every program
+const REQUIRED_UNIFORMS: [&str; 3] = ["projection", "view", "model"];
+/// Uniforms of optional features, which are only supported by some programs (e.g. unlit debug programs don't need the light)
+const FEATURE_UNIFORMS: [&str; 30] = ["viewPos", "lightPos", "tangentSpaceVector", "detailEnabled", "detailScale", "shininess", "emissiveStrength", "worldSpaceNormals", "smoothHeight", "tessLevel", "tiling", "directXNormals", "lightColor", "parallaxLayers", "shadowMap", "shadowsEnabled", "lightSpace", "shadowBias", "fogMode", "fogColor", "fogDensity", "fogStart", "fogEnd", "flatShading", "ambientStrength", "doubleSided", "minParallaxLayers", "parallaxRefinement", "normalStrength", "multiMaterial"];
+const CAMERA_PATH_NAME: &str = "camera_path.txt";
+const KEYMAP_NAME: &str = "keymap.toml";
+const CONFIG_NAME: &str = "config.toml";
+
+const RESOURCE_DIR: &str = "resources";
+const RESOURCE_ARCHIVE: &str = "resources.zip";
+
+/// Folder that may contain a material definition, which replaces the texture naming convention
+const MATERIAL_FOLDER: &str = "textures";
+const TEXTURE_NAME: &str = "textures/wall";
+const TEXTURE_EXTENSION: &str = "jpg";
 
 ///
 /// Function that is executed when starting the compiled program
 ///
 fn main() {
-    simple_logger::init_with_level(Level::Debug).unwrap();
+    let (log_level, invalid_log_level) = determine_log_level();
+    simple_logger::init_with_level(log_level).unwrap();
+    if let Some(value) = invalid_log_level {
+        warn!(target: LOG_TARGET, "Invalid log level \"{}\" in {}, using {}", value, LOG_LEVEL_VAR, log_level);
+    }
+
+    let res = load_resources().unwrap();
+    // A configuration that could not be loaded is never written back, so it can still be fixed by hand
+    let (config, config_loaded) = match Config::from_resources(&res, CONFIG_NAME) {
+        Ok(config) => (config, true),
+        Err(e) => {
+            warn!(target: LOG_TARGET, "Could not load configuration, using defaults: {}", e);
+            (Config::default(), false)
+        }
+    };
+    let title = title_argument().unwrap_or_else(|| config.window_title.clone());
+    let check = std::env::args().skip(1).any(|arg| arg == CHECK_ARGUMENT);
 
     let sdl = sdl2::init().unwrap();
     let video_subsystem = sdl.video().unwrap();
-    let window = configure_and_create_window(&video_subsystem).unwrap();
+    // The check only needs the OpenGL context of the window, so it is not shown
+    let mut window = configure_and_create_window(&video_subsystem, &title, check).unwrap();
+    set_window_icon(&mut window, &res, &config.window_icon);
 
     // Configure OpenGL to use the SDL2 implementation of the interfaces
     let _gl_context = window.gl_create_context().unwrap();
     let _gl = gl::load_with(|s| video_subsystem.gl_get_proc_address(s) as *const std::os::raw::c_void);
+    log_context_info();
 
-    unsafe {
-        // Setup viewport
-        gl::Viewport(0, 0,
-                     WINDOW_WIDTH as gl::types::GLsizei,
-                     WINDOW_HEIGHT as gl::types::GLsizei);
-        // Set gray background color
-        gl::ClearColor(0.8, 0.8, 0.8, 1.0);
+    if check {
+        let passed = diagnostics::run_check(&res);
+        std::process::exit(if passed { 0 } else { 1 });
+    }
 
+    unsafe {
         // Enable features
         gl::Enable(gl::DEPTH_TEST);
+        // Convert the linear shader output to sRGB, matching the sRGB-decoded color textures
+        gl::Enable(gl::FRAMEBUFFER_SRGB);
         gl::Enable(gl::CULL_FACE);
+        // Filter across the edges of cube map faces
+        gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
     }
 
     // Load shader
-    let res = Resources::from_relative_exe_path(Path::new("resources")).unwrap();
-    let mut state = AppState::new(&res).unwrap();
+    let mut state = AppState::new(&res, &config).unwrap();
 
-    // init immutable data
-    let demo_texture = TextureCollection::from_resources(&res, "textures/wall", "jpg").unwrap();
-    let light_pos: Vector3<f32> = cgmath::vec3(1.0, 1.0, 1.0);
-    let model_trans: Matrix4<f32> = cgmath::One::one(); // no transformation for the displayed model; only the camera changes
+    if std::env::args().skip(1).any(|arg| arg == BENCHMARK_ARGUMENT) {
+        // Measure the rendering instead of the display rate
+        if let Err(e) = video_subsystem.gl_set_swap_interval(0) {
+            warn!(target: LOG_TARGET, "Could not disable vsync for the benchmark: {}", e);
+        }
+        let model_trans = state.model_matrix();
+        run_benchmark(&mut state, &window, &model_trans);
+        return;
+    }
 
-    log_instructions();
+    if config.show_controls {
+        state.toggle_help();
+        if config_loaded {
+            state.store_controls_shown(&res);
+        }
+    } else {
+        info!(target: LOG_TARGET, "Press {} to show the controls", state.keymap.keys(Action::ShowHelp).join("/"));
+    }
 
     let mut event_stream = sdl.event_pump().unwrap();
+    let mut last_frame_start = Instant::now();
+    let mut title_shows_generation = false;
     loop {
+        let frame_start = Instant::now();
+        let frame_delta = frame_start.duration_since(last_frame_start);
+        last_frame_start = frame_start;
+
         for event in event_stream.poll_iter() {
-            handle_event(&mut state, event);
+            handle_event(&mut state, &res, event);
+        }
+        if sdl.mouse().relative_mouse_mode() != state.mouse_captured {
+            apply_mouse_capture(&mut state, &sdl.mouse(), &window);
         }
 
         // Terminate if necessary
@@ -84,214 +337,1040 @@ fn main() {
             break;
         }
 
-        // rendering
-        unsafe {
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        state.auto_reload_programs(&res);
+
+        // Upload the mesh once the worker has generated it; until then the previous mesh is drawn
+        state.receive_mesh(false);
+        if state.generating_mesh() != title_shows_generation {
+            title_shows_generation = state.generating_mesh();
+            let shown_title = if title_shows_generation { format!("{}{}", title, GENERATING_TITLE_SUFFIX) } else { title.clone() };
+            if let Err(e) = window.set_title(&shown_title) {
+                debug!(target: LOG_TARGET, "Could not update the window title: {}", e);
+            }
         }
 
-        let proj = calc_projection_matrix(state.camera.zoom().to_radians(), (WINDOW_WIDTH as f32) / (WINDOW_HEIGHT as f32), 0.1, 100.0).unwrap();
-        let view = state.camera.calc_view_matrix();
-        let pos = state.camera.position();
+        // Apply the mouse movement, then animate the camera along the recorded path
+        state.camera.update_rotation();
+        state.update_demo_reel();
+        state.update_sun_cycle(frame_delta);
+        if let Some(pose) = state.camera_path.current_pose() {
+            state.camera.set_pose(&pose);
+        }
 
-        state.current_program().unwrap().set_active();
-        TextureCollection::configure_program(&state.current_program().unwrap());
-        demo_texture.set_active();
+        // rendering
+        let proj = calc_projection_matrix(state.camera.zoom().to_radians(), state.aspect, 0.1, 100.0).unwrap();
+        let view = state.camera.calc_view_matrix();
+        let model_trans = state.model_matrix();
+        let light_pos = state.light_pos();
+
+        render_shadow_map(&mut state, &model_trans, &light_pos);
+        render_output(&mut state, None, &proj, &view, &model_trans, &light_pos);
+        if let Some(cursor) = state.pick_request.take() {
+            if state.place_light {
+                place_light(&mut state, cursor, &proj, &view);
+            } else {
+                probe_surface(&mut state, cursor, &proj, &view);
+            }
+        }
+        if let Some(scale) = state.screenshot_scale.take() {
+            save_screenshot(&mut state, &res, scale, &proj, &view, &model_trans, &light_pos);
+        }
+        // The overlays are drawn after the screenshot, so they are not part of it
+        state.draw_overlays();
 
-        // We can only borrow the value here, so the `set_active`-call needs to retrieve it manually
-        let current_program = state.current_program().unwrap();
+        // Swap buffer
+        window.gl_swap_window();
 
-        current_program.set_property_mat4("projection", &proj);
-        current_program.set_property_mat4("view", &view);
-        current_program.set_property_mat4("model", &model_trans);
+        // Limit the frame rate by waiting for the rest of the frame time
+        if let Some(frame_time) = state.frame_time {
+            if let Some(remaining) = frame_time.checked_sub(frame_start.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
+    }
+}
 
-        current_program.set_property_vec3("viewPos", &pos);
-        current_program.set_property_vec3("lightPos", &light_pos);
+/// Renders [`BENCHMARK_FRAMES`] frames at every sample amount up to [`BENCHMARK_MAX_SAMPLES_IDX`] from the initial camera pose
+/// and prints the average frame time (and GPU time, if supported) as a table to stdout
+fn run_benchmark(state: &mut AppState, window: &sdl2::video::Window, model_trans: &Matrix4<f32>) {
+    state.camera.reset_position();
+    let proj = calc_projection_matrix(state.camera.zoom().to_radians(), state.aspect, 0.1, 100.0).unwrap();
+    let view = state.camera.calc_view_matrix();
+    let light_pos = state.light_pos();
+
+    info!(target: LOG_TARGET, "Benchmarking {} frames per sample amount", BENCHMARK_FRAMES);
+    println!("| {:<20} | {:>10} | {:>15} | {:>13} |", "Samples", "Vertices", "Frame time (ms)", "GPU time (ms)");
+    println!("|{:-<22}|{:->12}|{:->17}|{:->15}|", "", "", "", "");
+    for samples_idx in 0..=BENCHMARK_MAX_SAMPLES_IDX {
+        state.set_sample_index(samples_idx as isize);
+        state.receive_mesh(true);
+        if state.samples_idx != samples_idx {
+            println!("| {:<20} | {:>10} | {:>15} | {:>13} |", sample_preset_name(samples_idx), sample_vertex_count(samples_idx), "over budget", "-");
+            continue;
+        }
+        state.gpu_timer = GpuTimer::new().map(GpuTimer::without_logging).ok();
 
+        let start = Instant::now();
+        for _ in 0..BENCHMARK_FRAMES {
+            render_shadow_map(state, model_trans, &light_pos);
+            render_output(state, None, &proj, &view, model_trans, &light_pos);
+            window.gl_swap_window();
+        }
         unsafe {
-            gl::BindVertexArray(state.vao_id);
-            gl::DrawArrays(
-                gl::TRIANGLES,
-                0,
-                state.point_count as gl::types::GLsizei,
-            );
+            gl::Finish();
         }
+        let frame_time = start.elapsed() / BENCHMARK_FRAMES;
+        let gpu_time = state.gpu_timer.as_mut()
+            .and_then(GpuTimer::take_average)
+            .map_or_else(|| "-".to_string(), |time| format!("{:.3}", time.as_secs_f64() * 1000.0));
 
-        // Swap buffer
-        window.gl_swap_window();
+        println!("| {:<20} | {:>10} | {:>15.3} | {:>13} |", sample_preset_name(samples_idx), state.point_count,
+            frame_time.as_secs_f64() * 1000.0, gpu_time);
+    }
+    state.gpu_timer = None;
+}
+
+/// Logs the driver and the limits of the current OpenGL context
+fn log_context_info() {
+    let describe = |name| gl_query::get_string(name).unwrap_or_else(|| "unknown".to_string());
+    info!(target: LOG_TARGET, "OpenGL {} (GLSL {}) on {} by {}",
+        describe(gl::VERSION), describe(gl::SHADING_LANGUAGE_VERSION), describe(gl::RENDERER), describe(gl::VENDOR));
+    debug!(target: LOG_TARGET, "Maximum texture size: {}, maximum texture LOD bias: {}",
+        gl_query::get_int(gl::MAX_TEXTURE_SIZE), gl_query::get_float(gl::MAX_TEXTURE_LOD_BIAS));
+}
+
+/// Reads the log level from the environment, falling back to the default level.
+/// The second value contains the content of the variable, if it could not be parsed.
+fn determine_log_level() -> (Level, Option<String>) {
+    match std::env::var(LOG_LEVEL_VAR) {
+        Ok(value) => match value.parse::<Level>() {
+            Ok(level) => (level, None),
+            Err(_) => (DEFAULT_LOG_LEVEL, Some(value)),
+        },
+        Err(_) => (DEFAULT_LOG_LEVEL, None),
+    }
+}
+
+/// Uses the resource folder next to the executable, or the resource archive if there is no folder
+fn load_resources() -> Result<Resources, resources::Error> {
+    let exe_dir = resources::exe_dir()?;
+
+    if !exe_dir.join(RESOURCE_DIR).is_dir() && exe_dir.join(RESOURCE_ARCHIVE).is_file() {
+        info!(target: LOG_TARGET, "Loading resources from archive \"{}\"", RESOURCE_ARCHIVE);
+        return Resources::from_relative_exe_archive(Path::new(RESOURCE_ARCHIVE), Path::new(RESOURCE_DIR));
     }
+    Resources::from_relative_exe_path(Path::new(RESOURCE_DIR))
 }
 
-/// Creates an SDL Window and configures it for use with OpenGl
-fn configure_and_create_window(video_sys: &VideoSubsystem) -> Result<sdl2::video::Window, WindowBuildError> {
+/// Creates an SDL Window and configures it for use with OpenGl. A hidden window only provides the OpenGL context.
+fn configure_and_create_window(video_sys: &VideoSubsystem, title: &str, hidden: bool) -> Result<sdl2::video::Window, WindowBuildError> {
     // Configure OpenGL attributes
     let gl_attr = video_sys.gl_attr();
     gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
     gl_attr.set_context_version(4, 5);
+    gl_attr.set_framebuffer_srgb_compatible(true);
 
     // Initialize Window
-    video_sys
-        .window(WINDOW_TITLE, WINDOW_WIDTH, WINDOW_HEIGHT)
-        .opengl()
-        .build()
+    let mut window = video_sys.window(title, WINDOW_WIDTH, WINDOW_HEIGHT);
+    window.opengl().resizable();
+    if hidden {
+        window.hidden();
+    }
+    window.build()
+}
+
+/// Reads the window title from the command line arguments (`--title <title>`), if given
+fn title_argument() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == TITLE_ARGUMENT {
+            return args.next();
+        }
+    }
+    None
+}
+
+/// Loads the given image from the resources and uses it as icon of the window.
+/// Without the image the default icon of the platform is kept.
+fn set_window_icon(window: &mut sdl2::video::Window, res: &Resources, name: &str) {
+    let icon = res.load_bytes(name)
+        .map_err(|e| format!("{:?}", e))
+        .and_then(|data| image::load_from_memory(&data).map_err(|e| e.to_string()));
+    let mut icon = match icon {
+        Ok(icon) => icon.to_rgba8(),
+        Err(e) => {
+            debug!(target: LOG_TARGET, "No window icon loaded from \"{}\": {}", name, e);
+            return;
+        }
+    };
+
+    let (width, height) = icon.dimensions();
+    let surface = Surface::from_data(&mut icon, width, height, width * 4, PixelFormatEnum::RGBA32);
+    match surface {
+        Ok(surface) => {
+            window.set_icon(surface);
+            info!(target: LOG_TARGET, "Using {}x{} window icon \"{}\"", width, height, name);
+        }
+        Err(e) => warn!(target: LOG_TARGET, "Could not create the window icon from \"{}\": {}", name, e),
+    }
+}
+
+/// Binds the given framebuffer or the window (if `None`) and renders the frame into it.
+/// With HDR enabled, the frame is rendered into the HDR buffer first and tone mapped into the target afterwards.
+/// The window is rendered at the render scale; a frame without HDR at a different scale is rendered into the scaled buffer
+/// first and stretched onto the window afterwards.
+fn render_output(state: &mut AppState, target: Option<&Framebuffer>, proj: &Matrix4<f32>, view: &Matrix4<f32>, model_trans: &Matrix4<f32>, light_pos: &Vector3<f32>) {
+    let (width, height) = match target {
+        Some(framebuffer) => (framebuffer.width(), framebuffer.height()),
+        None => state.render_resolution(),
+    };
+    let hdr_buffer = if state.hdr_enabled { state.take_hdr_buffer(width, height) } else { None };
+    let scaled_buffer = if hdr_buffer.is_none() && target.is_none() && state.render_scale != 1.0 {
+        state.take_scaled_buffer(width, height)
+    } else {
+        None
+    };
+
+    if let Some(offscreen_buffer) = hdr_buffer.as_ref().or(scaled_buffer.as_ref()) {
+        offscreen_buffer.bind();
+        unsafe {
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+        render_frame(state, proj, view, model_trans, light_pos);
+    }
+
+    match target {
+        Some(framebuffer) => framebuffer.bind(),
+        None => {
+            Framebuffer::unbind();
+            state.update_viewport();
+        }
+    }
+    unsafe {
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+    }
+    match (hdr_buffer, scaled_buffer) {
+        (Some(hdr_buffer), _) => {
+            state.draw_tonemapped(&hdr_buffer);
+            state.hdr_buffer = Some(hdr_buffer);
+        }
+        (None, Some(scaled_buffer)) => {
+            state.draw_scaled(&scaled_buffer);
+            state.scaled_buffer = Some(scaled_buffer);
+        }
+        (None, None) => render_frame(state, proj, view, model_trans, light_pos),
+    }
+}
+
+/// Renders either the selected texture map or the scene, with the selected inset map in the corner
+fn render_frame(state: &mut AppState, proj: &Matrix4<f32>, view: &Matrix4<f32>, model_trans: &Matrix4<f32>, light_pos: &Vector3<f32>) {
+    if let Some(map_idx) = state.map_view {
+        state.draw_map(map_idx);
+    } else {
+        render_scene(state, proj, view, model_trans, light_pos);
+        if let Some(map_idx) = state.map_inset {
+            state.draw_map_inset(map_idx);
+        }
+    }
+}
+
+/// Renders the frame again into an offscreen buffer with `scale` times the resolution of the viewport and saves it as PNG.
+/// The aspect ratio stays the same, so the projection of the frame is reused.
+/// If multisampling is configured, the frame is rendered with multiple samples per pixel and resolved afterwards.
+/// With HDR enabled, the screenshot is tone mapped the same way as the window.
+fn save_screenshot(state: &mut AppState, res: &Resources, scale: u32, proj: &Matrix4<f32>, view: &Matrix4<f32>, model_trans: &Matrix4<f32>, light_pos: &Vector3<f32>) {
+    let (_, _, width, height) = state.viewport();
+    let framebuffer = match Framebuffer::new(width * scale, height * scale) {
+        Ok(framebuffer) => framebuffer,
+        Err(e) => {
+            warn!(target: LOG_TARGET, "Could not create the buffer for a {}x screenshot: {}", scale, e);
+            return;
+        }
+    };
+
+    let multisampled = (state.screenshot_samples > 1).then(|| {
+        Framebuffer::multisampled(framebuffer.width(), framebuffer.height(), state.screenshot_samples)
+            .map_err(|e| warn!(target: LOG_TARGET, "Saving the screenshot without multisampling: {}", e))
+            .ok()
+    }).flatten();
+
+    render_output(state, Some(multisampled.as_ref().unwrap_or(&framebuffer)), proj, view, model_trans, light_pos);
+    if let Some(multisampled) = &multisampled {
+        if let Err(e) = multisampled.blit_to(&framebuffer) {
+            warn!(target: LOG_TARGET, "Resolving the samples of the screenshot failed: {}", e);
+        }
+    }
+    let pixels = framebuffer.read_pixels();
+    Framebuffer::unbind();
+    state.update_viewport();
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let name = format!("{}/screenshot_{}_{}x.png", SCREENSHOT_DIR, timestamp, scale);
+    let result = res.construct_path(&name)
+        .map_err(|e| format!("Could not determine the path: {:?}", e))
+        .and_then(|path| {
+            let mut image = image::RgbaImage::from_raw(framebuffer.width(), framebuffer.height(), pixels)
+                .ok_or_else(|| "Pixel data does not match the image size".to_string())?;
+            // OpenGL stores the rows bottom to top
+            image::imageops::flip_vertical_in_place(&mut image);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            image.save(&path).map_err(|e| e.to_string())?;
+            Ok(path)
+        });
+
+    match result {
+        Ok(path) => info!(target: LOG_TARGET, "Saved {}x{} screenshot to {:?}", framebuffer.width(), framebuffer.height(), path),
+        Err(e) => warn!(target: LOG_TARGET, "Saving the screenshot failed: {}", e),
+    }
+}
+
+/// Reads the depth of the rendered frame at the cursor (in window coordinates) and transforms the point back to world space.
+/// Returns `None` if there is no surface under the cursor (the depth is still at the far plane).
+fn unproject_cursor(state: &mut AppState, cursor: (i32, i32), proj: &Matrix4<f32>, view: &Matrix4<f32>) -> Option<Vector3<f32>> {
+    if state.map_view.is_some() {
+        debug!(target: LOG_TARGET, "The scene is not shown while a texture map is viewed");
+        return None;
+    }
+    let (viewport_x, viewport_y, width, height) = state.viewport();
+    // Window coordinates start at the top, OpenGL coordinates at the bottom
+    let x = cursor.0 - viewport_x as i32;
+    let y = (state.window_size.1 as i32 - 1 - cursor.1) - viewport_y as i32;
+    if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+        debug!(target: LOG_TARGET, "Cursor {:?} is outside of the viewport", cursor);
+        return None;
+    }
+
+    // With HDR enabled or a different render scale, the depth of the scene is only stored in the offscreen buffer,
+    // whose pixels may be larger or smaller than the pixels of the window
+    let offscreen_buffer = if state.hdr_enabled { state.hdr_buffer.as_ref() } else { state.scaled_buffer.as_ref().filter(|_| state.render_scale != 1.0) };
+    let (x, y, width, height) = match offscreen_buffer {
+        Some(buffer) => (
+            (x as u64 * buffer.width() as u64 / width as u64) as i32,
+            (y as u64 * buffer.height() as u64 / height as u64) as i32,
+            buffer.width(),
+            buffer.height(),
+        ),
+        None => (x, y, width, height),
+    };
+    let mut depth: f32 = 1.0;
+    unsafe {
+        match offscreen_buffer {
+            Some(buffer) => {
+                buffer.bind();
+                gl::ReadPixels(x, y, 1, 1, gl::DEPTH_COMPONENT, gl::FLOAT, (&mut depth as *mut f32).cast());
+                Framebuffer::unbind();
+                state.update_viewport();
+            }
+            None => gl::ReadPixels(x + viewport_x as i32, y + viewport_y as i32, 1, 1, gl::DEPTH_COMPONENT, gl::FLOAT, (&mut depth as *mut f32).cast()),
+        }
+    }
+    if depth >= 1.0 {
+        return None;
+    }
+
+    // Center of the pixel relative to the viewport (or the offscreen buffer)
+    let screen = cgmath::vec3(x as f32 + 0.5, y as f32 + 0.5, depth);
+    unproject(screen, view, proj, (0, 0, width, height))
+        .map_err(|e| warn!(target: LOG_TARGET, "Could not unproject the cursor {:?}: {}", cursor, e))
+        .ok()
+}
+
+/// Moves the fixed light just above the surface under the cursor. Clicks that miss the mesh are ignored.
+fn place_light(state: &mut AppState, cursor: (i32, i32), proj: &Matrix4<f32>, view: &Matrix4<f32>) {
+    match unproject_cursor(state, cursor, proj, view) {
+        Some(world_pos) => {
+            // The mesh lies in the xy-plane, so the light is placed along its normal
+            state.fixed_light_pos = world_pos + cgmath::vec3(0.0, 0.0, LIGHT_PLACEMENT_HEIGHT);
+            info!(target: LOG_TARGET, "Placed the light at {:?} above {:?}", state.fixed_light_pos, world_pos);
+            if state.headlamp || state.sun_cycle.is_some() {
+                info!(target: LOG_TARGET, "The placed light is only used with the headlamp and the sun cycle disabled");
+            }
+        }
+        None => debug!(target: LOG_TARGET, "No surface under the cursor {:?}, the light is not moved", cursor),
+    }
+}
+
+/// Logs the position, texture coordinates and the height of the terrain of the surface under the cursor
+fn probe_surface(state: &mut AppState, cursor: (i32, i32), proj: &Matrix4<f32>, view: &Matrix4<f32>) {
+    let world_pos = match unproject_cursor(state, cursor, proj, view) {
+        Some(world_pos) => world_pos,
+        None => {
+            info!(target: LOG_TARGET, "Probe at {:?}: no surface under the cursor", cursor);
+            return;
+        }
+    };
+
+    // Texture coordinates on the instance containing the point, derived from the undisplaced square.
+    // The instances are placed relative to the scene offset, which the model matrix moves to the origin.
+    let scene_pos = world_pos + state.scene_offset;
+    let uv = state.instance_transforms.iter()
+        .filter_map(|transform| calc_inverse_matrix(transform).ok())
+        .map(|inverse| (inverse * scene_pos.extend(1.0)).truncate())
+        .find(|local| (MIN_X..=MAX_X).contains(&local.x) && (MIN_Y..=MAX_Y).contains(&local.y))
+        .map(|local| cgmath::vec2((local.x - MIN_X) / (MAX_X - MIN_X), (local.y - MIN_Y) / (MAX_Y - MIN_Y)));
+
+    match (uv, &state.terrain) {
+        (Some(uv), Some(terrain)) => info!(target: LOG_TARGET, "Probe at {:?}: position {:?}, uv {:?}, terrain height {:.4}",
+            cursor, world_pos, uv, terrain.sample(uv.x, uv.y) * TERRAIN_HEIGHT_SCALE),
+        (Some(uv), None) => info!(target: LOG_TARGET, "Probe at {:?}: position {:?}, uv {:?}", cursor, world_pos, uv),
+        (None, _) => info!(target: LOG_TARGET, "Probe at {:?}: position {:?} (outside of the mesh)", cursor, world_pos),
+    }
+}
+
+/// Calculates the view and projection matrix of the directional light shining at the origin.
+/// A light at the origin shines straight down onto the mesh (along -Z), and the up vector switches to +Z
+/// when the light is (almost) parallel to the usual +Y, so the matrices never contain NaNs.
+fn calc_light_matrices(light_pos: &Vector3<f32>) -> (Matrix4<f32>, Matrix4<f32>) {
+    let dir = if light_pos.magnitude2() > f32::EPSILON { light_pos.normalize() } else { cgmath::vec3(0.0, 0.0, 1.0) };
+    let up = if dir.y.abs() > 0.99 { cgmath::vec3(0.0, 0.0, 1.0) } else { cgmath::vec3(0.0, 1.0, 0.0) };
+    let light_view = calc_look_at_matrix(dir * SHADOW_LIGHT_DISTANCE, cgmath::vec3(0.0, 0.0, 0.0), up);
+    let light_proj = calc_orthographic_matrix(-SHADOW_EXTENT, SHADOW_EXTENT, -SHADOW_EXTENT, SHADOW_EXTENT, 0.1, 2.0 * SHADOW_LIGHT_DISTANCE).unwrap();
+    (light_view, light_proj)
+}
+
+/// Renders the depth of the mesh as seen from the light into the shadow map, if shadows are enabled.
+/// The current program is used, so the shadows contain the displacement of the program.
+fn render_shadow_map(state: &mut AppState, model_trans: &Matrix4<f32>, light_pos: &Vector3<f32>) {
+    match &state.shadow_map {
+        Some(shadow_map) if state.map_view.is_none() => shadow_map.bind(),
+        _ => return,
+    }
+
+    let (light_view, light_proj) = calc_light_matrices(light_pos);
+    configure_scene_program(state, &light_proj, &light_view, model_trans, light_pos, None);
+    unsafe {
+        gl::Clear(gl::DEPTH_BUFFER_BIT);
+        // The mesh is only visible from one side, which may face away from the light
+        gl::Disable(gl::CULL_FACE);
+    }
+    draw_mesh(state);
+    state.apply_backface_culling();
+
+    Framebuffer::unbind();
+    state.update_viewport();
+}
+
+/// Renders the mesh (and the enabled helpers) with the current program
+fn render_scene(state: &mut AppState, proj: &Matrix4<f32>, view: &Matrix4<f32>, model_trans: &Matrix4<f32>, light_pos: &Vector3<f32>) {
+    let light_space = state.shadow_map.as_ref().map(|shadow_map| {
+        shadow_map.bind_depth_texture(gl::TEXTURE0 + SHADOW_MAP_UNIT);
+        let (light_view, light_proj) = calc_light_matrices(light_pos);
+        light_proj * light_view
+    });
+    configure_scene_program(state, proj, view, model_trans, light_pos, light_space.as_ref());
+
+    if let Some(timer) = state.gpu_timer.as_mut() {
+        timer.begin();
+    }
+    draw_mesh(state);
+    if let Some(timer) = state.gpu_timer.as_mut() {
+        timer.end();
+    }
+    if state.show_wireframe {
+        state.draw_wireframe();
+    }
+
+    if let Some(skybox) = state.skybox.as_ref().filter(|_| state.show_skybox) {
+        state.draw_skybox(skybox, proj, view);
+    }
+    if state.show_grid {
+        state.draw_grid(proj, view);
+    }
+    if state.show_axes {
+        state.draw_axes(proj, view);
+    }
+}
+
+/// Activates the current program and sets its uniforms.
+/// Without the matrix of the light space, the program does not sample the shadow map.
+fn configure_scene_program(state: &AppState, proj: &Matrix4<f32>, view: &Matrix4<f32>, model_trans: &Matrix4<f32>, light_pos: &Vector3<f32>, light_space: Option<&Matrix4<f32>>) {
+    let pos = state.camera.position();
+
+    let current_program = state.current_program().unwrap();
+    current_program.set_active();
+    TextureCollection::configure_program(current_program);
+    state.texture.configure_material(current_program);
+    state.texture.set_active();
+    if let Some(secondary_texture) = state.secondary_texture.as_ref().filter(|_| state.multi_material) {
+        secondary_texture.set_active_secondary();
+    }
+    if current_program.has_property("multiMaterial") {
+        current_program.set_property_bool("multiMaterial", state.multi_material);
+    }
+    if current_program.has_property("overrideColorEnabled") {
+        current_program.set_property_bool("overrideColorEnabled", false);
+    }
+
+    // The buffer is updated for every pass, since the shadow pass renders from the light instead of the camera.
+    // Programs without the uniform block (e.g. custom shaders) still get the plain uniforms.
+    if current_program.bind_uniform_block(PER_FRAME_BLOCK, state.per_frame_buffer.binding()) {
+        state.per_frame_buffer.update(&PerFrame::new(proj, view, &pos, light_pos));
+    } else {
+        current_program.set_property_mat4("projection", proj);
+        current_program.set_property_mat4("view", view);
+        current_program.set_property_vec3("viewPos", &pos);
+        current_program.set_property_vec3("lightPos", light_pos);
+    }
+    current_program.set_property_mat4("model", model_trans);
+
+    current_program.set_property_bool("detailEnabled", state.detail_enabled);
+    current_program.set_property_float("detailScale", DETAIL_SCALE);
+
+    if current_program.has_property("shininess") {
+        current_program.set_property_float("shininess", state.shininess);
+    }
+    if current_program.has_property("ambientStrength") {
+        current_program.set_property_float("ambientStrength", state.ambient_strength);
+    }
+    if current_program.has_property("normalStrength") {
+        current_program.set_property_float("normalStrength", state.normal_strength);
+    }
+    if current_program.has_property("emissiveStrength") {
+        current_program.set_property_float("emissiveStrength", state.emissive_strength());
+    }
+    if current_program.has_property("worldSpaceNormals") {
+        current_program.set_property_bool("worldSpaceNormals", state.world_space_normals);
+    }
+    if current_program.has_property("smoothHeight") {
+        current_program.set_property_bool("smoothHeight", state.smooth_height);
+    }
+    if current_program.has_property("lightColor") {
+        current_program.set_property_vec3("lightColor", &state.light_color());
+    }
+    if current_program.has_property("parallaxLayers") {
+        current_program.set_property_int("parallaxLayers", state.parallax_layers);
+    }
+    if current_program.has_property("minParallaxLayers") {
+        current_program.set_property_int("minParallaxLayers", state.min_parallax_layers);
+    }
+    if current_program.has_property("parallaxRefinement") {
+        current_program.set_property_bool("parallaxRefinement", state.parallax_refinement);
+    }
+    if current_program.has_property("tangentSpaceVector") {
+        current_program.set_property_int("tangentSpaceVector", state.tangent_space_vector_idx as i32);
+    }
+    if current_program.has_property("tessLevel") {
+        current_program.set_property_float("tessLevel", state.tess_level);
+    }
+    if current_program.has_property("flatShading") {
+        current_program.set_property_bool("flatShading", state.flat_shading);
+    }
+    if current_program.has_property("doubleSided") {
+        current_program.set_property_bool("doubleSided", state.double_sided);
+    }
+    if current_program.has_property("fogMode") {
+        current_program.set_property_int("fogMode", state.fog_mode_idx as i32);
+        current_program.set_property_vec3("fogColor", &state.fog_color());
+        current_program.set_property_float("fogDensity", state.fog_density);
+        current_program.set_property_float("fogStart", state.fog_start);
+        current_program.set_property_float("fogEnd", state.fog_end);
+    }
+    if current_program.has_property("shadowsEnabled") {
+        current_program.set_property_bool("shadowsEnabled", light_space.is_some());
+    }
+    if let Some(light_space) = light_space {
+        if current_program.has_property("shadowMap") {
+            current_program.set_property_mat4("lightSpace", light_space);
+            current_program.set_property_float("shadowBias", state.shadow_bias);
+        }
+    }
+}
+
+/// Draws all instances of the mesh with the active program
+fn draw_mesh(state: &AppState) {
+    let current_program = state.current_program().unwrap();
+    // Tessellation stages process patches of the mesh triangles instead of the triangles themselves
+    let primitive = if current_program.is_tessellated() {
+        unsafe {
+            gl::PatchParameteri(gl::PATCH_VERTICES, 3);
+        }
+        gl::PATCHES
+    } else {
+        gl::TRIANGLES
+    };
+
+    unsafe {
+        gl::BindVertexArray(state.vao_id);
+        gl::DrawArraysInstanced(
+            primitive,
+            0,
+            state.point_count as gl::types::GLsizei,
+            state.instance_transforms.len() as gl::types::GLsizei,
+        );
+    }
+}
+
+/// Limits the given index to the available sample amounts
+fn clamp_sample_index(idx: isize) -> usize {
+    idx.clamp(0, SAMPLE_STEPS_X.len().min(SAMPLE_STEPS_Y.len()) as isize - 1) as usize
+}
+
+/// Readable name of the given sample amount, e.g. `Medium 64x64`
+fn sample_preset_name(samples_idx: usize) -> String {
+    format!("{} {}x{}", SAMPLE_PRESET_NAMES[samples_idx], SAMPLE_STEPS_X[samples_idx], SAMPLE_STEPS_Y[samples_idx])
+}
+
+/// Amount of vertices generated for the given sample amount (two triangles per sample)
+fn sample_vertex_count(samples_idx: usize) -> u64 {
+    SAMPLE_STEPS_X[samples_idx] as u64 * SAMPLE_STEPS_Y[samples_idx] as u64 * 6
+}
+
+/// Estimated memory of the vertex data with the given layout in MiB, which is needed on the CPU and on the GPU
+fn vertex_memory_mib(vertex_count: u64, layout: &VertexLayout) -> u64 {
+    vertex_count * (layout.floats_per_vertex() * std::mem::size_of::<f32>()) as u64 / (1024 * 1024)
 }
 
-/// Generates vertices for a square with the given sample-size and stores the VBO-data to the buffer.
-fn generate_vertices(samples_idx: usize, buffer: &mut Vec<f32>) -> u32 {
-    // Clear existing data
-    buffer.clear();
+/// Generates vertices for a square with the given sample-size and returns the VBO-data with the given layout and the vertex count.
+/// If a height map is given, the square is displaced along its normal by the sampled heights to form a terrain.
+/// A positive decimation tolerance triangulates the terrain adaptively (see [`decimation::decimate_grid`]).
+/// The UV mapping only changes the texture coordinates of the vertices; the height map is sampled at the undisplaced points.
+/// `cancelled` is checked for every row of the grid; once it returns `true`, the generation stops and `None` is returned.
+fn generate_vertices(samples_idx: usize, terrain: Option<&HeightMap>, displacement_axis: Vector3<f32>, decimation_tolerance: f32, uv_mapping: UvMapping, layout: &VertexLayout, cancelled: impl Fn() -> bool) -> Option<(Vec<f32>, u32)> {
+    // Reserve the exact amount of memory, so the buffer does not grow beyond the needed size
+    let mut buffer = Vec::new();
+    buffer.reserve_exact(SAMPLE_STEPS_X[samples_idx] as usize * SAMPLE_STEPS_Y[samples_idx] as usize * 6 * layout.floats_per_vertex());
+    let needs_tangents = layout.contains(VertexAttribute::Tangent) || layout.contains(VertexAttribute::Bitangent);
 
     let full_diff_x = MAX_X - MIN_X;
     let full_diff_y = MAX_Y - MIN_Y;
     let diff_x = full_diff_x / SAMPLE_STEPS_X[samples_idx];
     let diff_y = full_diff_y / SAMPLE_STEPS_Y[samples_idx];
 
-    let normal = cgmath::vec3(0.0, 0.0, 1.0);
+    // Texture coordinates and position/normal of the (displaced) surface at a point of the square
+    let tex_coords = |x: f32, y: f32| cgmath::vec2((x - MIN_X) / full_diff_x, (y - MIN_Y) / full_diff_y);
+    let vertex_tex_coords = |x: f32, y: f32| uv_mapping.apply(tex_coords(x, y));
+    let surface_height = |x: f32, y: f32| match terrain {
+        Some(height_map) => {
+            let uv = tex_coords(x, y);
+            height_map.sample(uv.x, uv.y) * TERRAIN_HEIGHT_SCALE
+        }
+        None => 0.0,
+    };
+    let position = |x: f32, y: f32| cgmath::vec3(x, y, 0.0) + displacement_axis * surface_height(x, y);
+    let normal = |x: f32, y: f32| {
+        let flat_normal = cgmath::vec3(0.0, 0.0, 1.0);
+        if terrain.is_none() {
+            return flat_normal;
+        }
+        // Partial derivatives of the displaced position from central differences of the height,
+        // so the normals are smooth across triangles
+        let dh_dx = (surface_height(x + diff_x, y) - surface_height(x - diff_x, y)) / (2.0 * diff_x);
+        let dh_dy = (surface_height(x, y + diff_y) - surface_height(x, y - diff_y)) / (2.0 * diff_y);
+        let dp_dx = cgmath::vec3(1.0, 0.0, 0.0) + displacement_axis * dh_dx;
+        let dp_dy = cgmath::vec3(0.0, 1.0, 0.0) + displacement_axis * dh_dy;
+        let normal = dp_dx.cross(dp_dy);
+        // Displacing within the plane of the square can fold the surface onto itself
+        if normal.magnitude2() > f32::EPSILON { normal.normalize() } else { flat_normal }
+    };
+
     let mut point_count: u32 = 0;
 
+    if terrain.is_some() && decimation_tolerance > 0.0 {
+        let grid_point = |(grid_x, grid_y): decimation::GridPoint| (MIN_X + grid_x * diff_x, MIN_Y + grid_y * diff_y);
+        let triangles = decimation::decimate_grid(
+            SAMPLE_STEPS_X[samples_idx] as u32,
+            SAMPLE_STEPS_Y[samples_idx] as u32,
+            |grid_x, grid_y| {
+                let (x, y) = grid_point((grid_x as f32, grid_y as f32));
+                surface_height(x, y)
+            },
+            decimation_tolerance,
+        );
+
+        for (idx, triangle) in triangles.into_iter().enumerate() {
+            // Check as often as in the regular grid, which generates two triangles per cell
+            if idx % (2 * SAMPLE_STEPS_Y[samples_idx] as usize) == 0 && cancelled() {
+                return None;
+            }
+            let points = triangle.map(grid_point);
+            let p = points.map(|(x, y)| position(x, y));
+            let uv = points.map(|(x, y)| vertex_tex_coords(x, y));
+            let (tangent, bitangent) = if needs_tangents { mesh::compute_tangent_bitangent(p, uv) } else { (Vector3::zero(), Vector3::zero()) };
+            for idx in 0..3 {
+                let (x, y) = points[idx];
+                layout.push_vertex(&mut buffer, p[idx], normal(x, y), uv[idx], tangent, bitangent);
+            }
+            point_count += 3;
+        }
+        return Some((buffer, point_count));
+    }
+
     // Generate the data
     for step_x in 0..((SAMPLE_STEPS_X[samples_idx]) as i32) {
+        if cancelled() {
+            return None;
+        }
         for step_y in 0..((SAMPLE_STEPS_Y[samples_idx]) as i32) {
-            // Step 1: Positions
-            let p1 = cgmath::vec3(MIN_X + (step_x as f32 * diff_x), MIN_Y + (step_y as f32 * diff_y), 0.0);
-            let p2 = cgmath::vec3(MIN_X + (step_x as f32 * diff_x), MIN_Y + (step_y as f32 * diff_y) + diff_y, 0.0);
-            let p3 = cgmath::vec3(MIN_X + (step_x as f32 * diff_x) + diff_x, MIN_Y + (step_y as f32 * diff_y) + diff_y, 0.0);
-            let p4 = cgmath::vec3(MIN_X + (step_x as f32 * diff_x) + diff_x, MIN_Y + (step_y as f32 * diff_y), 0.0);
-
-            // Step 2: Texture coordinates
-            let uv1 = cgmath::vec2((p1.x - MIN_X) / full_diff_x, (p1.y - MIN_Y) / full_diff_y);
-            let uv2 = cgmath::vec2((p2.x - MIN_X) / full_diff_x, (p2.y - MIN_Y) / full_diff_y);
-            let uv3 = cgmath::vec2((p3.x - MIN_X) / full_diff_x, (p3.y - MIN_Y) / full_diff_y);
-            let uv4 = cgmath::vec2((p4.x - MIN_X) / full_diff_x, (p4.y - MIN_Y) / full_diff_y);
-
-            // Step 3: tangent and bitangent
-            // Tri 1
-            let edge1_t1 = p2 - p1;
-            let edge2_t1 = p3 - p1;
-            let delta_uv1_t1 = uv2 - uv1;
-            let delta_uv2_t1 = uv3 - uv1;
-
-            let f_t1 = 1.0 / (delta_uv1_t1.x * delta_uv2_t1.y - delta_uv2_t1.x * delta_uv1_t1.y);
-
-            let tangent1 = cgmath::vec3(
-                f_t1 * (delta_uv2_t1.y * edge1_t1.x - delta_uv1_t1.y * edge2_t1.x),
-                f_t1 * (delta_uv2_t1.y * edge1_t1.y - delta_uv1_t1.y * edge2_t1.y),
-                f_t1 * (delta_uv2_t1.y * edge1_t1.z - delta_uv1_t1.y * edge2_t1.z),
-            );
-            let bitangent1 = cgmath::vec3(
-                f_t1 * (-1.0 * delta_uv2_t1.y * edge1_t1.x + delta_uv1_t1.y * edge2_t1.x),
-                f_t1 * (-1.0 * delta_uv2_t1.y * edge1_t1.y + delta_uv1_t1.y * edge2_t1.y),
-                f_t1 * (-1.0 * delta_uv2_t1.y * edge1_t1.z + delta_uv1_t1.y * edge2_t1.z),
-            );
-
-            // Tri2
-            let edge1_t2 = p3 - p1;
-            let edge2_t2 = p4 - p1;
-            let delta_uv1_t2 = uv3 - uv1;
-            let delta_uv2_t2 = uv4 - uv1;
-
-            let f_t2 = 1.0 / (delta_uv1_t2.x * delta_uv2_t2.y - delta_uv2_t2.x * delta_uv1_t2.y);
-
-            let tangent2 = cgmath::vec3(
-                f_t2 * (delta_uv2_t2.y * edge1_t2.x - delta_uv1_t2.y * edge2_t2.x),
-                f_t2 * (delta_uv2_t2.y * edge1_t2.y - delta_uv1_t2.y * edge2_t2.y),
-                f_t2 * (delta_uv2_t2.y * edge1_t2.z - delta_uv1_t2.y * edge2_t2.z),
-            );
-            let bitangent2 = cgmath::vec3(
-                f_t2 * (-1.0 * delta_uv2_t2.y * edge1_t2.x + delta_uv1_t2.y * edge2_t2.x),
-                f_t2 * (-1.0 * delta_uv2_t2.y * edge1_t2.y + delta_uv1_t2.y * edge2_t2.y),
-                f_t2 * (-1.0 * delta_uv2_t2.y * edge1_t2.z + delta_uv1_t2.y * edge2_t2.z),
-            );
-
-            // Step 4: Add the data
-            let mut tmp_buffer = vec![
-                // position       // normal                    // tex.-coords // tangent                          // bitangent
-                p1.x, p1.y, p1.z, normal.x, normal.y, normal.z, uv1.x, uv1.y, tangent1.x, tangent1.y, tangent1.z, bitangent1.x, bitangent1.y, bitangent1.z,
-                p2.x, p2.y, p2.z, normal.x, normal.y, normal.z, uv2.x, uv2.y, tangent1.x, tangent1.y, tangent1.z, bitangent1.x, bitangent1.y, bitangent1.z,
-                p3.x, p3.y, p3.z, normal.x, normal.y, normal.z, uv3.x, uv3.y, tangent1.x, tangent1.y, tangent1.z, bitangent1.x, bitangent1.y, bitangent1.z,
-                p1.x, p1.y, p1.z, normal.x, normal.y, normal.z, uv1.x, uv1.y, tangent2.x, tangent2.y, tangent2.z, bitangent2.x, bitangent2.y, bitangent2.z,
-                p3.x, p3.y, p3.z, normal.x, normal.y, normal.z, uv3.x, uv3.y, tangent2.x, tangent2.y, tangent2.z, bitangent2.x, bitangent2.y, bitangent2.z,
-                p4.x, p4.y, p4.z, normal.x, normal.y, normal.z, uv4.x, uv4.y, tangent2.x, tangent2.y, tangent2.z, bitangent2.x, bitangent2.y, bitangent2.z,
-            ];
-            buffer.append(&mut tmp_buffer);
+            let x = MIN_X + (step_x as f32 * diff_x);
+            let y = MIN_Y + (step_y as f32 * diff_y);
+
+            // Step 1: Positions and normals
+            let p1 = position(x, y);
+            let p2 = position(x, y + diff_y);
+            let p3 = position(x + diff_x, y + diff_y);
+            let p4 = position(x + diff_x, y);
+            let n1 = normal(x, y);
+            let n2 = normal(x, y + diff_y);
+            let n3 = normal(x + diff_x, y + diff_y);
+            let n4 = normal(x + diff_x, y);
+
+            // Step 2: Texture coordinates of the undisplaced points
+            let uv1 = vertex_tex_coords(x, y);
+            let uv2 = vertex_tex_coords(x, y + diff_y);
+            let uv3 = vertex_tex_coords(x + diff_x, y + diff_y);
+            let uv4 = vertex_tex_coords(x + diff_x, y);
+
+            // Step 3: tangent and bitangent (only computed if the layout contains them)
+            let no_tangents = (Vector3::zero(), Vector3::zero());
+            let (tangent1, bitangent1) = if needs_tangents { mesh::compute_tangent_bitangent([p1, p2, p3], [uv1, uv2, uv3]) } else { no_tangents };
+            let (tangent2, bitangent2) = if needs_tangents { mesh::compute_tangent_bitangent([p1, p3, p4], [uv1, uv3, uv4]) } else { no_tangents };
+
+            // Step 4: Add the data of the attributes contained in the layout
+            layout.push_vertex(&mut buffer, p1, n1, uv1, tangent1, bitangent1);
+            layout.push_vertex(&mut buffer, p2, n2, uv2, tangent1, bitangent1);
+            layout.push_vertex(&mut buffer, p3, n3, uv3, tangent1, bitangent1);
+            layout.push_vertex(&mut buffer, p1, n1, uv1, tangent2, bitangent2);
+            layout.push_vertex(&mut buffer, p3, n3, uv3, tangent2, bitangent2);
+            layout.push_vertex(&mut buffer, p4, n4, uv4, tangent2, bitangent2);
             point_count += 6;
         }
     }
 
-    point_count
+    Some((buffer, point_count))
 }
 
-fn handle_event(state: &mut AppState, event: Event) {
+/// Enables or disables the relative mouse mode of the window as requested by the state.
+/// The movement collected so far is discarded, as well as the first motion event after the change:
+/// when capturing, it may contain the movement accumulated since the last event;
+/// when releasing, the cursor is warped to the center of the window, which causes a motion event as well.
+fn apply_mouse_capture(state: &mut AppState, mouse: &sdl2::mouse::MouseUtil, window: &sdl2::video::Window) {
+    mouse.set_relative_mouse_mode(state.mouse_captured);
+    if !state.mouse_captured {
+        let (width, height) = window.size();
+        mouse.warp_mouse_in_window(window, width as i32 / 2, height as i32 / 2);
+    }
+    state.camera.reset_mouse_delta();
+    state.just_captured = true;
+}
+
+fn handle_event(state: &mut AppState, res: &Resources, event: Event) {
     // Input handling
     match event {
         Event::Quit { .. } => state.terminate(),
-        Event::KeyDown { keycode, .. } => {
-            match keycode.unwrap_or_else(|| Keycode::F24) { // Match unknown keys to the (unused) F24-Key
-                Keycode::Plus | Keycode::KpPlus => {
-                    state.increase_samples()
-                }
-                Keycode::Minus | Keycode::KpMinus => {
-                    state.decrease_samples()
-                }
-                Keycode::W | Keycode::Up => {
-                    state.camera.move_camera(FORWARD, 0.1)
-                }
-                Keycode::A | Keycode::Left => {
-                    state.camera.move_camera(LEFT, 0.1)
-                }
-                Keycode::S | Keycode::Down => {
-                    state.camera.move_camera(BACKWARD, 0.1)
-                }
-                Keycode::D | Keycode::Right => {
-                    state.camera.move_camera(RIGHT, 0.1)
-                }
-                Keycode::Space | Keycode::PageUp => {
-                    state.camera.move_camera(UP, 0.1)
-                }
-                Keycode::LCtrl | Keycode::PageDown => {
-                    state.camera.move_camera(DOWN, 0.1)
-                }
-                Keycode::Kp0 | Keycode::Home => {
-                    state.camera.reset_position()
-                }
-                Keycode::M => {
-                    state.cycle_programs();
-                }
-                Keycode::Escape => state.terminate(),
-                _ => {}
+        Event::KeyDown { keycode: Some(keycode), keymod, .. } => {
+            let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+            if let Some(action) = state.keymap.action(keycode, shift) {
+                handle_action(state, res, action);
+            }
+        }
+        // Sprinting is the only action that lasts while its key is held
+        Event::KeyUp { keycode: Some(keycode), keymod, .. } => {
+            let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+            if state.keymap.action(keycode, shift) == Some(Action::Sprint) {
+                state.sprinting = false;
             }
         }
-        Event::MouseMotion { xrel, yrel, .. } => state.camera.rotate_camera(xrel as f32, yrel as f32),
-        Event::MouseWheel { y, .. } => state.camera.zoom_camera(y as f32),
+        Event::Window { win_event: WindowEvent::SizeChanged(width, height), .. } => state.resize(width as u32, height as u32),
+        // The release of the sprint key is not received while the window is unfocused
+        Event::Window { win_event: WindowEvent::FocusLost, .. } => state.sprinting = false,
+        Event::MouseMotion { .. } if state.just_captured => state.just_captured = false,
+        Event::MouseMotion { xrel, yrel, .. } => {
+            state.stop_demo_reel();
+            state.camera.rotate_camera(xrel as f32, yrel as f32)
+        }
+        Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => state.pick_request = Some((x, y)),
+        Event::MouseWheel { y, .. } => {
+            state.stop_demo_reel();
+            state.camera.zoom_camera(y as f32)
+        }
         _ => {} // do nothing for unhandled events
     }
 }
 
-fn log_instructions() {
-    // Instructions for using
+fn handle_action(state: &mut AppState, res: &Resources, action: Action) {
+    // Any input hands the control back to the user
+    if action != Action::ToggleDemoReel {
+        state.stop_demo_reel();
+    }
+
+    match action {
+        Action::Quit => state.terminate(),
+        Action::ShowHelp => state.toggle_help(),
+        Action::IncreaseSamples => state.increase_detail(),
+        Action::DecreaseSamples => state.decrease_detail(),
+        Action::SamplePresetLow => state.set_sample_preset("Low"),
+        Action::SamplePresetMedium => state.set_sample_preset("Medium"),
+        Action::SamplePresetHigh => state.set_sample_preset("High"),
+        Action::SamplePresetUltra => state.set_sample_preset("Ultra"),
+        Action::ToggleShaderDetail => state.toggle_shader_detail(),
+        Action::IncreaseMinParallaxLayers => state.change_min_parallax_layers(2.0),
+        Action::DecreaseMinParallaxLayers => state.change_min_parallax_layers(0.5),
+        Action::ToggleParallaxRefinement => state.toggle_parallax_refinement(),
+        Action::IncreaseTessLevel => state.change_tess_level(2.0),
+        Action::DecreaseTessLevel => state.change_tess_level(0.5),
+        Action::MoveForward => state.move_camera(FORWARD),
+        Action::MoveLeft => state.move_camera(LEFT),
+        Action::MoveBackward => state.move_camera(BACKWARD),
+        Action::MoveRight => state.move_camera(RIGHT),
+        Action::MoveUp => state.move_camera(UP),
+        Action::MoveDown => state.move_camera(DOWN),
+        Action::Sprint => state.sprinting = true,
+        Action::ResetCamera => state.camera.reset_position(),
+        Action::FrameMesh => state.frame_mesh(),
+        Action::TopDownView => state.top_down_view(),
+        Action::PivotLeft => state.move_pivot(cgmath::vec3(-PIVOT_STEP, 0.0, 0.0)),
+        Action::PivotRight => state.move_pivot(cgmath::vec3(PIVOT_STEP, 0.0, 0.0)),
+        Action::PivotDown => state.move_pivot(cgmath::vec3(0.0, -PIVOT_STEP, 0.0)),
+        Action::PivotUp => state.move_pivot(cgmath::vec3(0.0, PIVOT_STEP, 0.0)),
+        Action::PivotBackward => state.move_pivot(cgmath::vec3(0.0, 0.0, -PIVOT_STEP)),
+        Action::PivotForward => state.move_pivot(cgmath::vec3(0.0, 0.0, PIVOT_STEP)),
+        Action::CenterPivot => state.center_pivot(),
+        Action::ResetPivot => state.move_pivot(-state.scene_offset),
+        Action::ToggleInvertY => state.toggle_invert_y(),
+        Action::ToggleGroundMode => state.toggle_ground_mode(),
+        Action::ToggleMouseCapture => state.toggle_mouse_capture(),
+        Action::ToggleGroundPlane => state.toggle_ground_plane(),
+        Action::CyclePrograms => state.cycle_programs(),
+        Action::ReloadTextures => state.reload_textures(res),
+        Action::ReloadAll => state.reload_all(res),
+        Action::ToggleMipmaps => state.toggle_mipmaps(res),
+        Action::ToggleMagFilter => state.toggle_mag_filter(),
+        Action::ToggleDetail => state.toggle_detail(),
+        Action::ExportTextureReadback => state.export_texture_readback(res),
+        Action::ExportObj => state.export_obj(res),
+        Action::ExportConfig => state.export_config(res),
+        Action::IncreaseShininess => state.change_shininess(2.0),
+        Action::DecreaseShininess => state.change_shininess(0.5),
+        Action::IncreaseAmbient => state.change_ambient_strength(AMBIENT_STEP),
+        Action::DecreaseAmbient => state.change_ambient_strength(-AMBIENT_STEP),
+        Action::IncreaseNormalStrength => state.change_normal_strength(NORMAL_STRENGTH_STEP),
+        Action::DecreaseNormalStrength => state.change_normal_strength(-NORMAL_STRENGTH_STEP),
+        Action::ToggleHdr => state.toggle_hdr(),
+        Action::ToggleSkybox => state.toggle_skybox(res),
+        Action::ToggleGrid => state.toggle_grid(),
+        Action::ToggleWireframe => state.toggle_wireframe(),
+        Action::IncreaseExposure => state.change_exposure(EXPOSURE_STEP),
+        Action::DecreaseExposure => state.change_exposure(1.0 / EXPOSURE_STEP),
+        Action::CycleTonemapOperator => state.cycle_tonemap_operator(),
+        Action::CycleRenderScale => state.cycle_render_scale(),
+        Action::CycleEmissiveStrength => state.cycle_emissive_strength(),
+        Action::ToggleWorldSpaceNormals => state.toggle_world_space_normals(),
+        Action::ToggleSmoothHeight => state.toggle_smooth_height(),
+        Action::CycleTangentSpaceVector => state.cycle_tangent_space_vector(),
+        Action::ToggleTerrain => state.toggle_terrain(res),
+        Action::CycleDisplacementAxis => state.cycle_displacement_axis(),
+        Action::ToggleFlatNormals => state.toggle_flat_normals(),
+        Action::CycleDecimation => state.cycle_decimation(),
+        Action::ToggleUvClamp => state.toggle_uv_clamp(),
+        Action::ToggleMultiMaterial => state.toggle_multi_material(res),
+        Action::ToggleFlatShading => state.toggle_flat_shading(),
+        Action::ToggleVertexColors => state.toggle_vertex_colors(),
+        Action::ToggleDepthTest => state.toggle_depth_test(),
+        Action::ToggleBackfaceCulling => state.toggle_backface_culling(),
+        Action::ToggleDoubleSided => state.toggle_double_sided(),
+        Action::ToggleLetterbox => state.toggle_letterbox(),
+        Action::CycleLetterboxAspect => state.cycle_letterbox_aspect(),
+        Action::CycleClearColor => state.cycle_clear_color(),
+        Action::CycleLightColor => state.cycle_light_color(),
+        Action::ToggleShadows => state.toggle_shadows(),
+        Action::ToggleHeadlamp => state.toggle_headlamp(),
+        Action::TogglePlaceLight => state.toggle_place_light(),
+        Action::ToggleSunCycle => state.toggle_sun_cycle(),
+        Action::SunCycleForward => state.scrub_sun_cycle(SUN_SCRUB_STEP),
+        Action::SunCycleBackward => state.scrub_sun_cycle(-SUN_SCRUB_STEP),
+        Action::CycleFogMode => state.cycle_fog_mode(),
+        Action::IncreaseFogDensity => state.change_fog_density(1.5),
+        Action::DecreaseFogDensity => state.change_fog_density(1.0 / 1.5),
+        Action::ToggleAxes => state.toggle_axes(),
+        Action::CycleMapView => state.cycle_map_view(),
+        Action::CycleMapInset => state.cycle_map_inset(),
+        Action::ToggleGpuTimer => state.toggle_gpu_timer(),
+        Action::Screenshot => state.request_screenshot(1),
+        Action::SupersampledScreenshot => state.request_screenshot(SUPERSAMPLE_FACTOR),
+        Action::RecordKeyframe => state.record_keyframe(),
+        Action::ToggleCameraPath => state.toggle_camera_path_playback(),
+        Action::ClearCameraPath => state.clear_camera_path(),
+        Action::SaveCameraPath => state.save_camera_path(res),
+        Action::LoadCameraPath => state.load_camera_path(res),
+        Action::ToggleDemoReel => state.toggle_demo_reel(),
+        Action::AddInstance => state.add_instance(),
+        Action::RemoveInstance => state.remove_instance(),
+    }
+}
+
+/// Converts an sRGB-encoded color channel to a linear value
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Finds the preset with the given name, falling back to the first preset for unknown names
+fn preset_index<'a>(names: impl IntoIterator<Item = &'a str>, name: &str, kind: &str) -> usize {
+    names.into_iter().position(|preset| preset == name).unwrap_or_else(|| {
+        warn!(target: LOG_TARGET, "Unknown {} \"{}\", using the default", kind, name);
+        0
+    })
+}
+
+/// Derives a readable name from the file name of a program (e.g. `normal_mapping` => `Normal mapping`)
+fn program_display_name(program_name: &str) -> String {
+    let name = program_name.replace('_', " ");
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// Returns the categories of the actions with the keys and the description of each action.
+/// The categories are ordered by their first action.
+fn controls_by_category(keymap: &KeyMap) -> Vec<(&'static str, Vec<(String, &'static str)>)> {
+    let mut categories: Vec<&str> = Vec::new();
+    for action in Action::ALL.iter() {
+        if !categories.contains(&action.category()) {
+            categories.push(action.category());
+        }
+    }
+
+    categories.into_iter()
+        .map(|category| {
+            let actions = Action::ALL.iter()
+                .filter(|action| action.category() == category)
+                .map(|action| {
+                    let keys = keymap.keys(*action);
+                    let keys = if keys.is_empty() { "(unbound)".to_string() } else { keys.join("/") };
+                    (keys, action.description())
+                })
+                .collect();
+            (category, actions)
+        })
+        .collect()
+}
+
+/// Lines of the help overlay: the controls of every category below a heading
+fn help_lines(keymap: &KeyMap) -> Vec<TextLine> {
+    let mut lines = Vec::new();
+    for (category, actions) in controls_by_category(keymap) {
+        if !lines.is_empty() {
+            lines.push(TextLine::new("", HELP_TEXT_COLOR));
+        }
+        lines.push(TextLine::new(format!("{}:", category), HELP_HEADING_COLOR));
+        lines.extend(actions.into_iter().map(|(keys, description)| TextLine::new(format!(" {:<14}{}", keys, description), HELP_TEXT_COLOR)));
+    }
+    lines.push(TextLine::new("", HELP_TEXT_COLOR));
+    lines.push(TextLine::new("Mouse: Look around, Scroll: Zoom", HELP_HEADING_COLOR));
+    lines
+}
+
+fn log_instructions(keymap: &KeyMap) {
+    // Instructions for using, generated from the current key bindings and grouped in the order of the first action of each category
+    let controls: String = controls_by_category(keymap).into_iter()
+        .map(|(category, actions)| {
+            let actions: String = actions.into_iter()
+                .map(|(keys, description)| format!("       - {:<14}=> {}\n", keys, description))
+                .collect();
+            format!("     {}:\n{}", category, actions)
+        })
+        .collect();
+
     warn!(target: "INSTRUCTIONS", r#"
     Controls:
-     - ESC           => Quit
-     - '+'           => Increase model vertices
-     - '-'           => Decrease model vertices
-     - W/UP          => Move forward
-     - A/LEFT        => Move left
-     - S/DOWN        => Move backward
-     - S/RIGHT       => Move right
-     - Space/PgUp    => Move up
-     - Ctrl/PgDown   => Move down
-     - Pos1/KeyPad0  => Reset camera
-     - M             => Cycle shaders
-
+{}
     Use the mouse to look around.
     Scroll to zoom.
-    "#);
+    "#, controls);
+}
+
+/// # UvMapping
+/// Mapping of the points of the square to the texture coordinates of the generated vertices
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UvMapping {
+    /// The texture coordinates span exactly `0..1`, so tileable textures continue seamlessly across the edges
+    Wrap,
+    /// The texture coordinates are inset by the given amount on each side (in texture coordinates),
+    /// so filtering at the border of the square does not blend in texels of the opposite edge
+    Clamp(Vector2<f32>),
+}
+
+impl UvMapping {
+    /// Maps texture coordinates in `0..1` to the ones of the vertices
+    fn apply(&self, uv: Vector2<f32>) -> Vector2<f32> {
+        match self {
+            UvMapping::Wrap => uv,
+            UvMapping::Clamp(inset) => cgmath::vec2(
+                inset.x + uv.x * (1.0 - 2.0 * inset.x),
+                inset.y + uv.y * (1.0 - 2.0 * inset.y),
+            ),
+        }
+    }
+}
+
+/// # DisplacementAxis
+/// Direction along which the square is displaced by the height map of the terrain
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DisplacementAxis {
+    /// Normal of the undisplaced square (`+z`)
+    Normal,
+    /// Up direction of the world (`+y`), regardless of the orientation of the surface
+    WorldUp,
+    /// Any direction; the length scales the displacement
+    Custom(Vector3<f32>),
+}
+
+impl DisplacementAxis {
+    /// Names of the axes in the configuration, in the order they are cycled through
+    const NAMES: [&'static str; 3] = ["normal", "world_up", "custom"];
+
+    /// Finds the axis with the given name in the configuration; the custom axis has the given direction
+    fn from_name(name: &str, custom_direction: Vector3<f32>) -> DisplacementAxis {
+        match preset_index(DisplacementAxis::NAMES, name, "displacement axis") {
+            1 => DisplacementAxis::WorldUp,
+            2 => DisplacementAxis::Custom(custom_direction),
+            _ => DisplacementAxis::Normal,
+        }
+    }
+
+    /// Get the name of the axis in the configuration
+    fn name(&self) -> &'static str {
+        match self {
+            DisplacementAxis::Normal => DisplacementAxis::NAMES[0],
+            DisplacementAxis::WorldUp => DisplacementAxis::NAMES[1],
+            DisplacementAxis::Custom(_) => DisplacementAxis::NAMES[2],
+        }
+    }
+
+    /// Get the direction of the displacement in world space
+    fn direction(&self) -> Vector3<f32> {
+        match self {
+            DisplacementAxis::Normal => cgmath::vec3(0.0, 0.0, 1.0),
+            DisplacementAxis::WorldUp => cgmath::vec3(0.0, 1.0, 0.0),
+            DisplacementAxis::Custom(direction) => *direction,
+        }
+    }
 }
 
 /// # AppState
 /// Contains values related to the mutable state of the application
 struct AppState {
+    /// Assignment of keys to actions
+    keymap: KeyMap,
+
     /// Camera to view the scene
     camera: Camera,
+    /// Recorded keyframes for an animated camera flight
+    camera_path: CameraPath,
+    /// Flag that Left Shift is held, which speeds up the camera movement
+    sprinting: bool,
+    /// Factor applied to the camera movement while sprinting
+    sprint_factor: f32,
 
     /// Flag to terminate the program
     should_terminate: bool,
@@ -302,63 +1381,472 @@ struct AppState {
     available_programs: Vec<Program>,
     /// List of readable identifiers for the available programs/shaders
     available_program_names: Vec<String>,
+    /// List of the resource names of the available programs/shaders
+    available_program_resources: Vec<String>,
+
+    /// Program to draw unlit lines
+    line_program: Program,
+    /// Coordinate axes at the origin
+    axes: Lines,
+    /// Flag to draw the coordinate axes
+    show_axes: bool,
+
+    /// Program to draw the skybox
+    skybox_program: Program,
+    /// Skybox behind the scene, loaded when it is shown for the first time
+    skybox: Option<Skybox>,
+    /// Flag to draw the skybox
+    show_skybox: bool,
+
+    /// Program to draw the floor grid
+    grid_program: Program,
+    /// Floor below the mesh for spatial reference
+    floor_grid: FloorGrid,
+    /// Flag to draw the floor grid
+    show_grid: bool,
+    /// Distance between two lines of the floor grid
+    grid_cell_size: f32,
+    /// Distance to the camera at which the floor grid has faded out
+    grid_fade_distance: f32,
+
+    /// Program to draw the text of the overlays
+    text_program: Program,
+    /// Controls listed on top of the scene
+    help_overlay: TextOverlay,
+    /// Flag to draw the help overlay
+    show_help: bool,
+    /// Busy indicator drawn while the mesh is generated in the background
+    status_overlay: TextOverlay,
+
+    /// Flag to draw the edges of the triangles on top of the shaded mesh
+    show_wireframe: bool,
+    /// Color of the wireframe (linear)
+    wireframe_color: Vector3<f32>,
+
+    /// Uniform buffer with the matrices and positions shared by the mesh programs
+    per_frame_buffer: UniformBuffer<PerFrame>,
+
+    /// Program to show a texture map on the whole screen
+    map_program: Program,
+    /// Quad covering the screen
+    screen_quad: ScreenQuad,
+    /// Program to tone map the HDR buffer
+    tonemap_program: Program,
+    /// Flag to render into a floating point buffer and tone map it afterwards
+    hdr_enabled: bool,
+    /// Floating point buffer of the last HDR frame, recreated if the size changes
+    hdr_buffer: Option<Framebuffer>,
+    /// Factor applied to the HDR colors before tone mapping
+    exposure: f32,
+    /// Index of the used tone mapping operator, see [`TONEMAP_OPERATORS`]
+    tonemap_idx: usize,
+    /// Factor the resolution of the scene is scaled by compared to the viewport, see [`RENDER_SCALES`]
+    render_scale: f32,
+    /// Buffer of the last frame rendered at a different scale without HDR, recreated if the size changes
+    scaled_buffer: Option<Framebuffer>,
+    /// Index of the texture map that is shown instead of the scene, if any
+    map_view: Option<usize>,
+    /// Index of the texture map that is shown in a corner of the scene, if any
+    map_inset: Option<usize>,
+    /// Cursor position (in window coordinates) to probe the surface at after the next frame, if requested
+    pick_request: Option<(i32, i32)>,
+    /// Flag to place the light at the clicked surface instead of probing it
+    place_light: bool,
+    /// Whether the mouse is captured in relative mode (see [`apply_mouse_capture`])
+    mouse_captured: bool,
+    /// Whether the capture was just toggled, so the next motion event is discarded.
+    /// It carries the movement accumulated before capturing or the jump of the cursor warped on release.
+    just_captured: bool,
+    /// Resolution scale of the screenshot to save after the next frame, if requested
+    screenshot_scale: Option<u32>,
+    /// Samples per pixel of screenshots; values below 2 disable multisampling
+    screenshot_samples: u32,
+
+    /// Running unattended presentation, if any
+    demo_reel: Option<DemoReel>,
+    /// Time between two steps of the demo reel
+    demo_reel_interval: Duration,
+    /// Animated sun replacing the fixed light, if enabled
+    sun_cycle: Option<SunCycle>,
+    /// Time of a full day of the animated sun
+    sun_cycle_time: Duration,
+
+    /// Textures of the displayed material
+    texture: TextureCollection,
+    /// Flag to blend the detail map on top of the base texture
+    detail_enabled: bool,
+    /// Textures of the material selected by the material-ID map, loaded when it is first enabled
+    secondary_texture: Option<TextureCollection>,
+    /// Flag to blend between the displayed and the secondary material with the material-ID map
+    multi_material: bool,
+
+    /// Exponent of the specular highlight (higher values result in a tighter highlight)
+    shininess: f32,
+    /// Fraction of the surface color that is visible without direct light
+    ambient_strength: f32,
+    /// Factor for the slope of the normal map (`0.0` is flat, `1.0` as authored)
+    normal_strength: f32,
+    /// Index of the used strength of the emissive map
+    emissive_idx: usize,
+    /// Flag to interpret the normal map as world-space instead of tangent-space normals
+    world_space_normals: bool,
+    /// Flag to sample the height map with the texture filter instead of the nearest texel
+    smooth_height: bool,
+    /// Index of the tangent space vector shown by the tangent space program
+    tangent_space_vector_idx: usize,
+
+    /// Index of the used background color preset
+    clear_color_idx: usize,
+    /// Index of the used light color preset
+    light_color_idx: usize,
+    /// Flag to discard fragments hidden behind already rendered geometry
+    depth_test: bool,
+    /// Flag to skip triangles facing away from the camera
+    backface_culling: bool,
+    /// Flag to light back faces with the flipped normal, so the mesh looks correct from both sides without culling
+    double_sided: bool,
+    /// Flag to place the light at the camera instead of its fixed position
+    headlamp: bool,
+    /// Position of the light while it neither follows the camera nor the sun; can be placed by clicking the surface
+    fixed_light_pos: Vector3<f32>,
+    /// Depth of the mesh as seen from the light, if shadows are enabled
+    shadow_map: Option<Framebuffer>,
+    /// Offset subtracted from the depth of a fragment before comparing it with the shadow map
+    shadow_bias: f32,
+    /// Index of the used fog mode
+    fog_mode_idx: usize,
+    /// Density of the exponential fog
+    fog_density: f32,
+    /// Distance to the camera where the linear fog starts
+    fog_start: f32,
+    /// Distance to the camera where the linear fog hides the surface completely
+    fog_end: f32,
+    /// Color of the fog in sRGB, if it should not match the background color
+    fog_color: Option<[f32; 3]>,
+
+    /// Time of the last check for modified shader sources, if programs are reloaded automatically
+    shader_reload_check: Option<Instant>,
+
+    /// Minimum duration of a frame, if the frame rate is limited
+    frame_time: Option<Duration>,
+    /// Timer to measure the GPU time of the mesh, if enabled
+    gpu_timer: Option<GpuTimer>,
+
+    /// Current size of the window in pixels
+    window_size: (u32, u32),
+    /// Flag to keep a fixed aspect ratio by adding bars to the sides of the viewport instead of stretching the scene
+    letterbox: bool,
+    /// Index of the aspect ratio kept when letterboxing
+    letterbox_aspect_idx: usize,
+    /// Aspect ratio of the current viewport, used for the projection
+    aspect: f32,
+
+    /// Tessellation level of the edges and the inside of the patches for tessellated programs
+    tess_level: f32,
+    /// Largest tessellation level supported by the driver
+    max_tess_level: f32,
+
+    /// Amount of layers the view ray is divided into by parallax programs when looking along the surface
+    parallax_layers: i32,
+    /// Amount of layers when looking straight at the surface; the amount is interpolated between both by the view angle
+    min_parallax_layers: i32,
+    /// Flag to refine the parallax intersection by a binary search between the last two layers
+    parallax_refinement: bool,
+    /// Flag to change the parallax layers instead of the mesh samples with the detail keys, if the program supports it
+    shader_detail: bool,
 
     /// Index to determine the amount of samples to generate
     samples_idx: usize,
-
+    /// Maximum amount of vertices the generated mesh may contain
+    max_vertices: u64,
+    /// Height map on the CPU, if the square is displaced into a terrain
+    terrain: Option<Arc<HeightMap>>,
+    /// Direction the terrain is displaced along
+    displacement_axis: DisplacementAxis,
+    /// Direction of the custom displacement axis, which is selected after the world up axis
+    custom_displacement_axis: Vector3<f32>,
+    /// Index of the used level of the terrain decimation (see [`DECIMATION_LEVELS`])
+    decimation_idx: usize,
+    /// Flag to inset the texture coordinates of the square instead of spanning exactly `0..1` (see [`UvMapping`])
+    clamp_uvs: bool,
+    /// Inset of the clamped texture coordinates on each side in texels of the base texture
+    uv_inset: f32,
+    /// Flag to use the face normal for all vertices of a triangle instead of the interpolated surface normals
+    flat_normals: bool,
+    /// Flag to use the normal of the first vertex for the whole triangle in programs supporting it, instead of interpolating
+    flat_shading: bool,
+    /// Flag to add a color to every vertex (see [`mesh::color_cells`])
+    vertex_colors: bool,
+
+    /// Vertex data that is currently stored in the VBO.
+    /// It is retained for features working on the CPU-side mesh (e.g. OBJ-export).
+    /// Note that it needs `samples² * 6 * 14 * 4` bytes with the default layout, which are ~336MiB at 1024x1024 samples and ~1.3GiB at 2048x2048 samples.
+    vertices: Vec<f32>,
+    /// Attributes contained in the vertex data and their order, which determines the configuration of the VAO.
+    /// The color is added and removed with the vertex colors.
+    vertex_layout: VertexLayout,
     /// OpenGL-Id of the VBO
     vbo_id: GLuint,
     /// OpenGL-Id of the VAO
     vao_id: GLuint,
     /// Current count of vertices
     point_count: u32,
+    /// Channel the mesh generated in the background is received from, while the generation is in progress
+    mesh_generation: Option<Receiver<GeneratedMesh>>,
+    /// Number of the latest mesh generation; workers of superseded generations stop at their next row
+    latest_mesh_generation: Arc<AtomicUsize>,
+    /// Flag to fit the mesh into the view once the mesh in generation is uploaded
+    frame_generated_mesh: bool,
+
+    /// OpenGL-Id of the VBO containing the per-instance model matrices
+    instance_vbo_id: GLuint,
+    /// Model matrices of the rendered instances, laid out in a grid
+    instance_transforms: Vec<Matrix4<f32>>,
+    /// Point of the scene that is moved to the world origin, which is the pivot of the camera animations
+    scene_offset: Vector3<f32>,
+
+    /// Configuration loaded at startup, completing the exported settings
+    startup_config: Config,
 }
 
 impl AppState {
     /// Initialize the AppState with default values
-    fn new(res: &Resources) -> Result<AppState, String> {
+    fn new(res: &Resources, config: &Config) -> Result<AppState, String> {
+        let keymap = KeyMap::from_resources(res, KEYMAP_NAME).unwrap_or_else(|e| {
+            warn!(target: LOG_TARGET, "Could not load key bindings, using defaults: {}", e);
+            KeyMap::default()
+        });
+
         let mut state = AppState {
-            camera: Camera::new(),
+            keymap,
+
+            camera: {
+                let camera = Camera::with_world_up(Vector3::from(config.world_up))
+                    .with_lock_axis(Vector3::from(config.lock_axis));
+                match &config.initial_pose {
+                    Some(pose) => camera.with_initial_pose(pose.into()),
+                    None => camera,
+                }
+            },
+            camera_path: CameraPath::new(),
+            sprinting: false,
+            sprint_factor: config.sprint_factor.clamp(MIN_SPRINT_FACTOR, MAX_SPRINT_FACTOR),
             should_terminate: false,
 
             used_program_idx: 0,
             available_programs: Vec::new(),
             available_program_names: Vec::new(),
-
-            samples_idx: SAMPLE_START_IDX,
-
+            available_program_resources: Vec::new(),
+
+            line_program: Program::from_res_cached(res, LINE_PROGRAM_NAME)?,
+            axes: Lines::axes(AXES_LENGTH),
+            show_axes: false,
+
+            skybox_program: Program::from_res_cached(res, SKYBOX_PROGRAM_NAME)?,
+            skybox: None,
+            show_skybox: false,
+
+            grid_program: Program::from_res_cached(res, GRID_PROGRAM_NAME)?,
+            floor_grid: FloorGrid::new(GRID_EXTENT),
+            show_grid: false,
+            grid_cell_size: config.grid_cell_size,
+            grid_fade_distance: config.grid_fade_distance,
+
+            text_program: Program::from_res_cached(res, TEXT_PROGRAM_NAME)?,
+            help_overlay: TextOverlay::new(HELP_BACKGROUND_COLOR),
+            show_help: false,
+            status_overlay: {
+                let mut overlay = TextOverlay::new(HELP_BACKGROUND_COLOR);
+                overlay.set_lines(vec![TextLine::new(GENERATING_STATUS_TEXT, HELP_HEADING_COLOR)]);
+                overlay
+            },
+
+            show_wireframe: false,
+            wireframe_color: Vector3::from(config.wireframe_color.map(srgb_to_linear)),
+
+            per_frame_buffer: UniformBuffer::new(PER_FRAME_BINDING),
+
+            map_program: Program::from_res_cached(res, MAP_VIEW_PROGRAM_NAME)?,
+            screen_quad: ScreenQuad::new(),
+            tonemap_program: Program::from_sources(res, TONEMAP_PROGRAM)?,
+            hdr_enabled: config.hdr,
+            hdr_buffer: None,
+            exposure: config.exposure.clamp(MIN_EXPOSURE, MAX_EXPOSURE),
+            render_scale: config.render_scale.clamp(RENDER_SCALES[0], RENDER_SCALES[RENDER_SCALES.len() - 1]),
+            scaled_buffer: None,
+            tonemap_idx: 0,
+            map_view: None,
+            map_inset: None,
+            pick_request: None,
+            place_light: false,
+            mouse_captured: false,
+            just_captured: false,
+            screenshot_scale: None,
+            screenshot_samples: config.screenshot_samples,
+
+            demo_reel: None,
+            demo_reel_interval: Duration::from_secs_f32(config.demo_reel_interval.max(1.0)),
+            sun_cycle: None,
+            sun_cycle_time: Duration::from_secs_f32(config.sun_cycle_time.max(1.0)),
+
+            texture: match MaterialDef::from_resources(res, MATERIAL_FOLDER)? {
+                Some(material) => TextureCollection::from_material(res, &material)?,
+                None => TextureCollection::from_resources(res, TEXTURE_NAME, TEXTURE_EXTENSION)?,
+            },
+            detail_enabled: false,
+            secondary_texture: None,
+            multi_material: false,
+
+            shininess: config.shininess.clamp(MIN_SHININESS, MAX_SHININESS),
+            ambient_strength: config.ambient_strength.clamp(0.0, 1.0),
+            normal_strength: config.normal_strength.clamp(0.0, MAX_NORMAL_STRENGTH),
+            emissive_idx: EMISSIVE_START_IDX,
+            world_space_normals: false,
+            smooth_height: true,
+            tangent_space_vector_idx: 0,
+
+            clear_color_idx: preset_index(CLEAR_COLORS.map(|(name, _)| name), &config.clear_color, "background color"),
+            light_color_idx: preset_index(LIGHT_COLORS.map(|(name, _)| name), &config.light_color, "light color"),
+            depth_test: true,
+            backface_culling: true,
+            double_sided: false,
+            headlamp: config.headlamp,
+            fixed_light_pos: Vector3::from(config.light_position),
+            shadow_map: None,
+            shadow_bias: config.shadow_bias,
+            fog_mode_idx: preset_index(FOG_MODES, &config.fog_mode, "fog mode"),
+            fog_density: config.fog_density.clamp(MIN_FOG_DENSITY, MAX_FOG_DENSITY),
+            fog_start: config.fog_start,
+            // The linear fog needs a range to fade in
+            fog_end: config.fog_end.max(config.fog_start + 0.1),
+            fog_color: config.fog_color,
+
+            shader_reload_check: config.auto_reload_shaders.then(Instant::now),
+
+            frame_time: (config.frame_limit > 0).then(|| Duration::from_secs_f64(1.0 / config.frame_limit as f64)),
+            gpu_timer: None,
+
+            window_size: (WINDOW_WIDTH, WINDOW_HEIGHT),
+            letterbox: false,
+            letterbox_aspect_idx: 0,
+            aspect: LETTERBOX_ASPECTS[0].1,
+
+            tess_level: TESS_LEVEL_START,
+            max_tess_level: TESS_LEVEL_START,
+
+            parallax_layers: PARALLAX_LAYERS_START,
+            min_parallax_layers: MIN_PARALLAX_LAYERS_START,
+            parallax_refinement: false,
+            shader_detail: false,
+
+            samples_idx: match SAMPLE_PRESET_NAMES.iter().position(|&name| name == config.sample_preset) {
+                Some(idx) if sample_vertex_count(idx) <= config.max_vertices => idx,
+                Some(_) => {
+                    warn!(target: LOG_TARGET, "Sample preset \"{}\" exceeds the vertex budget, using the default", config.sample_preset);
+                    SAMPLE_START_IDX
+                }
+                None => {
+                    warn!(target: LOG_TARGET, "Unknown sample preset \"{}\", using the default", config.sample_preset);
+                    SAMPLE_START_IDX
+                }
+            },
+            max_vertices: config.max_vertices,
+            terrain: None,
+            displacement_axis: DisplacementAxis::from_name(&config.displacement_axis, Vector3::from(config.custom_displacement_axis)),
+            custom_displacement_axis: Vector3::from(config.custom_displacement_axis),
+            decimation_idx: preset_index(DECIMATION_LEVELS.map(|(name, _)| name), &config.decimation, "decimation level"),
+            clamp_uvs: config.clamp_uvs,
+            uv_inset: config.uv_inset.max(0.0),
+            flat_normals: false,
+            flat_shading: false,
+            vertex_colors: config.vertex_attributes.contains(&VertexAttribute::Color),
+
+            vertices: Vec::new(),
+            vertex_layout: VertexLayout::new(config.vertex_attributes.clone()).unwrap_or_else(|e| {
+                warn!(target: LOG_TARGET, "{}, using the default layout", e);
+                VertexLayout::default()
+            }),
             vbo_id: 0,
             vao_id: 0,
             point_count: 0,
+            mesh_generation: None,
+            latest_mesh_generation: Arc::new(AtomicUsize::new(0)),
+            frame_generated_mesh: false,
+
+            instance_vbo_id: 0,
+            instance_transforms: Vec::new(),
+            scene_offset: Vector3::zero(),
+
+            startup_config: config.clone(),
         };
 
-        // Load and initialize programs
-        state.available_program_names.push("Kein Mapping".to_string());
-        state.available_programs.push(
-            Program::from_res(res, "shaders/base")
-                .map_err(|s| s)?);
+        // Load and initialize all programs with a vertex shader in the shader folder.
+        // Programs that fail to load are skipped, so a single broken shader does not prevent the start.
+        let shader_files = res.list_dir(SHADER_DIR)
+            .map_err(|e| format!("Error listing shaders: {:?}", e))?;
+        for program_name in shader_files.iter().filter_map(|file| file.strip_suffix(".vert")) {
+            let program_resource = format!("{}/{}", SHADER_DIR, program_name);
+            let program = match Program::from_res_cached(res, &program_resource) {
+                Ok(program) => program,
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Skipping program \"{}\", which could not be loaded: {}", program_name, e);
+                    continue;
+                }
+            };
+            debug!(target: LOG_TARGET, "Active uniforms of program \"{}\": {:?}", program_name, program.active_uniforms());
 
-        state.available_program_names.push("Normal-Mapping".to_string());
-        state.available_programs.push(
-            Program::from_res(res, "shaders/normal")
-                .map_err(|s| s)?);
+            // Uniforms that are misnamed or optimized out would otherwise be ignored silently when setting them
+            let missing_required = program.missing_properties(&REQUIRED_UNIFORMS);
+            if !missing_required.is_empty() {
+                warn!(target: LOG_TARGET, "Program \"{}\" is missing the uniforms {:?}", program_name, missing_required);
+            }
+            let missing_features = program.missing_properties(&FEATURE_UNIFORMS);
+            if !missing_features.is_empty() {
+                info!(target: LOG_TARGET, "Program \"{}\" does not support the feature uniforms {:?}", program_name, missing_features);
+            }
 
-        state.available_program_names.push("Parallax-Mapping".to_string());
-        state.available_programs.push(
-            Program::from_res(res, "shaders/parallax")
-                .map_err(|s| s)?);
+            state.available_programs.push(program);
+            state.available_program_names.push(program_display_name(program_name));
+            state.available_program_resources.push(program_resource);
+        }
+        if state.available_programs.is_empty() {
+            return Err(format!("No program could be loaded from \"{}\"", SHADER_DIR));
+        }
+        info!(target: LOG_TARGET, "Loaded {} programs: {:?}", state.available_programs.len(), state.available_program_names);
+        if let Some(program_name) = &config.program {
+            let program_resource = format!("{}/{}", SHADER_DIR, program_name);
+            match state.available_program_resources.iter().position(|resource| *resource == program_resource) {
+                Some(idx) => state.used_program_idx = idx,
+                None => warn!(target: LOG_TARGET, "Program \"{}\" is not available, using \"{}\"", program_name, state.available_program_names[0]),
+            }
+        }
 
-        state.available_program_names.push("Displacement-Mapping".to_string());
-        state.available_programs.push(
-            Program::from_res(res, "shaders/displacement")
-                .map_err(|s| s)?);
+        let max_tess_level = gl_query::get_int(gl::MAX_TESS_GEN_LEVEL);
+        debug!(target: LOG_TARGET, "Maximum tessellation level: {}", max_tess_level);
+        state.max_tess_level = (max_tess_level as f32).max(1.0);
+        state.tess_level = config.tess_level.clamp(1.0, state.max_tess_level);
+
+        state.texture.log_resolutions();
+        state.camera.set_invert_y(config.invert_y);
+        state.camera.set_mouse_smoothing(config.mouse_smoothing);
+        state.camera.set_mouse_dead_zone(config.mouse_dead_zone);
+        if config.shadows {
+            state.toggle_shadows();
+        }
+        state.apply_clear_color();
+        state.update_viewport();
 
         // Init buffers
         unsafe {
             gl::GenBuffers(1, &mut state.vbo_id);
+            gl::GenBuffers(1, &mut state.instance_vbo_id);
         }
         state.refresh_vbo();
-        state.vao_id = configure_vao(state.vbo_id);
+        state.vao_id = configure_vao(state.vbo_id, &state.vertex_layout);
+        configure_instance_attributes(state.vao_id, state.instance_vbo_id);
+        // The first frame needs a mesh, so the startup waits for the generation
+        state.receive_mesh(true);
+        state.refresh_instances(1);
 
         Ok(state)
     }
@@ -367,35 +1855,1420 @@ impl AppState {
         self.should_terminate = true;
     }
 
-    pub fn cycle_programs(&mut self) {
-        self.used_program_idx = (self.used_program_idx + 1) % self.available_programs.len();
+    /// Moves the camera one step in the given direction, faster while sprinting
+    pub fn move_camera(&mut self, dir: MovementDirection) {
+        let factor = if self.sprinting { self.sprint_factor } else { 1.0 };
+        self.camera.move_camera(dir, MOVEMENT_AMOUNT * factor);
+    }
+
+    pub fn toggle_invert_y(&mut self) {
+        let invert_y = !self.camera.invert_y();
+        self.camera.set_invert_y(invert_y);
+        info!(target: LOG_TARGET, "Vertical mouse axis {}", if invert_y { "inverted" } else { "not inverted" });
+    }
+
+    /// Toggles the ground mode for browsing the mesh: the camera keeps its height above the mesh plane and its pitch,
+    /// and can only move parallel to the mesh and turn
+    pub fn toggle_ground_mode(&mut self) {
+        let ground_mode = !(self.camera.lock_vertical() && self.camera.lock_pitch());
+        self.camera.set_lock_vertical(ground_mode);
+        self.camera.set_lock_pitch(ground_mode);
+        info!(target: LOG_TARGET, "Ground mode {}", if ground_mode { "enabled (no vertical movement and pitch)" } else { "disabled" });
+    }
+
+    /// Toggles the mouse capture, which is applied to the window at the start of the next frame
+    pub fn toggle_mouse_capture(&mut self) {
+        self.mouse_captured = !self.mouse_captured;
+        info!(target: LOG_TARGET, "Mouse {}", if self.mouse_captured { "captured" } else { "released" });
+    }
+
+    /// Toggles whether the camera collides with the plane of the mesh.
+    /// The mesh lies in the xy-plane and is viewed from the negative z-side, so the camera is kept at `z <= -GROUND_PLANE_OFFSET`.
+    pub fn toggle_ground_plane(&mut self) {
+        let ground_plane = match self.camera.ground_plane() {
+            Some(_) => None,
+            None => Some(GroundPlane {
+                normal: MESH_PLANE_NORMAL,
+                distance: GROUND_PLANE_OFFSET,
+            }),
+        };
+        self.camera.set_ground_plane(ground_plane);
+        info!(target: LOG_TARGET, "Camera collision with the mesh plane {}", if ground_plane.is_some() { "enabled" } else { "disabled" });
+    }
+
+    pub fn cycle_programs(&mut self) {
+        if self.available_programs.len() <= 1 {
+            info!(target: LOG_TARGET, "No other program available");
+            return;
+        }
+        self.used_program_idx = (self.used_program_idx + 1) % self.available_programs.len();
         info!(target: LOG_TARGET, "Using program {}: \"{}\"", self.used_program_idx, self.available_program_names.get(self.used_program_idx).unwrap());
     }
 
-    pub fn current_program(&mut self) -> Option<&mut Program> {
-        self.available_programs.get_mut(self.used_program_idx)
+    pub fn current_program(&self) -> Option<&Program> {
+        self.available_programs.get(self.used_program_idx)
+    }
+
+    pub fn reload_textures(&mut self, res: &Resources) {
+        match self.texture.reload_all(res) {
+            Ok(_) => {
+                info!(target: LOG_TARGET, "Reloaded textures");
+                self.texture.log_resolutions();
+            }
+            Err(e) => warn!(target: LOG_TARGET, "Reloading textures failed, keeping previous textures: {}", e),
+        }
+        if let Some(secondary_texture) = self.secondary_texture.as_mut() {
+            if let Err(e) = secondary_texture.reload_all(res) {
+                warn!(target: LOG_TARGET, "Reloading the secondary material failed, keeping previous textures: {}", e);
+            }
+        }
+    }
+
+    /// Returns all programs with their shaders: the programs for the mesh followed by the helper programs
+    fn programs_mut(&mut self) -> impl Iterator<Item = (&mut Program, ProgramSources<'_>)> {
+        let util_programs = [
+            &mut self.line_program,
+            &mut self.map_program,
+            &mut self.tonemap_program,
+            &mut self.skybox_program,
+            &mut self.grid_program,
+            &mut self.text_program,
+        ];
+        self.available_programs.iter_mut()
+            .zip(self.available_program_resources.iter().map(|name| ProgramSources::Named(name)))
+            .chain(util_programs.into_iter().zip(UTIL_PROGRAMS))
+    }
+
+    /// Reloads all programs and the textures. Everything that fails to reload keeps its previous state.
+    pub fn reload_all(&mut self, res: &Resources) {
+        let mut failed = Vec::new();
+
+        let mut program_count = 0;
+        for (program, resource_name) in self.programs_mut() {
+            program_count += 1;
+            if let Err(e) = program.reload(res, resource_name) {
+                warn!(target: LOG_TARGET, "Reloading program \"{}\" failed, keeping previous program: {}", resource_name, e);
+                failed.push(resource_name.to_string());
+            }
+        }
+
+        if let Err(e) = self.texture.reload_all(res) {
+            warn!(target: LOG_TARGET, "Reloading textures failed, keeping previous textures: {}", e);
+            failed.push("textures".to_string());
+        }
+        if let Some(Err(e)) = self.secondary_texture.as_mut().map(|texture| texture.reload_all(res)) {
+            warn!(target: LOG_TARGET, "Reloading the secondary material failed, keeping previous textures: {}", e);
+            failed.push("secondary material".to_string());
+        }
+
+        if failed.is_empty() {
+            info!(target: LOG_TARGET, "Reloaded {} programs and the textures", program_count);
+        } else {
+            warn!(target: LOG_TARGET, "Reloaded {} programs and the textures, but reloading failed for {:?}", program_count, failed);
+        }
+    }
+
+    /// Reloads the programs whose shader sources were modified, checking at most once per [`SHADER_RELOAD_INTERVAL`].
+    /// A program that fails to reload keeps its previous version until its sources are changed again.
+    pub fn auto_reload_programs(&mut self, res: &Resources) {
+        match self.shader_reload_check {
+            Some(last_check) if last_check.elapsed() >= SHADER_RELOAD_INTERVAL => self.shader_reload_check = Some(Instant::now()),
+            _ => return,
+        }
+
+        for (program, resource_name) in self.programs_mut() {
+            if !program.needs_reload(res) {
+                continue;
+            }
+            match program.reload(res, resource_name) {
+                Ok(_) => info!(target: LOG_TARGET, "Reloaded modified program \"{}\"", resource_name),
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Reloading modified program \"{}\" failed, keeping previous program: {}", resource_name, e);
+                    program.accept_source_changes(res);
+                }
+            }
+        }
+    }
+
+    pub fn toggle_mipmaps(&mut self, res: &Resources) {
+        let mipmaps = !self.texture.mipmaps();
+        match self.texture.set_mipmaps(res, mipmaps) {
+            Ok(_) => info!(target: LOG_TARGET, "Mipmapping {}", if mipmaps { "enabled" } else { "disabled" }),
+            Err(e) => warn!(target: LOG_TARGET, "Reloading textures without mipmaps failed: {}", e),
+        }
+    }
+
+    /// Switches the magnification filter of the textures between linear and nearest, without reloading them
+    pub fn toggle_mag_filter(&mut self) {
+        let filter = if self.texture.mag_filter() == gl::NEAREST { gl::LINEAR } else { gl::NEAREST };
+        self.texture.set_mag_filter(filter);
+        info!(target: LOG_TARGET, "Magnifying textures {}", if filter == gl::NEAREST { "with the nearest texel" } else { "linearly" });
+    }
+
+    /// Toggles blending a secondary material into the displayed one with the material-ID map.
+    /// The secondary material is loaded from the configuration when it is enabled for the first time.
+    pub fn toggle_multi_material(&mut self, res: &Resources) {
+        if self.multi_material {
+            self.multi_material = false;
+            info!(target: LOG_TARGET, "Disabled the secondary material");
+            return;
+        }
+        if !self.texture.has_material_ids() {
+            info!(target: LOG_TARGET, "The current material has no material-ID map");
+            return;
+        }
+
+        if self.secondary_texture.is_none() {
+            let name = match self.startup_config.secondary_material.as_deref() {
+                Some(name) => name,
+                None => {
+                    info!(target: LOG_TARGET, "No secondary material configured (`secondary_material`)");
+                    return;
+                }
+            };
+            let texture = MaterialDef::from_resources(res, name)
+                .and_then(|material| match material {
+                    Some(material) => TextureCollection::from_material(res, &material),
+                    None => TextureCollection::from_resources(res, name, TEXTURE_EXTENSION),
+                });
+            match texture {
+                Ok(texture) => {
+                    texture.log_resolutions();
+                    self.secondary_texture = Some(texture);
+                }
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Could not load the secondary material \"{}\": {}", name, e);
+                    return;
+                }
+            }
+        }
+
+        self.multi_material = true;
+        let name = self.secondary_texture.as_ref().map(TextureCollection::name).unwrap_or_default();
+        info!(target: LOG_TARGET, "Blending the secondary material \"{}\" by the material-ID map", name);
+        if !self.current_program().map(|program| program.has_property("secondaryBaseMap")).unwrap_or(false) {
+            info!(target: LOG_TARGET, "The current program does not shade multiple materials; switch to the displacement program");
+        }
+    }
+
+    pub fn toggle_detail(&mut self) {
+        if !self.texture.has_detail() {
+            info!(target: LOG_TARGET, "The current material has no detail map");
+            return;
+        }
+        self.detail_enabled = !self.detail_enabled;
+        info!(target: LOG_TARGET, "Detail map {}", if self.detail_enabled { "enabled" } else { "disabled" });
+    }
+
+    pub fn change_ambient_strength(&mut self, delta: f32) {
+        self.ambient_strength = (self.ambient_strength + delta).clamp(0.0, 1.0);
+        info!(target: LOG_TARGET, "Using ambient strength {:.2}", self.ambient_strength);
+    }
+
+    pub fn change_normal_strength(&mut self, delta: f32) {
+        self.normal_strength = (self.normal_strength + delta).clamp(0.0, MAX_NORMAL_STRENGTH);
+        info!(target: LOG_TARGET, "Using normal map strength {:.2}", self.normal_strength);
+    }
+
+    pub fn change_shininess(&mut self, factor: f32) {
+        self.shininess = (self.shininess * factor).clamp(MIN_SHININESS, MAX_SHININESS);
+        info!(target: LOG_TARGET, "Using specular shininess {}", self.shininess);
+    }
+
+    pub fn record_keyframe(&mut self) {
+        self.camera_path.record(self.camera.pose());
+        info!(target: LOG_TARGET, "Recorded camera keyframe {}", self.camera_path.len());
+    }
+
+    pub fn toggle_camera_path_playback(&mut self) {
+        if self.camera_path.is_playing() {
+            self.camera_path.stop();
+            info!(target: LOG_TARGET, "Stopped camera path");
+            return;
+        }
+
+        match self.camera_path.play() {
+            Ok(_) => info!(target: LOG_TARGET, "Playing camera path with {} keyframes", self.camera_path.len()),
+            Err(e) => warn!(target: LOG_TARGET, "Could not play camera path: {}", e),
+        }
+    }
+
+    pub fn toggle_demo_reel(&mut self) {
+        if self.demo_reel.is_some() {
+            self.stop_demo_reel();
+            return;
+        }
+
+        self.camera_path.stop();
+        self.demo_reel = Some(DemoReel::new(self.demo_reel_interval));
+        info!(target: LOG_TARGET, "Started demo reel, switching every {:?}", self.demo_reel_interval);
+    }
+
+    pub fn stop_demo_reel(&mut self) {
+        if self.demo_reel.take().is_some() {
+            info!(target: LOG_TARGET, "Stopped demo reel");
+        }
+    }
+
+    /// Moves the camera of the running demo reel and switches to the next program once its interval elapsed.
+    /// After all programs were shown, the light color and the sample amount change as well.
+    fn update_demo_reel(&mut self) {
+        let reel = match self.demo_reel.as_mut() {
+            Some(reel) => reel,
+            None => return,
+        };
+        let step = reel.next_step();
+        let pose = reel.camera_pose(self.camera.zoom());
+        self.camera.set_pose(&pose);
+
+        if let Some(step) = step {
+            self.cycle_programs();
+            if step % self.available_programs.len() == 0 {
+                self.cycle_light_color();
+                if self.samples_idx >= DEMO_REEL_MAX_SAMPLES_IDX {
+                    self.set_sample_index(1);
+                } else {
+                    self.increase_samples();
+                }
+            }
+        }
+    }
+
+    pub fn clear_camera_path(&mut self) {
+        self.camera_path.clear();
+        info!(target: LOG_TARGET, "Cleared camera path");
+    }
+
+    pub fn save_camera_path(&self, res: &Resources) {
+        if let Err(e) = self.camera_path.save(res, CAMERA_PATH_NAME) {
+            warn!(target: LOG_TARGET, "Could not save camera path: {}", e);
+        }
+    }
+
+    pub fn load_camera_path(&mut self, res: &Resources) {
+        match CameraPath::from_resources(res, CAMERA_PATH_NAME) {
+            Ok(path) => self.camera_path = path,
+            Err(e) => warn!(target: LOG_TARGET, "Could not load camera path: {}", e),
+        }
+    }
+
+    pub fn cycle_emissive_strength(&mut self) {
+        if !self.texture.has_emissive() {
+            info!(target: LOG_TARGET, "The current material has no emissive map");
+            return;
+        }
+        self.emissive_idx = (self.emissive_idx + 1) % EMISSIVE_STRENGTHS.len();
+        info!(target: LOG_TARGET, "Using emissive strength {}", EMISSIVE_STRENGTHS[self.emissive_idx]);
+    }
+
+    /// Strength of the emissive map; 0 if the material has none, so the unbound sampler is ignored
+    pub fn emissive_strength(&self) -> f32 {
+        if self.texture.has_emissive() {
+            EMISSIVE_STRENGTHS[self.emissive_idx]
+        } else {
+            0.0
+        }
+    }
+
+    pub fn toggle_world_space_normals(&mut self) {
+        self.world_space_normals = !self.world_space_normals;
+        info!(target: LOG_TARGET, "Interpreting normal map as {}-space normals", if self.world_space_normals { "world" } else { "tangent" });
+    }
+
+    pub fn toggle_axes(&mut self) {
+        self.show_axes = !self.show_axes;
+        info!(target: LOG_TARGET, "Coordinate axes {}", if self.show_axes { "shown" } else { "hidden" });
+    }
+
+    /// Draws the coordinate axes on top of the scene, so they are not hidden by the mesh lying in the xy-plane
+    fn draw_axes(&self, proj: &Matrix4<f32>, view: &Matrix4<f32>) {
+        self.line_program.set_active();
+        self.line_program.set_property_mat4("projection", proj);
+        self.line_program.set_property_mat4("view", view);
+        self.line_program.set_property_mat4("model", &cgmath::One::one());
+
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+        }
+        self.axes.draw();
+        if self.depth_test {
+            unsafe {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    /// Draws the skybox behind the scene. It is placed on the far plane, so it passes the depth test only where nothing was drawn.
+    fn draw_skybox(&self, skybox: &Skybox, proj: &Matrix4<f32>, view: &Matrix4<f32>) {
+        self.skybox_program.set_active();
+        self.skybox_program.set_property_mat4("projection", proj);
+        self.skybox_program.set_property_mat4("view", view);
+        self.skybox_program.set_property_int("skybox", 0);
+
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthFunc(gl::LEQUAL);
+            gl::Disable(gl::CULL_FACE);
+        }
+        skybox.draw(gl::TEXTURE0);
+        self.apply_backface_culling();
+        unsafe {
+            gl::DepthFunc(gl::LESS);
+            if !self.depth_test {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    /// Draws the floor grid at the lower edge of the mesh. The lines are blended onto the scene without writing the depth,
+    /// so the faded parts do not hide anything drawn afterwards.
+    fn draw_grid(&self, proj: &Matrix4<f32>, view: &Matrix4<f32>) {
+        self.grid_program.set_active();
+        self.grid_program.set_property_mat4("projection", proj);
+        self.grid_program.set_property_mat4("view", view);
+        self.grid_program.set_property_mat4("model", &Matrix4::from_translation(cgmath::vec3(0.0, MIN_Y, 0.0)));
+        self.grid_program.set_property_vec3("viewPos", &self.camera.position());
+        self.grid_program.set_property_vec3("gridColor", &GRID_COLOR);
+        self.grid_program.set_property_float("cellSize", self.grid_cell_size);
+        self.grid_program.set_property_float("fadeDistance", self.grid_fade_distance);
+
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::DepthMask(gl::FALSE);
+            gl::Disable(gl::CULL_FACE);
+        }
+        self.floor_grid.draw();
+        self.apply_backface_culling();
+        unsafe {
+            gl::DepthMask(gl::TRUE);
+            gl::Disable(gl::BLEND);
+        }
+    }
+
+    /// Shows or hides the overlay listing the controls. The controls are logged as well when it is shown.
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+        if self.show_help {
+            self.help_overlay.set_lines(help_lines(&self.keymap));
+            log_instructions(&self.keymap);
+        }
+        info!(target: LOG_TARGET, "Controls {}", if self.show_help { "shown" } else { "hidden" });
+    }
+
+    /// Draws the help overlay (if shown) or else the busy indicator (while the mesh is generated) on top of the frame.
+    /// Both are placed in the top left corner, so only one of them is drawn. They are blended onto the scene without testing the depth.
+    fn draw_overlays(&mut self) {
+        let overlay = if self.show_help {
+            &mut self.help_overlay
+        } else if self.generating_mesh() {
+            &mut self.status_overlay
+        } else {
+            return;
+        };
+        let [_, _, width, height] = gl_query::get_ints::<4>(gl::VIEWPORT);
+        self.text_program.set_active();
+        self.text_program.set_property_int("font", 0);
+
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::CULL_FACE);
+        }
+        overlay.draw(width.max(0) as usize, height.max(0) as usize);
+        self.apply_backface_culling();
+        unsafe {
+            gl::Disable(gl::BLEND);
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    pub fn toggle_grid(&mut self) {
+        self.show_grid = !self.show_grid;
+        info!(target: LOG_TARGET, "Floor grid {}", if self.show_grid { "shown" } else { "hidden" });
+    }
+
+    /// Draws the edges of the mesh triangles in a single color on top of the filled mesh.
+    /// The mesh is drawn again with the current program as configured for the scene, so the wireframe shows the triangles
+    /// after its tessellation and displacement. Programs without the override color draw shaded lines.
+    /// The lines are offset towards the camera, so they are not hidden by the triangles they belong to.
+    fn draw_wireframe(&self) {
+        let current_program = self.current_program().unwrap();
+        if current_program.has_property("overrideColorEnabled") {
+            current_program.set_property_bool("overrideColorEnabled", true);
+            current_program.set_property_vec3("overrideColor", &self.wireframe_color);
+        }
+
+        set_polygon_offset(gl::POLYGON_OFFSET_LINE, Some(WIREFRAME_POLYGON_OFFSET));
+        unsafe {
+            gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+        }
+        draw_mesh(self);
+        unsafe {
+            gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+        }
+        set_polygon_offset(gl::POLYGON_OFFSET_LINE, None);
+    }
+
+    pub fn toggle_wireframe(&mut self) {
+        self.show_wireframe = !self.show_wireframe;
+        info!(target: LOG_TARGET, "Wireframe overlay {}", if self.show_wireframe { "shown" } else { "hidden" });
+    }
+
+    /// Shows or hides the skybox, loading it from [`SKYBOX_FOLDER`] when it is shown for the first time.
+    /// If the skybox can not be loaded, it stays hidden.
+    pub fn toggle_skybox(&mut self, res: &Resources) {
+        if !self.show_skybox && self.skybox.is_none() {
+            match CubeMap::from_resources(res, SKYBOX_FOLDER, SKYBOX_EXTENSION) {
+                Ok(cube_map) => self.skybox = Some(Skybox::new(cube_map)),
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Could not load the skybox from \"{}\": {}", SKYBOX_FOLDER, e);
+                    return;
+                }
+            }
+        }
+        self.show_skybox = !self.show_skybox;
+        info!(target: LOG_TARGET, "Skybox {}", if self.show_skybox { "shown" } else { "hidden" });
+    }
+
+    /// Shows the next texture map of the material on the whole screen, returning to the scene after the last one
+    pub fn cycle_map_view(&mut self) {
+        let textures = self.texture.textures();
+        self.map_view = match self.map_view {
+            None => Some(0),
+            Some(idx) if idx + 1 < textures.len() => Some(idx + 1),
+            Some(_) => None,
+        };
+
+        match self.map_view {
+            Some(idx) => info!(target: LOG_TARGET, "Showing {} map", textures[idx].0),
+            None => info!(target: LOG_TARGET, "Showing scene"),
+        }
+    }
+
+    /// Shows the next texture map of the material in the corner of the scene, hiding the inset after the last one
+    pub fn cycle_map_inset(&mut self) {
+        let textures = self.texture.textures();
+        self.map_inset = match self.map_inset {
+            None => Some(0),
+            Some(idx) if idx + 1 < textures.len() => Some(idx + 1),
+            Some(_) => None,
+        };
+
+        match self.map_inset {
+            Some(idx) => info!(target: LOG_TARGET, "Showing {} map in the corner", textures[idx].0),
+            None => info!(target: LOG_TARGET, "Hiding the map in the corner"),
+        }
+    }
+
+    /// Draws the texture map with the given index into the bottom right corner of the current viewport,
+    /// on top of the scene and with the aspect ratio of the texture
+    fn draw_map_inset(&self, map_idx: usize) {
+        let texture = match self.texture.textures().get(map_idx) {
+            Some((_, texture)) => *texture,
+            None => return,
+        };
+        let [x, y, width, height] = gl_query::get_ints::<4>(gl::VIEWPORT);
+        let inset_height = (height as f32 * MAP_INSET_SIZE) as i32;
+        let inset_width = (inset_height as f32 * texture.width() as f32 / texture.height() as f32) as i32;
+        let margin = (height as f32 * MAP_INSET_MARGIN) as i32;
+
+        unsafe {
+            gl::Viewport(x + width - inset_width - margin, y + margin, inset_width, inset_height);
+            gl::Disable(gl::DEPTH_TEST);
+        }
+        self.draw_map(map_idx);
+        unsafe {
+            gl::Viewport(x, y, width, height);
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    /// Draws the texture map with the given index on the whole screen
+    fn draw_map(&self, map_idx: usize) {
+        let textures = self.texture.textures();
+        let (texture_type, texture) = match textures.get(map_idx) {
+            Some(map) => *map,
+            None => return,
+        };
+
+        self.map_program.set_active();
+        texture.bind_texture(gl::TEXTURE0);
+        self.map_program.set_property_int("map", 0);
+        self.map_program.set_property_bool("singleChannel", texture_type == "height");
+        self.screen_quad.draw();
+    }
+
+    /// Draws the HDR buffer tone mapped on the whole screen
+    fn draw_tonemapped(&self, hdr_buffer: &Framebuffer) {
+        self.tonemap_program.set_active();
+        hdr_buffer.bind_color_texture(gl::TEXTURE0);
+        self.tonemap_program.set_property_int("hdrBuffer", 0);
+        self.tonemap_program.set_property_float("exposure", self.exposure);
+        self.tonemap_program.set_property_int("tonemapOperator", self.tonemap_idx as i32);
+        self.screen_quad.draw();
+    }
+
+    /// Draws the frame rendered at a different scale stretched onto the whole screen.
+    /// The buffer is filtered linearly, which averages the pixels of a larger buffer and interpolates those of a smaller one.
+    fn draw_scaled(&self, scaled_buffer: &Framebuffer) {
+        self.map_program.set_active();
+        scaled_buffer.bind_color_texture(gl::TEXTURE0);
+        self.map_program.set_property_int("map", 0);
+        self.map_program.set_property_bool("singleChannel", false);
+        self.screen_quad.draw();
+    }
+
+    /// Takes the scaled buffer with the given size, creating a new one if the size differs.
+    /// Resets the render scale if the buffer can not be created.
+    fn take_scaled_buffer(&mut self, width: u32, height: u32) -> Option<Framebuffer> {
+        if let Some(scaled_buffer) = self.scaled_buffer.take() {
+            if scaled_buffer.width() == width && scaled_buffer.height() == height {
+                return Some(scaled_buffer);
+            }
+        }
+
+        match Framebuffer::new(width, height) {
+            Ok(scaled_buffer) => Some(scaled_buffer),
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not create the {}x{} scaled buffer, resetting the render scale: {}", width, height, e);
+                self.render_scale = 1.0;
+                None
+            }
+        }
+    }
+
+    /// Resolution the scene is rendered at in the window, which is the size of the viewport scaled by the render scale
+    fn render_resolution(&self) -> (u32, u32) {
+        let (_, _, width, height) = self.viewport();
+        let scale = |size: u32| ((size as f32 * self.render_scale).round() as u32).max(1);
+        (scale(width), scale(height))
+    }
+
+    /// Switches to the next larger render scale, starting over at the smallest one
+    pub fn cycle_render_scale(&mut self) {
+        self.render_scale = RENDER_SCALES.iter()
+            .copied()
+            .find(|&scale| scale > self.render_scale)
+            .unwrap_or(RENDER_SCALES[0]);
+        if self.render_scale == 1.0 {
+            self.scaled_buffer = None;
+        }
+        let (width, height) = self.render_resolution();
+        info!(target: LOG_TARGET, "Using render scale {}x, rendering at {}x{}", self.render_scale, width, height);
+    }
+
+    /// Takes the HDR buffer with the given size, creating a new one if the size differs.
+    /// Disables HDR if the buffer can not be created.
+    fn take_hdr_buffer(&mut self, width: u32, height: u32) -> Option<Framebuffer> {
+        if let Some(hdr_buffer) = self.hdr_buffer.take() {
+            if hdr_buffer.width() == width && hdr_buffer.height() == height {
+                return Some(hdr_buffer);
+            }
+        }
+
+        match Framebuffer::hdr(width, height) {
+            Ok(hdr_buffer) => Some(hdr_buffer),
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not create the {}x{} HDR buffer, disabling HDR: {}", width, height, e);
+                self.hdr_enabled = false;
+                None
+            }
+        }
+    }
+
+    pub fn toggle_hdr(&mut self) {
+        self.hdr_enabled = !self.hdr_enabled;
+        if !self.hdr_enabled {
+            self.hdr_buffer = None;
+        }
+        info!(target: LOG_TARGET, "HDR rendering {}", if self.hdr_enabled { "enabled" } else { "disabled" });
+    }
+
+    pub fn change_exposure(&mut self, factor: f32) {
+        self.exposure = (self.exposure * factor).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+        info!(target: LOG_TARGET, "Using exposure {:.2}", self.exposure);
+    }
+
+    pub fn cycle_tonemap_operator(&mut self) {
+        self.tonemap_idx = (self.tonemap_idx + 1) % TONEMAP_OPERATORS.len();
+        info!(target: LOG_TARGET, "Using {} tone mapping", TONEMAP_OPERATORS[self.tonemap_idx]);
+    }
+
+    /// Saves a screenshot with `scale` times the resolution of the viewport after the next frame
+    pub fn request_screenshot(&mut self, scale: u32) {
+        self.screenshot_scale = Some(scale);
+    }
+
+    pub fn toggle_gpu_timer(&mut self) {
+        if self.gpu_timer.take().is_some() {
+            info!(target: LOG_TARGET, "Disabled GPU time measurement");
+            return;
+        }
+
+        match GpuTimer::new() {
+            Ok(timer) => {
+                self.gpu_timer = Some(timer);
+                info!(target: LOG_TARGET, "Enabled GPU time measurement");
+            }
+            Err(e) => warn!(target: LOG_TARGET, "Could not enable GPU time measurement: {}", e),
+        }
+    }
+
+    pub fn cycle_clear_color(&mut self) {
+        self.clear_color_idx = (self.clear_color_idx + 1) % CLEAR_COLORS.len();
+        info!(target: LOG_TARGET, "Using {} background color", CLEAR_COLORS[self.clear_color_idx].0);
+        self.apply_clear_color();
+    }
+
+    /// Sets the current background color for all following calls to `gl::Clear`.
+    /// The presets are given in sRGB, so they are converted to linear values for the sRGB framebuffer.
+    fn apply_clear_color(&self) {
+        let [r, g, b] = CLEAR_COLORS[self.clear_color_idx].1.map(srgb_to_linear);
+        unsafe {
+            gl::ClearColor(r, g, b, 1.0);
+        }
+    }
+
+    pub fn cycle_light_color(&mut self) {
+        self.light_color_idx = (self.light_color_idx + 1) % LIGHT_COLORS.len();
+        info!(target: LOG_TARGET, "Using {} light color", LIGHT_COLORS[self.light_color_idx].0);
+    }
+
+    /// Color of the light in linear values. The presets are given in sRGB, like the background colors.
+    /// While the sun is animated, its color replaces the preset.
+    fn light_color(&self) -> Vector3<f32> {
+        let color = match self.sun_cycle.as_ref() {
+            Some(sun_cycle) => sun_cycle.color(),
+            None => LIGHT_COLORS[self.light_color_idx].1,
+        };
+        Vector3::from(color.map(srgb_to_linear))
+    }
+
+    /// Toggles the shadows of the light. The shadow map is only allocated while they are enabled.
+    pub fn toggle_shadows(&mut self) {
+        if self.shadow_map.take().is_some() {
+            info!(target: LOG_TARGET, "Disabled shadows");
+            return;
+        }
+
+        match Framebuffer::depth_only(SHADOW_MAP_SIZE, SHADOW_MAP_SIZE) {
+            Ok(shadow_map) => {
+                self.shadow_map = Some(shadow_map);
+                info!(target: LOG_TARGET, "Enabled shadows (bias {})", self.shadow_bias);
+            }
+            Err(e) => warn!(target: LOG_TARGET, "Could not create the shadow map: {}", e),
+        }
+    }
+
+    pub fn toggle_headlamp(&mut self) {
+        self.headlamp = !self.headlamp;
+        info!(target: LOG_TARGET, "Light {}", if self.headlamp { "follows the camera" } else { "is fixed" });
+    }
+
+    /// Position of the light for the current frame
+    fn light_pos(&self) -> Vector3<f32> {
+        if self.headlamp {
+            self.camera.position()
+        } else if let Some(sun_cycle) = self.sun_cycle.as_ref() {
+            sun_cycle.position()
+        } else {
+            self.fixed_light_pos
+        }
+    }
+
+    /// Toggles between probing the clicked surface and placing the light above it
+    pub fn toggle_place_light(&mut self) {
+        self.place_light = !self.place_light;
+        info!(target: LOG_TARGET, "Clicking the surface {}", if self.place_light { "places the light above it" } else { "probes it" });
+    }
+
+    /// Starts the sun at sunrise or stops it, which restores the fixed light
+    pub fn toggle_sun_cycle(&mut self) {
+        if self.sun_cycle.take().is_some() {
+            info!(target: LOG_TARGET, "Stopped the sun cycle");
+            return;
+        }
+
+        self.sun_cycle = Some(SunCycle::new(self.sun_cycle_time));
+        info!(target: LOG_TARGET, "Started the sun cycle with {:?} per day", self.sun_cycle_time);
+        if self.headlamp {
+            info!(target: LOG_TARGET, "The sun is only visible with the headlamp disabled");
+        }
+    }
+
+    /// Moves the sun by the given fraction of a day
+    pub fn scrub_sun_cycle(&mut self, amount: f32) {
+        match self.sun_cycle.as_mut() {
+            Some(sun_cycle) => {
+                sun_cycle.scrub(amount);
+                let hours = sun_cycle.time_of_day() * 24.0;
+                // The cycle starts at sunrise, which is shown as 06:00
+                info!(target: LOG_TARGET, "Sun at {:02}:{:02}", (hours as u32 + 6) % 24, (hours.fract() * 60.0) as u32);
+            }
+            None => debug!(target: LOG_TARGET, "The sun cycle is not running"),
+        }
+    }
+
+    /// Advances the animated sun by the time of the last frame
+    fn update_sun_cycle(&mut self, frame_delta: Duration) {
+        if let Some(sun_cycle) = self.sun_cycle.as_mut() {
+            sun_cycle.advance(frame_delta);
+        }
+    }
+
+    pub fn cycle_fog_mode(&mut self) {
+        self.fog_mode_idx = (self.fog_mode_idx + 1) % FOG_MODES.len();
+        info!(target: LOG_TARGET, "Using fog mode {}", FOG_MODES[self.fog_mode_idx]);
+    }
+
+    pub fn change_fog_density(&mut self, factor: f32) {
+        self.fog_density = (self.fog_density * factor).clamp(MIN_FOG_DENSITY, MAX_FOG_DENSITY);
+        info!(target: LOG_TARGET, "Using fog density {}", self.fog_density);
+    }
+
+    /// Color of the fog in linear values. Without a configured color, the fog blends into the background.
+    fn fog_color(&self) -> Vector3<f32> {
+        let color = self.fog_color.unwrap_or(CLEAR_COLORS[self.clear_color_idx].1);
+        Vector3::from(color.map(srgb_to_linear))
+    }
+
+    pub fn toggle_backface_culling(&mut self) {
+        self.backface_culling = !self.backface_culling;
+        self.apply_backface_culling();
+        info!(target: LOG_TARGET, "Backface culling {}", if self.backface_culling { "enabled" } else { "disabled" });
+    }
+
+    /// Enables or disables the backface culling as selected, e.g. after drawing something that needs both sides
+    fn apply_backface_culling(&self) {
+        unsafe {
+            if self.backface_culling {
+                gl::Enable(gl::CULL_FACE);
+            } else {
+                gl::Disable(gl::CULL_FACE);
+            }
+        }
+    }
+
+    pub fn toggle_double_sided(&mut self) {
+        self.double_sided = !self.double_sided;
+        info!(target: LOG_TARGET, "Double-sided lighting {}", if self.double_sided { "enabled" } else { "disabled" });
+        if self.backface_culling && self.double_sided {
+            info!(target: LOG_TARGET, "Back faces are only visible with backface culling disabled");
+        }
+    }
+
+    pub fn toggle_depth_test(&mut self) {
+        self.depth_test = !self.depth_test;
+        unsafe {
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            } else {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+        }
+        info!(target: LOG_TARGET, "Depth test {}", if self.depth_test { "enabled" } else { "disabled" });
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        debug!(target: LOG_TARGET, "Window resized to {}x{}", width, height);
+        self.window_size = (width.max(1), height.max(1));
+        self.update_viewport();
+    }
+
+    pub fn toggle_letterbox(&mut self) {
+        self.letterbox = !self.letterbox;
+        info!(target: LOG_TARGET, "Letterboxing {}", if self.letterbox { "enabled" } else { "disabled" });
+        self.update_viewport();
+    }
+
+    pub fn cycle_letterbox_aspect(&mut self) {
+        self.letterbox_aspect_idx = (self.letterbox_aspect_idx + 1) % LETTERBOX_ASPECTS.len();
+        info!(target: LOG_TARGET, "Using letterbox aspect ratio {}", LETTERBOX_ASPECTS[self.letterbox_aspect_idx].0);
+        self.update_viewport();
+    }
+
+    /// Sets the viewport to the full window or, when letterboxing, to the largest centered area with the target aspect ratio.
+    /// The margins are not drawn to and therefore keep the clear color.
+    fn update_viewport(&mut self) {
+        let (x, y, width, height) = self.viewport();
+        unsafe {
+            gl::Viewport(x as i32, y as i32, width as gl::types::GLsizei, height as gl::types::GLsizei);
+        }
+        self.aspect = width as f32 / height as f32;
+    }
+
+    /// Calculates the area of the window that is rendered to as `(x, y, width, height)`, respecting the letterboxing
+    fn viewport(&self) -> (u32, u32, u32, u32) {
+        let (window_width, window_height) = (self.window_size.0 as f32, self.window_size.1 as f32);
+        let (width, height) = if self.letterbox {
+            let target_aspect = LETTERBOX_ASPECTS[self.letterbox_aspect_idx].1;
+            if window_width / window_height > target_aspect {
+                (window_height * target_aspect, window_height)
+            } else {
+                (window_width, window_width / target_aspect)
+            }
+        } else {
+            (window_width, window_height)
+        };
+        let x = (window_width - width) / 2.0;
+        let y = (window_height - height) / 2.0;
+        (x as u32, y as u32, (width as u32).max(1), (height as u32).max(1))
+    }
+
+    pub fn toggle_smooth_height(&mut self) {
+        self.smooth_height = !self.smooth_height;
+        info!(target: LOG_TARGET, "Sampling height map {}", if self.smooth_height { "smoothly" } else { "with the nearest texel" });
+    }
+
+    pub fn cycle_tangent_space_vector(&mut self) {
+        self.tangent_space_vector_idx = (self.tangent_space_vector_idx + 1) % TANGENT_SPACE_VECTORS.len();
+        info!(target: LOG_TARGET, "Visualizing the {} of the tangent space", TANGENT_SPACE_VECTORS[self.tangent_space_vector_idx]);
+    }
+
+    pub fn change_tess_level(&mut self, factor: f32) {
+        self.tess_level = (self.tess_level * factor).clamp(1.0, self.max_tess_level);
+        info!(target: LOG_TARGET, "Using tessellation level {}", self.tess_level);
+    }
+
+    /// Increases the detail of the displacement: the parallax layers of the current program in shader detail mode
+    /// (keeping the coarse mesh), otherwise the mesh samples
+    pub fn increase_detail(&mut self) {
+        if self.uses_shader_detail() {
+            self.set_parallax_layers(self.parallax_layers * 2);
+        } else {
+            self.increase_samples();
+        }
+    }
+
+    /// Decreases the detail of the displacement, see [`AppState::increase_detail`]
+    pub fn decrease_detail(&mut self) {
+        if self.uses_shader_detail() {
+            self.set_parallax_layers(self.parallax_layers / 2);
+        } else {
+            self.decrease_samples();
+        }
+    }
+
+    pub fn toggle_shader_detail(&mut self) {
+        self.shader_detail = !self.shader_detail;
+        info!(target: LOG_TARGET, "Detail keys change the {}", if self.shader_detail { "parallax layers of parallax programs" } else { "mesh samples" });
+    }
+
+    /// Check whether the detail keys currently change the parallax layers
+    fn uses_shader_detail(&self) -> bool {
+        self.shader_detail && self.current_program().is_some_and(|program| program.has_property("parallaxLayers"))
+    }
+
+    fn set_parallax_layers(&mut self, layers: i32) {
+        self.parallax_layers = layers.clamp(1, MAX_PARALLAX_LAYERS);
+        info!(target: LOG_TARGET, "Using {} parallax layers", self.parallax_layers);
+    }
+
+    /// Changes the parallax layers used when looking straight at the surface.
+    /// The shader limits them to the amount used for flat angles.
+    pub fn change_min_parallax_layers(&mut self, factor: f32) {
+        self.min_parallax_layers = ((self.min_parallax_layers as f32 * factor) as i32).clamp(1, MAX_PARALLAX_LAYERS);
+        info!(target: LOG_TARGET, "Using at least {} parallax layers (at most {})", self.min_parallax_layers, self.parallax_layers);
+    }
+
+    pub fn toggle_parallax_refinement(&mut self) {
+        self.parallax_refinement = !self.parallax_refinement;
+        info!(target: LOG_TARGET, "Parallax refinement {}", if self.parallax_refinement { "enabled" } else { "disabled" });
     }
 
     pub fn increase_samples(&mut self) {
-        self.samples_idx = (self.samples_idx + 1).clamp(0, SAMPLE_STEPS_X.len() - 1);
-        info!(target: LOG_TARGET, "Using sample amount {}: {}x{}", self.samples_idx, SAMPLE_STEPS_X[self.samples_idx], SAMPLE_STEPS_Y[self.samples_idx]);
-        self.refresh_vbo();
+        self.set_sample_index(self.samples_idx as isize + 1);
     }
 
     pub fn decrease_samples(&mut self) {
-        if self.samples_idx == 0 {
-            debug!("Sample amount could not be decreased");
+        self.set_sample_index(self.samples_idx as isize - 1);
+    }
+
+    /// Switches to the sample amount with the given index, saturating at the first and last available amount.
+    /// Larger amounts whose vertices exceed the budget are refused, smaller amounts are always allowed.
+    pub fn set_sample_index(&mut self, idx: isize) {
+        let idx = clamp_sample_index(idx);
+        if idx == self.samples_idx {
+            debug!(target: LOG_TARGET, "Sample amount {} is already used", idx);
             return;
         }
 
-        self.samples_idx = (self.samples_idx - 1).clamp(0, SAMPLE_STEPS_X.len() - 1);
-        info!(target: LOG_TARGET, "Using sample amount {}: {}x{}", self.samples_idx, SAMPLE_STEPS_X[self.samples_idx], SAMPLE_STEPS_Y[self.samples_idx]);
+        let vertex_count = sample_vertex_count(idx);
+        if idx > self.samples_idx && vertex_count > self.max_vertices {
+            warn!(target: LOG_TARGET, "Not increasing the sample amount to \"{}\": {} vertices (~{}MiB) exceed the budget of {} vertices",
+                sample_preset_name(idx), vertex_count, vertex_memory_mib(vertex_count, &self.vertex_layout), self.max_vertices);
+            return;
+        }
+
+        self.samples_idx = idx;
+        info!(target: LOG_TARGET, "Using sample amount \"{}\" (~{}MiB)", sample_preset_name(idx), vertex_memory_mib(vertex_count, &self.vertex_layout));
         self.refresh_vbo();
     }
 
+    /// Switches directly to the sample amount with the given preset name
+    pub fn set_sample_preset(&mut self, name: &str) {
+        match SAMPLE_PRESET_NAMES.iter().position(|&preset| preset == name) {
+            Some(idx) => self.set_sample_index(idx as isize),
+            None => warn!(target: LOG_TARGET, "Unknown sample preset \"{}\"", name),
+        }
+    }
+
+    pub fn toggle_terrain(&mut self, res: &Resources) {
+        if self.terrain.take().is_some() {
+            info!(target: LOG_TARGET, "Disabled terrain");
+            self.refresh_vbo();
+            return;
+        }
+
+        match self.texture.load_height_map(res) {
+            Ok(height_map) => {
+                info!(target: LOG_TARGET, "Enabled terrain from {}x{} height map of \"{}\"", height_map.width(), height_map.height(), self.texture.name());
+                self.terrain = Some(Arc::new(height_map));
+                self.refresh_vbo();
+                // The terrain is higher than the square, so it may not fit into the view anymore
+                self.frame_generated_mesh = true;
+            }
+            Err(e) => warn!(target: LOG_TARGET, "Could not load height map for the terrain: {}", e),
+        }
+    }
+
+    /// Toggles between texture coordinates spanning exactly `0..1` and inset ones, and regenerates the mesh
+    pub fn toggle_uv_clamp(&mut self) {
+        self.clamp_uvs = !self.clamp_uvs;
+        match self.uv_mapping() {
+            UvMapping::Wrap => info!(target: LOG_TARGET, "Wrapping the texture coordinates (0..1)"),
+            UvMapping::Clamp(inset) => info!(target: LOG_TARGET, "Clamping the texture coordinates with an inset of {} texels ({:?})", self.uv_inset, inset),
+        }
+        self.refresh_vbo();
+    }
+
+    /// Mapping of the texture coordinates of the square.
+    /// The inset is given in texels, so it is converted with the size of the base texture and the tiling of the material:
+    /// with tiling, the texture is repeated across the square and a texel covers a smaller part of the texture coordinates.
+    fn uv_mapping(&self) -> UvMapping {
+        if !self.clamp_uvs {
+            return UvMapping::Wrap;
+        }
+        let (width, height) = self.texture.base_size();
+        let tiling = self.texture.tiling().max(f32::EPSILON);
+        let inset = cgmath::vec2(self.uv_inset / (width.max(1) as f32 * tiling), self.uv_inset / (height.max(1) as f32 * tiling));
+        // The inset can not exceed half of the texture coordinates, otherwise the square would be mirrored
+        UvMapping::Clamp(inset.map(|value| value.min(0.5)))
+    }
+
+    /// Switches to the next level of the terrain decimation and regenerates the terrain
+    pub fn cycle_decimation(&mut self) {
+        self.decimation_idx = (self.decimation_idx + 1) % DECIMATION_LEVELS.len();
+        let (name, tolerance) = DECIMATION_LEVELS[self.decimation_idx];
+        info!(target: LOG_TARGET, "Using terrain decimation {} (tolerance {})", name, tolerance);
+        if self.terrain.is_none() {
+            info!(target: LOG_TARGET, "The decimation only applies to the terrain");
+            return;
+        }
+        self.refresh_vbo();
+    }
+
+    /// Switches to the next displacement axis (normal, world up, custom) and regenerates the terrain
+    pub fn cycle_displacement_axis(&mut self) {
+        self.displacement_axis = match self.displacement_axis {
+            DisplacementAxis::Normal => DisplacementAxis::WorldUp,
+            DisplacementAxis::WorldUp => DisplacementAxis::Custom(self.custom_displacement_axis),
+            DisplacementAxis::Custom(_) => DisplacementAxis::Normal,
+        };
+        info!(target: LOG_TARGET, "Displacing the terrain along {:?}", self.displacement_axis);
+        if self.terrain.is_some() {
+            self.refresh_vbo();
+        }
+    }
+
+    /// Moves the camera back until the whole mesh is visible
+    pub fn frame_mesh(&mut self) {
+        match self.mesh_bounds() {
+            Some((min, max)) => self.camera.frame_bounds(min - self.scene_offset, max - self.scene_offset, self.aspect),
+            None => debug!(target: LOG_TARGET, "No mesh to frame"),
+        }
+    }
+
+    /// Moves the camera above the center of the mesh, looking straight down onto it
+    pub fn top_down_view(&mut self) {
+        // Without a mesh the camera is centered over the origin of the mesh
+        let center = self.mesh_bounds().map_or(Vector3::zero(), |(min, max)| (min + max) / 2.0);
+        self.camera.set_top_down(center - self.scene_offset, TOP_DOWN_HEIGHT);
+    }
+
+    /// Computes the axis-aligned bounding box of all instances of the mesh (before the scene offset is applied)
+    fn mesh_bounds(&self) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        let (min, max) = mesh::bounding_box(&self.vertices, &self.vertex_layout)?;
+        let corners = (0..8).map(|idx| cgmath::vec3(
+            if idx & 1 == 0 { min.x } else { max.x },
+            if idx & 2 == 0 { min.y } else { max.y },
+            if idx & 4 == 0 { min.z } else { max.z },
+        ));
+        self.instance_transforms.iter()
+            .flat_map(|transform| corners.clone().map(move |corner| (transform * corner.extend(1.0)).truncate()))
+            .fold(None, |bounds, p| match bounds {
+                None => Some((p, p)),
+                Some((min, max)) => Some((
+                    cgmath::vec3(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                    cgmath::vec3(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+                )),
+            })
+    }
+
+    /// Model matrix of the scene, which moves the scene offset to the origin
+    fn model_matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(-self.scene_offset)
+    }
+
+    /// Moves the point of the scene that is placed at the origin by the given amount
+    pub fn move_pivot(&mut self, amount: Vector3<f32>) {
+        self.scene_offset += amount;
+        info!(target: LOG_TARGET, "Scene pivot at {:?}", self.scene_offset);
+    }
+
+    /// Moves the center of the mesh bounding box to the origin
+    pub fn center_pivot(&mut self) {
+        match self.mesh_bounds() {
+            Some((min, max)) => self.move_pivot((min + max) / 2.0 - self.scene_offset),
+            None => debug!(target: LOG_TARGET, "No mesh to center the pivot on"),
+        }
+    }
+
+    /// Toggles between flat (per-face) and smooth (per-vertex) normals and regenerates the mesh.
+    /// On the flat square all normals are equal, so the difference is only visible with the terrain enabled.
+    pub fn toggle_flat_normals(&mut self) {
+        self.flat_normals = !self.flat_normals;
+        info!(target: LOG_TARGET, "Using {} normals", if self.flat_normals { "flat" } else { "smooth" });
+        self.refresh_vbo();
+    }
+
+    /// Toggles flat shading, which uses the normal of the first vertex of every triangle without interpolating it.
+    /// Unlike flat normals, the mesh is not regenerated; the shader reads a `flat` output of the provoking vertex instead.
+    /// The mesh is drawn without indices, so every triangle has its own provoking vertex.
+    /// With shared (indexed) vertices, one vertex would determine the normal of several triangles.
+    pub fn toggle_flat_shading(&mut self) {
+        self.flat_shading = !self.flat_shading;
+        unsafe {
+            gl::ProvokingVertex(if self.flat_shading { gl::FIRST_VERTEX_CONVENTION } else { gl::LAST_VERTEX_CONVENTION });
+        }
+        info!(target: LOG_TARGET, "Using {} shading", if self.flat_shading { "flat" } else { "smooth" });
+        if self.current_program().is_some_and(|program| !program.has_property("flatShading")) {
+            info!(target: LOG_TARGET, "The current program does not support flat shading");
+        }
+    }
+
+    /// Toggles the generation of per-vertex colors, which are shown by the vertex color program
+    pub fn toggle_vertex_colors(&mut self) {
+        self.vertex_colors = !self.vertex_colors;
+        info!(target: LOG_TARGET, "Vertex colors {}", if self.vertex_colors { "enabled" } else { "disabled" });
+        self.refresh_vbo();
+    }
+
+    pub fn add_instance(&mut self) {
+        if self.instance_transforms.len() >= MAX_INSTANCES {
+            debug!("Instance count could not be increased");
+            return;
+        }
+        self.refresh_instances(self.instance_transforms.len() + 1);
+    }
+
+    pub fn remove_instance(&mut self) {
+        if self.instance_transforms.len() <= 1 {
+            debug!("Instance count could not be decreased");
+            return;
+        }
+        self.refresh_instances(self.instance_transforms.len() - 1);
+    }
+
+    /// Lays out the given amount of instances in a square grid and uploads their model matrices
+    fn refresh_instances(&mut self, count: usize) {
+        let columns = (count as f32).sqrt().ceil() as usize;
+
+        self.instance_transforms = (0..count)
+            .map(|i| Matrix4::from_translation(cgmath::vec3(
+                (i % columns) as f32 * INSTANCE_SPACING,
+                (i / columns) as f32 * INSTANCE_SPACING,
+                0.0,
+            )))
+            .collect();
+        info!(target: LOG_TARGET, "Rendering {} instance(s)", count);
+
+        let data: Vec<f32> = self.instance_transforms.iter()
+            .flat_map(|m| AsRef::<[f32; 16]>::as_ref(m).to_vec())
+            .collect();
+        fill_vbo(self.instance_vbo_id, &data);
+    }
+
+    /// Captures the current settings and camera pose in a configuration, which restores this state when used at startup.
+    /// Settings that can not be changed while running are taken from the configuration loaded at startup.
+    fn current_config(&self) -> Config {
+        Config {
+            invert_y: self.camera.invert_y(),
+            fog_density: self.fog_density,
+            sample_preset: SAMPLE_PRESET_NAMES[self.samples_idx].to_string(),
+            program: self.available_program_resources.get(self.used_program_idx)
+                .and_then(|resource| resource.strip_prefix(&format!("{}/", SHADER_DIR)))
+                .map(str::to_string),
+            shininess: self.shininess,
+            ambient_strength: self.ambient_strength,
+            normal_strength: self.normal_strength,
+            tess_level: self.tess_level,
+            clear_color: CLEAR_COLORS[self.clear_color_idx].0.to_string(),
+            light_color: LIGHT_COLORS[self.light_color_idx].0.to_string(),
+            fog_mode: FOG_MODES[self.fog_mode_idx].to_string(),
+            shadows: self.shadow_map.is_some(),
+            headlamp: self.headlamp,
+            hdr: self.hdr_enabled,
+            exposure: self.exposure,
+            render_scale: self.render_scale,
+            clamp_uvs: self.clamp_uvs,
+            uv_inset: self.uv_inset,
+            displacement_axis: self.displacement_axis.name().to_string(),
+            decimation: DECIMATION_LEVELS[self.decimation_idx].0.to_string(),
+            light_position: self.fixed_light_pos.into(),
+            initial_pose: Some(PoseConfig::from(&self.camera.pose())),
+            ..self.startup_config.clone()
+        }
+    }
+
+    /// Disables showing the controls at startup in the configuration file after they were shown on the first launch.
+    /// The rest of the file is written as it was loaded.
+    pub fn store_controls_shown(&mut self, res: &Resources) {
+        self.startup_config.show_controls = false;
+        let result = self.startup_config.to_toml()
+            .and_then(|toml| res.write_bytes(CONFIG_NAME, toml.as_bytes()).map_err(|e| format!("{:?}", e)));
+        match result {
+            Ok(_) => info!(target: LOG_TARGET, "The controls will not be shown at the next start, press {} to show them", self.keymap.keys(Action::ShowHelp).join("/")),
+            Err(e) => warn!(target: LOG_TARGET, "Storing that the controls were shown failed: {}", e),
+        }
+    }
+
+    pub fn export_config(&self, res: &Resources) {
+        let result = self.current_config().to_toml()
+            .and_then(|toml| res.write_bytes(CONFIG_EXPORT_NAME, toml.as_bytes()).map_err(|e| format!("{:?}", e)));
+        match result {
+            Ok(_) => info!(target: LOG_TARGET, "Exported the current settings to \"{}\"", CONFIG_EXPORT_NAME),
+            Err(e) => warn!(target: LOG_TARGET, "Exporting the settings failed: {}", e),
+        }
+    }
+
+    /// Saves the maps of the displayed material as they were uploaded to the GPU, to tell upload errors from shader errors
+    pub fn export_texture_readback(&self, res: &Resources) {
+        for (texture_type, texture) in self.texture.textures() {
+            let name = format!("{}/readback_{}.png", SCREENSHOT_DIR, texture_type);
+            let result = res.construct_path(&name)
+                .map_err(|e| format!("Could not determine the path: {:?}", e))
+                .and_then(|path| {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                    }
+                    texture.read_back().save(&path).map_err(|e| e.to_string())?;
+                    Ok(path)
+                });
+
+            match result {
+                Ok(path) => info!(target: LOG_TARGET, "Saved the uploaded {} map ({}x{}) to {:?}", texture_type, texture.width(), texture.height(), path),
+                Err(e) => warn!(target: LOG_TARGET, "Saving the uploaded {} map failed: {}", texture_type, e),
+            }
+        }
+    }
+
+    pub fn export_obj(&self, res: &Resources) {
+        let path = match res.construct_path(OBJ_EXPORT_NAME) {
+            Ok(path) => path,
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not determine export path: {:?}", e);
+                return;
+            }
+        };
+
+        match mesh::write_obj(&path, &self.vertices, &self.vertex_layout) {
+            Ok(_) => info!(target: LOG_TARGET, "Exported {} vertices to {:?}", self.point_count, path),
+            Err(e) => warn!(target: LOG_TARGET, "Exporting the mesh to {:?} failed: {}", path, e),
+        }
+    }
+
+    /// Starts generating the mesh with the current settings on a worker thread.
+    /// The previous mesh is drawn until the new one is uploaded by [`AppState::receive_mesh`].
+    /// A generation that is still in progress is superseded; its result is discarded.
     fn refresh_vbo(&mut self) {
-        let mut vertices = Vec::new();
-        self.point_count = generate_vertices(self.samples_idx, &mut vertices);
-        fill_vbo(self.vbo_id, &vertices);
+        let samples_idx = self.samples_idx;
+        let terrain = self.terrain.clone();
+        let displacement_axis = self.displacement_axis.direction();
+        let (decimation_name, decimation_tolerance) = DECIMATION_LEVELS[self.decimation_idx];
+        let uv_mapping = self.uv_mapping();
+        let layout = if self.vertex_colors {
+            self.vertex_layout.with_attribute(VertexAttribute::Color)
+        } else {
+            self.vertex_layout.without_attribute(VertexAttribute::Color)
+        };
+        let flat_normals = self.flat_normals;
+        // Starting a new generation cancels the one in progress, so only one worker keeps running
+        let generation = self.latest_mesh_generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let latest_generation = Arc::clone(&self.latest_mesh_generation);
+
+        let (sender, receiver) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name("mesh generation".to_string())
+            .spawn(move || {
+                let start = Instant::now();
+                let cancelled = || latest_generation.load(Ordering::Relaxed) != generation;
+                let (mut vertices, point_count) = match generate_vertices(samples_idx, terrain.as_deref(), displacement_axis, decimation_tolerance, uv_mapping, &layout, cancelled) {
+                    Some(generated) => generated,
+                    None => {
+                        debug!(target: LOG_TARGET, "Cancelled the superseded mesh generation after {:?}", start.elapsed());
+                        return;
+                    }
+                };
+                if terrain.is_some() && decimation_tolerance > 0.0 {
+                    let full_count = sample_vertex_count(samples_idx);
+                    info!(target: LOG_TARGET, "Decimated terrain ({}) to {} triangles, {:.1}% of {}",
+                        decimation_name, point_count / 3, 100.0 * point_count as f64 / full_count as f64, full_count / 3);
+                }
+                if flat_normals {
+                    mesh::flatten_normals(&mut vertices, &layout);
+                }
+                mesh::color_cells(&mut vertices, &layout, SAMPLE_STEPS_X[samples_idx] as u32, SAMPLE_STEPS_Y[samples_idx] as u32);
+                debug!(target: LOG_TARGET, "Generated {} vertices in {:?}", point_count, start.elapsed());
+
+                // Sending fails if the generation was superseded in the meantime, which drops the data
+                let _ = sender.send(GeneratedMesh { vertices, layout, point_count });
+            });
+
+        match worker {
+            Ok(_) => self.mesh_generation = Some(receiver),
+            Err(e) => warn!(target: LOG_TARGET, "Could not start the mesh generation, keeping the previous mesh: {}", e),
+        }
+    }
+
+    /// Uploads the mesh generated on the worker thread to the VBOs, if it is finished.
+    /// With `wait`, blocks until the generation in progress is finished.
+    pub fn receive_mesh(&mut self, wait: bool) {
+        let receiver = match self.mesh_generation.as_ref() {
+            Some(receiver) => receiver,
+            None => return,
+        };
+        let generated = if wait {
+            receiver.recv().ok()
+        } else {
+            match receiver.try_recv() {
+                Ok(generated) => Some(generated),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => None,
+            }
+        };
+        self.mesh_generation = None;
+
+        let generated = match generated {
+            Some(generated) => generated,
+            None => {
+                warn!(target: LOG_TARGET, "The mesh generation failed, keeping the previous mesh");
+                return;
+            }
+        };
+        self.vertices = generated.vertices;
+        self.point_count = generated.point_count;
+        fill_vbo(self.vbo_id, &self.vertices);
+        if generated.layout != self.vertex_layout {
+            // The attributes changed (e.g. the vertex colors were toggled), so the VAO is configured for the new layout
+            unsafe {
+                gl::DeleteVertexArrays(1, &self.vao_id);
+            }
+            self.vao_id = configure_vao(self.vbo_id, &generated.layout);
+            configure_instance_attributes(self.vao_id, self.instance_vbo_id);
+            self.vertex_layout = generated.layout;
+        }
+
+        if std::mem::take(&mut self.frame_generated_mesh) {
+            self.frame_mesh();
+        }
+    }
+
+    /// Check whether a mesh is generated in the background
+    pub fn generating_mesh(&self) -> bool {
+        self.mesh_generation.is_some()
+    }
+}
+
+/// # GeneratedMesh
+/// Vertex data generated on the worker thread, which is uploaded on the main thread (owning the OpenGL context)
+struct GeneratedMesh {
+    vertices: Vec<f32>,
+    /// Attributes contained in the vertex data, which differ from the current layout if the vertex colors were toggled
+    layout: VertexLayout,
+    point_count: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_index_saturates_at_the_boundaries() {
+        let last = SAMPLE_STEPS_X.len() - 1;
+        assert_eq!(clamp_sample_index(isize::MIN), 0);
+        assert_eq!(clamp_sample_index(-1), 0);
+        assert_eq!(clamp_sample_index(0), 0);
+        assert_eq!(clamp_sample_index(1), 1);
+        assert_eq!(clamp_sample_index(last as isize - 1), last - 1);
+        assert_eq!(clamp_sample_index(last as isize), last);
+        assert_eq!(clamp_sample_index(last as isize + 1), last);
+        assert_eq!(clamp_sample_index(isize::MAX), last);
+    }
+
+    #[test]
+    fn light_matrices_are_finite_for_every_light_position() {
+        for light_pos in [cgmath::vec3(0.0, 0.0, 0.0), cgmath::vec3(0.0, 3.0, 0.0), cgmath::vec3(0.0, -1e-3, 0.0),
+                          cgmath::vec3(0.0, 1.0, 1e-4), cgmath::vec3(2.0, 1.0, 3.0)] {
+            let (view, proj) = calc_light_matrices(&light_pos);
+            assert!((proj * view).is_finite(), "light matrices for {:?} are not finite", light_pos);
+        }
+    }
+
+    #[test]
+    fn sample_index_steps_through_all_amounts() {
+        // Increasing and decreasing from the boundaries like increase_samples and decrease_samples
+        let mut idx = 0;
+        let mut visited = vec![idx];
+        loop {
+            let next = clamp_sample_index(idx as isize + 1);
+            if next == idx {
+                break;
+            }
+            idx = next;
+            visited.push(idx);
+        }
+        assert_eq!(visited, (0..SAMPLE_STEPS_X.len()).collect::<Vec<_>>());
+        assert_eq!(clamp_sample_index(idx as isize - 1), SAMPLE_STEPS_X.len() - 2);
+        assert_eq!(SAMPLE_PRESET_NAMES.len(), SAMPLE_STEPS_X.len());
+    }
+
+    #[test]
+    fn cancelled_generation_stops_early() {
+        let layout = VertexLayout::default();
+        let generate = |cancelled: &dyn Fn() -> bool| {
+            generate_vertices(2, None, cgmath::vec3(0.0, 0.0, 1.0), 0.0, UvMapping::Wrap, &layout, cancelled)
+        };
+
+        let (vertices, point_count) = generate(&|| false).unwrap();
+        assert_eq!(point_count as u64, sample_vertex_count(2));
+        assert_eq!(vertices.len(), point_count as usize * layout.floats_per_vertex());
+
+        assert!(generate(&|| true).is_none());
+
+        // Cancelling during the generation stops at the next row
+        let checks = std::cell::Cell::new(0);
+        let cancel_after_first_row = || {
+            checks.set(checks.get() + 1);
+            checks.get() > 1
+        };
+        assert!(generate(&cancel_after_first_row).is_none());
+        assert_eq!(checks.get(), 2);
     }
 }
@@ -0,0 +1,101 @@
+use log::{debug, info};
+use serde::Deserialize;
+
+use crate::Resources;
+
+const LOG_TARGET: &str = "Material";
+
+/// Name of the material definition inside a resource folder
+pub const MATERIAL_FILE_NAME: &str = "material.toml";
+
+/// # MaterialDef
+/// Explicit description of the texture maps of a material, loaded from a `material.toml` file.
+/// The file names of the maps are relative to the folder containing the definition, e.g.:
+/// ```toml
+/// tiling = 2.0
+/// directx_normals = true
+///
+/// [base]
+/// file = "wall_base.jpg"
+///
+/// [normal]
+/// file = "wall_normal.jpg"
+///
+/// [height]
+/// file = "wall_height.jpg"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct MaterialDef {
+    /// Resource folder the definition was loaded from
+    #[serde(skip)]
+    folder: String,
+
+    /// Basic texture (color/image)
+    pub base: MapDef,
+    /// Normal map
+    pub normal: MapDef,
+    /// Height map
+    pub height: MapDef,
+    /// Detail map tiled on top of the basic texture
+    pub detail: Option<MapDef>,
+    /// Light emitted by the surface
+    pub emissive: Option<MapDef>,
+    /// Grayscale map selecting between this and a secondary material (black = this material, white = secondary)
+    pub matid: Option<MapDef>,
+
+    /// Amount of times the textures are repeated across the mesh
+    #[serde(default = "default_tiling")]
+    pub tiling: f32,
+    /// Whether the normal map uses the DirectX convention (green channel pointing down)
+    #[serde(default)]
+    pub directx_normals: bool,
+}
+
+/// # MapDef
+/// A single texture map of a [`MaterialDef`]
+#[derive(Debug, Deserialize)]
+pub struct MapDef {
+    /// File name of the map relative to the material folder
+    pub file: String,
+    /// Whether the map contains sRGB-encoded colors. Defaults to `true` for color maps and `false` for data maps.
+    pub srgb: Option<bool>,
+    /// Channels the red, green, blue and alpha values are read from when sampling (`r`, `g`, `b`, `a`, `0` or `1`),
+    /// e.g. `["a", "a", "a", "1"]` for a height map stored in the alpha channel
+    pub swizzle: Option<[char; 4]>,
+}
+
+impl MaterialDef {
+    /// # Constructor
+    /// Loads the material definition from the given resource folder.
+    /// Returns `None` if the folder does not contain a definition.
+    pub fn from_resources(res: &Resources, folder: &str) -> Result<Option<MaterialDef>, String> {
+        let name = format!("{}/{}", folder, MATERIAL_FILE_NAME);
+        let data = match res.load_bytes(&name) {
+            Ok(data) => data,
+            Err(e) => {
+                debug!(target: LOG_TARGET, "No material definition loaded from \"{}\": {:?}", name, e);
+                return Ok(None);
+            }
+        };
+
+        let mut material: MaterialDef = toml::from_slice(&data)
+            .map_err(|e| format!("Invalid material definition in {}: {}", name, e))?;
+        material.folder = folder.to_string();
+        info!(target: LOG_TARGET, "Loaded material definition from \"{}\": {:?}", name, material);
+        Ok(Some(material))
+    }
+
+    /// Get the resource folder the definition was loaded from
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    /// Get the resource name of the given map
+    pub fn map_path(&self, map: &MapDef) -> String {
+        format!("{}/{}", self.folder, map.file)
+    }
+}
+
+fn default_tiling() -> f32 {
+    1.0
+}
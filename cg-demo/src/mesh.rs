@@ -0,0 +1,469 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use cgmath::{Array, InnerSpace, Vector2, Vector3, Zero};
+use serde::{Deserialize, Serialize};
+
+/// # VertexAttribute
+/// A single attribute of the vertices in the VBO.
+/// Every attribute has a fixed location in the shaders, independent of the layout it is used in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VertexAttribute {
+    /// `layout (location = 0) in vec3 inPos;`
+    Position,
+    /// `layout (location = 1) in vec3 inNormal;`
+    Normal,
+    /// `layout (location = 2) in vec2 inTexCoords;`
+    TexCoords,
+    /// `layout (location = 3) in vec4 inTangent;` with the handedness (see [`tangent_handedness`]) in w
+    Tangent,
+    /// `layout (location = 4) in vec3 inBitangent;`
+    Bitangent,
+    /// `layout (location = 9) in vec3 inColor;` (the locations 5 to 8 are occupied by the per-instance model matrix)
+    Color,
+}
+
+impl VertexAttribute {
+    /// All attributes in the order of their locations
+    pub const ALL: [VertexAttribute; 6] = [
+        VertexAttribute::Position,
+        VertexAttribute::Normal,
+        VertexAttribute::TexCoords,
+        VertexAttribute::Tangent,
+        VertexAttribute::Bitangent,
+        VertexAttribute::Color,
+    ];
+
+    /// Attributes of the default layout; the color is only added while vertex colors are shown
+    pub const DEFAULT: [VertexAttribute; 5] = [
+        VertexAttribute::Position,
+        VertexAttribute::Normal,
+        VertexAttribute::TexCoords,
+        VertexAttribute::Tangent,
+        VertexAttribute::Bitangent,
+    ];
+
+    /// Amount of `f32`-values of the attribute
+    pub fn size(&self) -> usize {
+        match self {
+            VertexAttribute::TexCoords => 2,
+            VertexAttribute::Tangent => 4,
+            _ => 3,
+        }
+    }
+
+    /// Location of the attribute in the shaders
+    pub fn location(&self) -> u32 {
+        match self {
+            VertexAttribute::Position => 0,
+            VertexAttribute::Normal => 1,
+            VertexAttribute::TexCoords => 2,
+            VertexAttribute::Tangent => 3,
+            VertexAttribute::Bitangent => 4,
+            VertexAttribute::Color => 9,
+        }
+    }
+}
+
+/// # VertexLayout
+/// Describes which attributes every vertex in the VBO contains and in which order they are packed.
+/// The default layout contains all attributes except the color (15 `f32`-values per vertex);
+/// shaders that do not need all of them can use a more compact layout, e.g. only the position and the texture coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexLayout {
+    attributes: Vec<VertexAttribute>,
+}
+
+impl VertexLayout {
+    /// # Constructor
+    /// Creates a layout with the given attributes in the given order.
+    /// The position is required and every attribute may only be contained once.
+    pub fn new(attributes: Vec<VertexAttribute>) -> Result<VertexLayout, String> {
+        if !attributes.contains(&VertexAttribute::Position) {
+            return Err(format!("Vertex layout {:?} does not contain the position", attributes));
+        }
+        for (idx, attribute) in attributes.iter().enumerate() {
+            if attributes[..idx].contains(attribute) {
+                return Err(format!("Vertex layout {:?} contains {:?} more than once", attributes, attribute));
+            }
+        }
+        Ok(VertexLayout { attributes })
+    }
+
+    /// Get the attributes in the order they are packed
+    pub fn attributes(&self) -> &[VertexAttribute] {
+        &self.attributes
+    }
+
+    /// Checks whether the vertices contain the given attribute
+    pub fn contains(&self, attribute: VertexAttribute) -> bool {
+        self.attributes.contains(&attribute)
+    }
+
+    /// Creates a copy of the layout with the given attribute appended, unless it is already contained
+    pub fn with_attribute(&self, attribute: VertexAttribute) -> VertexLayout {
+        let mut layout = self.clone();
+        if !layout.contains(attribute) {
+            layout.attributes.push(attribute);
+        }
+        layout
+    }
+
+    /// Creates a copy of the layout without the given attribute. The position can not be removed.
+    pub fn without_attribute(&self, attribute: VertexAttribute) -> VertexLayout {
+        let mut layout = self.clone();
+        if attribute != VertexAttribute::Position {
+            layout.attributes.retain(|&a| a != attribute);
+        }
+        layout
+    }
+
+    /// Amount of `f32`-values per vertex
+    pub fn floats_per_vertex(&self) -> usize {
+        self.attributes.iter().map(VertexAttribute::size).sum()
+    }
+
+    /// Offset of the given attribute within a vertex in `f32`-values, or `None` if the layout does not contain it
+    pub fn offset(&self, attribute: VertexAttribute) -> Option<usize> {
+        let idx = self.attributes.iter().position(|&a| a == attribute)?;
+        Some(self.attributes[..idx].iter().map(VertexAttribute::size).sum())
+    }
+
+    /// Appends a single vertex with the attributes of this layout to the buffer. Attributes not contained are skipped.
+    /// The handedness of the tangent space is stored in the w-component of the tangent (see [`tangent_handedness`]).
+    /// The color is initialized to white, it is derived from the finished mesh (see [`color_cells`]).
+    pub fn push_vertex(&self, buffer: &mut Vec<f32>, position: Vector3<f32>, normal: Vector3<f32>, tex_coords: Vector2<f32>,
+                       tangent: Vector3<f32>, bitangent: Vector3<f32>) {
+        for attribute in &self.attributes {
+            match attribute {
+                VertexAttribute::Position => buffer.extend_from_slice(&[position.x, position.y, position.z]),
+                VertexAttribute::Normal => buffer.extend_from_slice(&[normal.x, normal.y, normal.z]),
+                VertexAttribute::TexCoords => buffer.extend_from_slice(&[tex_coords.x, tex_coords.y]),
+                VertexAttribute::Tangent => {
+                    let handedness = tangent_handedness(normal, tangent, bitangent);
+                    buffer.extend_from_slice(&[tangent.x, tangent.y, tangent.z, handedness])
+                }
+                VertexAttribute::Bitangent => buffer.extend_from_slice(&[bitangent.x, bitangent.y, bitangent.z]),
+                VertexAttribute::Color => buffer.extend_from_slice(&[1.0, 1.0, 1.0]),
+            }
+        }
+    }
+
+    /// Offset of the position within a vertex
+    fn position_offset(&self) -> usize {
+        self.offset(VertexAttribute::Position).expect("the position is required by every layout")
+    }
+}
+
+impl Default for VertexLayout {
+    fn default() -> Self {
+        VertexLayout { attributes: VertexAttribute::DEFAULT.to_vec() }
+    }
+}
+
+/// UV-triangles with `|det| <= epsilon * |delta_uv1| * |delta_uv2|` (i.e. nearly collinear edges) are treated as degenerate
+const DEGENERATE_UV_EPSILON: f32 = 1e-6;
+
+/// Computes the tangent and bitangent of a triangle from its positions and texture coordinates.
+/// The tangent points in the direction of increasing u, the bitangent in the direction of increasing v.
+///
+/// If the texture coordinates are degenerate (collinear), no tangent space can be derived from them.
+/// In that case a tangent space is derived from the geometry instead (see [`fallback_tangent_bitangent`]),
+/// so no `inf`/`NaN` values are produced.
+pub fn compute_tangent_bitangent(p: [Vector3<f32>; 3], uv: [Vector2<f32>; 3]) -> (Vector3<f32>, Vector3<f32>) {
+    let edge1 = p[1] - p[0];
+    let edge2 = p[2] - p[0];
+    let delta_uv1 = uv[1] - uv[0];
+    let delta_uv2 = uv[2] - uv[0];
+
+    let det = delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y;
+    if det.abs() <= DEGENERATE_UV_EPSILON * delta_uv1.magnitude() * delta_uv2.magnitude() {
+        return fallback_tangent_bitangent(edge1, edge2);
+    }
+    let f = 1.0 / det;
+
+    let tangent = (edge1 * delta_uv2.y - edge2 * delta_uv1.y) * f;
+    let bitangent = (edge2 * delta_uv1.x - edge1 * delta_uv2.x) * f;
+
+    if !tangent.is_finite() || !bitangent.is_finite() {
+        return fallback_tangent_bitangent(edge1, edge2);
+    }
+    (tangent, bitangent)
+}
+
+/// Sign of the tangent space: `1.0` if the bitangent points to the side of `normal x tangent`, `-1.0` if the texture
+/// coordinates are mirrored. The shaders rebuild the bitangent from the normal and tangent and flip it by this sign.
+pub fn tangent_handedness(normal: Vector3<f32>, tangent: Vector3<f32>, bitangent: Vector3<f32>) -> f32 {
+    if normal.cross(tangent).dot(bitangent) < 0.0 { -1.0 } else { 1.0 }
+}
+
+/// Derives an orthonormal tangent and bitangent from the edges of a triangle.
+/// The tangent follows the first (non-zero) edge and the bitangent is perpendicular to it within the triangle plane,
+/// so `tangent x bitangent` equals the face normal.
+/// Triangles without area (relative to the length of their edges, so small triangles of fine meshes are not affected)
+/// fall back to the unit x- and y-axis.
+pub fn fallback_tangent_bitangent(edge1: Vector3<f32>, edge2: Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    let normal = edge1.cross(edge2);
+    // |e1 x e2|^2 = |e1|^2 * |e2|^2 * sin^2(angle), so this compares the angle between the edges
+    if normal.magnitude2() <= f32::EPSILON * edge1.magnitude2() * edge2.magnitude2() || !normal.is_finite() {
+        return (Vector3::unit_x(), Vector3::unit_y());
+    }
+
+    let tangent = edge1.normalize();
+    let bitangent = normal.normalize().cross(tangent);
+    (tangent, bitangent)
+}
+
+/// Computes the axis-aligned bounding box of the positions in the given vertex data as `(min, max)`.
+/// Returns `None` if there are no vertices.
+pub fn bounding_box(vertices: &[f32], layout: &VertexLayout) -> Option<(Vector3<f32>, Vector3<f32>)> {
+    let position_offset = layout.position_offset();
+    vertices.chunks_exact(layout.floats_per_vertex())
+        .map(|vertex| Vector3::new(vertex[position_offset], vertex[position_offset + 1], vertex[position_offset + 2]))
+        .fold(None, |bounds, p| match bounds {
+            None => Some((p, p)),
+            Some((min, max)) => Some((
+                Vector3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                Vector3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+            )),
+        })
+}
+
+/// Replaces the normals of every triangle with its face normal, so the triangles are shaded flat instead of smooth.
+/// Every three consecutive vertices are treated as one triangle. The face normal is oriented to the same side
+/// as the previous (interpolated) normals of the triangle, so it does not depend on the winding order.
+/// Triangles without area keep their normals. Layouts without normals are left unchanged.
+pub fn flatten_normals(vertices: &mut [f32], layout: &VertexLayout) {
+    let Some(normal_offset) = layout.offset(VertexAttribute::Normal) else {
+        return;
+    };
+    let position_offset = layout.position_offset();
+    let floats_per_vertex = layout.floats_per_vertex();
+
+    for triangle in vertices.chunks_exact_mut(3 * floats_per_vertex) {
+        let position = |idx: usize| {
+            let offset = idx * floats_per_vertex + position_offset;
+            Vector3::new(triangle[offset], triangle[offset + 1], triangle[offset + 2])
+        };
+        let normal = |idx: usize| {
+            let offset = idx * floats_per_vertex + normal_offset;
+            Vector3::new(triangle[offset], triangle[offset + 1], triangle[offset + 2])
+        };
+
+        let mut face_normal = (position(1) - position(0)).cross(position(2) - position(0));
+        if face_normal.magnitude2() <= f32::EPSILON * f32::EPSILON || !face_normal.is_finite() {
+            continue;
+        }
+        if face_normal.dot(normal(0) + normal(1) + normal(2)) < 0.0 {
+            face_normal = -face_normal;
+        }
+        let face_normal = face_normal.normalize();
+
+        for idx in 0..3 {
+            let offset = idx * floats_per_vertex + normal_offset;
+            triangle[offset..offset + 3].copy_from_slice(&[face_normal.x, face_normal.y, face_normal.z]);
+        }
+    }
+}
+
+/// Sets the color (`r, g, b`) of every vertex in the given vertex data.
+/// The red and green channels follow the texture coordinates as a gradient, while the blue channel alternates
+/// between the cells of a `cells_x` by `cells_y` grid like a checkerboard.
+/// All vertices of a triangle get the color of the cell containing the center of the triangle.
+/// Layouts without texture coordinates color all vertices like the first cell. Layouts without colors are left unchanged.
+pub fn color_cells(vertices: &mut [f32], layout: &VertexLayout, cells_x: u32, cells_y: u32) {
+    let Some(color_offset) = layout.offset(VertexAttribute::Color) else {
+        return;
+    };
+    let tex_coords_offset = layout.offset(VertexAttribute::TexCoords);
+    let floats_per_vertex = layout.floats_per_vertex();
+
+    for triangle in vertices.chunks_exact_mut(3 * floats_per_vertex) {
+        let tex_coords = |idx: usize| match tex_coords_offset {
+            Some(tex_coords_offset) => {
+                let offset = idx * floats_per_vertex + tex_coords_offset;
+                Vector2::new(triangle[offset], triangle[offset + 1])
+            }
+            None => Vector2::zero(),
+        };
+        let center = (tex_coords(0) + tex_coords(1) + tex_coords(2)) / 3.0;
+        let cell_x = (center.x * cells_x as f32).floor() as i64;
+        let cell_y = (center.y * cells_y as f32).floor() as i64;
+        let checker = if (cell_x + cell_y) % 2 == 0 { 0.2 } else { 0.8 };
+
+        for idx in 0..3 {
+            let offset = idx * floats_per_vertex + color_offset;
+            triangle[offset..offset + 3].copy_from_slice(&[center.x, center.y, checker]);
+        }
+    }
+}
+
+/// Writes the given vertex data as Wavefront OBJ-file with positions, normals and texture coordinates.
+/// Normals and texture coordinates are only written if the layout contains them.
+/// Every three consecutive vertices are exported as one triangle.
+pub fn write_obj(path: &Path, vertices: &[f32], layout: &VertexLayout) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = BufWriter::new(File::create(path)?);
+
+    let position_offset = layout.position_offset();
+    let normal_offset = layout.offset(VertexAttribute::Normal);
+    let tex_coords_offset = layout.offset(VertexAttribute::TexCoords);
+    let floats_per_vertex = layout.floats_per_vertex();
+
+    writeln!(out, "# Generated by cg-demo")?;
+    for vertex in vertices.chunks_exact(floats_per_vertex) {
+        let p = &vertex[position_offset..position_offset + 3];
+        writeln!(out, "v {} {} {}", p[0], p[1], p[2])?;
+        if let Some(offset) = normal_offset {
+            let n = &vertex[offset..offset + 3];
+            writeln!(out, "vn {} {} {}", n[0], n[1], n[2])?;
+        }
+        if let Some(offset) = tex_coords_offset {
+            let uv = &vertex[offset..offset + 2];
+            writeln!(out, "vt {} {}", uv[0], uv[1])?;
+        }
+    }
+
+    // OBJ-indices start at 1; the references to normals and texture coordinates use the same index as the position
+    let face_vertex = |idx: usize| match (tex_coords_offset, normal_offset) {
+        (Some(_), Some(_)) => format!("{0}/{0}/{0}", idx),
+        (Some(_), None) => format!("{0}/{0}", idx),
+        (None, Some(_)) => format!("{0}//{0}", idx),
+        (None, None) => idx.to_string(),
+    };
+    let vertex_count = vertices.len() / floats_per_vertex;
+    for first in (1..=vertex_count).step_by(3) {
+        writeln!(out, "f {} {} {}", face_vertex(first), face_vertex(first + 1), face_vertex(first + 2))?;
+    }
+
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{vec2, vec3};
+
+    use super::*;
+
+    /// Checks that the vectors are equal within a small tolerance
+    fn assert_close(actual: Vector3<f32>, expected: Vector3<f32>) {
+        assert!((actual - expected).magnitude() < 1e-5, "expected {:?}, got {:?}", expected, actual);
+    }
+
+    #[test]
+    fn tangent_follows_the_texture_coordinates() {
+        let p = [vec3(0.0, 0.0, 0.0), vec3(2.0, 0.0, 0.0), vec3(0.0, 4.0, 0.0)];
+
+        let (tangent, bitangent) = compute_tangent_bitangent(p, [vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(0.0, 1.0)]);
+        assert_close(tangent, vec3(2.0, 0.0, 0.0));
+        assert_close(bitangent, vec3(0.0, 4.0, 0.0));
+
+        // Swapped texture coordinates swap the tangent and the bitangent
+        let (tangent, bitangent) = compute_tangent_bitangent(p, [vec2(0.0, 0.0), vec2(0.0, 1.0), vec2(1.0, 0.0)]);
+        assert_close(tangent, vec3(0.0, 4.0, 0.0));
+        assert_close(bitangent, vec3(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn tangent_of_a_sheared_mapping() {
+        // u runs diagonally across the triangle: u = x + y, v = y
+        let p = [vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)];
+        let uv = [vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(1.0, 1.0)];
+
+        let (tangent, bitangent) = compute_tangent_bitangent(p, uv);
+        assert_close(tangent, vec3(1.0, 0.0, 0.0));
+        assert_close(bitangent, vec3(-1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn degenerate_texture_coordinates_use_the_geometry() {
+        let p = [vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)];
+        for uv in [
+            [vec2(0.0, 0.0), vec2(0.0, 0.0), vec2(0.0, 0.0)],
+            [vec2(0.0, 0.0), vec2(0.5, 0.5), vec2(1.0, 1.0)],
+        ] {
+            let (tangent, bitangent) = compute_tangent_bitangent(p, uv);
+            assert_close(tangent, vec3(1.0, 0.0, 0.0));
+            assert_close(bitangent, vec3(0.0, 1.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn small_triangles_are_not_degenerate() {
+        // A cell of a 1024x1024 grid with collinear texture coordinates
+        let step = 1.0 / 1024.0;
+        let p = [vec3(0.0, 0.0, 0.0), vec3(0.0, step, 0.0), vec3(step, step, 0.0)];
+        let uv = [vec2(0.0, 0.0), vec2(0.0, 0.0), vec2(0.0, 0.0)];
+
+        let (tangent, bitangent) = compute_tangent_bitangent(p, uv);
+        assert_close(tangent, vec3(0.0, 1.0, 0.0));
+        assert_close(bitangent, vec3(1.0, 0.0, 0.0));
+        assert_close(tangent.cross(bitangent), vec3(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn triangles_without_area_fall_back_to_the_axes() {
+        let degenerate = [
+            [vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0)],
+            [vec3(0.0, 0.0, 0.0), vec3(1.0, 1.0, 0.0), vec3(2.0, 2.0, 0.0)],
+            [vec3(0.0, 0.0, 0.0), vec3(f32::NAN, 0.0, 0.0), vec3(0.0, 1.0, 0.0)],
+        ];
+        for p in degenerate {
+            let (tangent, bitangent) = compute_tangent_bitangent(p, [vec2(0.0, 0.0), vec2(0.0, 0.0), vec2(0.0, 0.0)]);
+            assert_eq!(tangent, Vector3::unit_x());
+            assert_eq!(bitangent, Vector3::unit_y());
+        }
+    }
+
+    #[test]
+    fn mirrored_texture_coordinates_flip_the_handedness() {
+        // Two triangles next to each other in the xy-plane; the texture is mirrored along u on the second one
+        let islands = [
+            ([vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)], [vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(0.0, 1.0)], 1.0),
+            ([vec3(1.0, 0.0, 0.0), vec3(2.0, 0.0, 0.0), vec3(1.0, 1.0, 0.0)], [vec2(1.0, 0.0), vec2(0.0, 0.0), vec2(1.0, 1.0)], -1.0),
+        ];
+        let normal = vec3(0.0, 0.0, 1.0);
+        let layout = VertexLayout::default();
+        let tangent_offset = layout.offset(VertexAttribute::Tangent).unwrap();
+
+        for (p, uv, expected) in islands {
+            let (tangent, bitangent) = compute_tangent_bitangent(p, uv);
+            assert_eq!(tangent_handedness(normal, tangent, bitangent), expected);
+
+            let mut buffer = Vec::new();
+            for idx in 0..3 {
+                layout.push_vertex(&mut buffer, p[idx], normal, uv[idx], tangent, bitangent);
+            }
+            for vertex in buffer.chunks_exact(layout.floats_per_vertex()) {
+                let stored = &vertex[tangent_offset..tangent_offset + 4];
+                assert_close(vec3(stored[0], stored[1], stored[2]), tangent);
+                assert_eq!(stored[3], expected);
+                // Flipped by the handedness, `normal x tangent` points to the side of the bitangent on both islands
+                assert!((stored[3] * normal.cross(tangent)).dot(bitangent) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn colors_are_written_into_the_interleaved_vertices() {
+        let layout = VertexLayout::new(vec![VertexAttribute::Position, VertexAttribute::Color, VertexAttribute::TexCoords]).unwrap();
+        let mut vertices = Vec::new();
+        for uv in [vec2(0.6, 0.1), vec2(0.9, 0.1), vec2(0.9, 0.4)] {
+            layout.push_vertex(&mut vertices, uv.extend(0.0), Vector3::unit_z(), uv, Vector3::unit_x(), Vector3::unit_y());
+        }
+        assert_eq!(&vertices[3..6], &[1.0, 1.0, 1.0]);
+
+        let untouched = vertices.clone();
+        color_cells(&mut vertices, &layout, 2, 2);
+        for (idx, vertex) in vertices.chunks_exact(8).enumerate() {
+            // The center (0.8, 0.2) lies in the cell (1, 0)
+            assert_close(vec3(vertex[3], vertex[4], vertex[5]), vec3(0.8, 0.2, 0.8));
+            assert_eq!(vertex[..3], untouched[idx * 8..idx * 8 + 3]);
+            assert_eq!(vertex[6..], untouched[idx * 8 + 6..idx * 8 + 8]);
+        }
+    }
+}
@@ -1,11 +1,16 @@
+use std::cell::RefCell;
 use std::ffi;
 use std::fs;
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use zip::ZipArchive;
 
 #[derive(Debug)]
 pub enum Error {
     IO(io::Error),
+    Zip(zip::result::ZipError),
     FileContainsNil,
     FailedToGetExePath,
 }
@@ -16,29 +21,41 @@ impl From<io::Error> for Error {
     }
 }
 
+impl From<zip::result::ZipError> for Error {
+    fn from(other: zip::result::ZipError) -> Self {
+        Error::Zip(other)
+    }
+}
+
+/// Resources are read from a folder and, optionally, from a zip archive.
+/// Files in the folder take precedence over the archive, and all written files are stored in the folder.
 pub struct Resources {
     root_path: PathBuf,
+    archive: Option<RefCell<ZipArchive<fs::File>>>,
 }
 
 impl Resources {
     pub fn from_relative_exe_path(rel_path: &Path) -> Result<Resources, Error> {
-        let exe_file_name = std::env::current_exe()
-            .map_err(|_| Error::FailedToGetExePath)?;
-        let exe_path = exe_file_name.parent()
-            .ok_or(Error::FailedToGetExePath)?;
         Ok(Resources {
-            root_path: exe_path.join(rel_path)
+            root_path: exe_dir()?.join(rel_path),
+            archive: None,
+        })
+    }
+
+    /// Creates resources that are read from the given zip archive (relative to the executable).
+    /// Files written to the resources are stored in the folder `rel_path` next to the executable.
+    pub fn from_relative_exe_archive(archive_rel_path: &Path, rel_path: &Path) -> Result<Resources, Error> {
+        let exe_path = exe_dir()?;
+        let archive = ZipArchive::new(fs::File::open(exe_path.join(archive_rel_path))?)?;
+        Ok(Resources {
+            root_path: exe_path.join(rel_path),
+            archive: Some(RefCell::new(archive)),
         })
     }
 
     pub fn load_cstring(&self, resouce_name: &str) -> Result<ffi::CString, Error> {
-        let mut file = fs::File::open(
-            resource_name_to_path(&self.root_path, resouce_name)
-        )?;
-        let mut buffer: Vec<u8> = Vec::with_capacity(
-            file.metadata()?.len() as usize + 1
-        );
-        file.read_to_end(&mut buffer)?;
+        let mut buffer = self.load_bytes(resouce_name)?;
+        buffer.reserve_exact(1);
 
         // check no nul byte was read
         if buffer.iter().find(|i| **i == 0).is_some() {
@@ -48,11 +65,77 @@ impl Resources {
         Ok(unsafe { ffi::CString::from_vec_unchecked(buffer) })
     }
 
+    pub fn load_bytes(&self, resource_name: &str) -> Result<Vec<u8>, Error> {
+        let path = resource_name_to_path(&self.root_path, resource_name);
+        match &self.archive {
+            Some(archive) if !path.is_file() => {
+                let mut archive = archive.borrow_mut();
+                let mut entry = archive.by_name(resource_name)?;
+                let mut buffer = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut buffer)?;
+                Ok(buffer)
+            }
+            _ => Ok(fs::read(path)?),
+        }
+    }
+
+    pub fn write_bytes(&self, resource_name: &str, data: &[u8]) -> Result<(), Error> {
+        let path = resource_name_to_path(&self.root_path, resource_name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write(path, data)?)
+    }
+
+    /// Determines when the resource in the folder was last modified.
+    /// Resources that are only contained in the archive can not change and return an error.
+    pub fn modified_time(&self, resource_name: &str) -> Result<SystemTime, Error> {
+        let path = resource_name_to_path(&self.root_path, resource_name);
+        Ok(fs::metadata(path)?.modified()?)
+    }
+
+    /// Lists the names of all files in the given resource folder (sorted alphabetically)
+    pub fn list_dir(&self, resource_name: &str) -> Result<Vec<String>, Error> {
+        let mut names = Vec::new();
+        let path = resource_name_to_path(&self.root_path, resource_name);
+        if self.archive.is_none() || path.is_dir() {
+            for entry in fs::read_dir(path)? {
+                let entry = entry?;
+                if entry.file_type()?.is_file() {
+                    names.push(entry.file_name().to_string_lossy().into_owned());
+                }
+            }
+        }
+
+        if let Some(archive) = &self.archive {
+            let prefix = format!("{}/", resource_name.trim_end_matches('/'));
+            names.extend(archive.borrow().file_names()
+                .filter_map(|entry| entry.strip_prefix(&prefix))
+                .filter(|name| !name.is_empty() && !name.contains('/'))
+                .map(String::from));
+        }
+
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    /// Constructs the path of the resource in the folder.
+    /// Resources that are only contained in the archive can not be accessed with this path; use [`Resources::load_bytes`] instead.
     pub fn construct_path(&self, resource_name: &str) -> Result<PathBuf, Error> {
         Ok(resource_name_to_path(&self.root_path, resource_name))
     }
 }
 
+/// Determines the folder containing the executable
+pub fn exe_dir() -> Result<PathBuf, Error> {
+    let exe_file_name = std::env::current_exe()
+        .map_err(|_| Error::FailedToGetExePath)?;
+    exe_file_name.parent()
+        .map(Path::to_path_buf)
+        .ok_or(Error::FailedToGetExePath)
+}
+
 ///
 /// Converts the given platform-independent path (separated with '/') to the platform-specific path
 fn resource_name_to_path(root_dir: &Path, location: &str) -> PathBuf {
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use cgmath::{InnerSpace, Vector3};
+
+/// Distance of the sun to the center of the mesh, which is large enough for the light to be nearly parallel
+const SUN_DISTANCE: f32 = 100.0;
+/// Tilt of the sun path towards the positive y-axis, so the sun does not pass exactly through the zenith
+const SUN_PATH_TILT: f32 = 0.3;
+/// Color of the sun (sRGB) at the horizon and at noon
+const HORIZON_COLOR: [f32; 3] = [1.0, 0.55, 0.25];
+const NOON_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+/// Color of the light (sRGB) while the sun is below the horizon
+const NIGHT_COLOR: [f32; 3] = [0.05, 0.07, 0.12];
+
+/// # SunCycle
+/// Animated light that moves like the sun across the sky above the mesh.
+/// The time of day is accumulated from the frame times and wraps around after a full cycle (day and night).
+/// The mesh lies in the xy-plane with its normal pointing to the positive z-axis, which is the zenith of the sun.
+#[derive(Debug)]
+pub struct SunCycle {
+    /// Time for a full day
+    cycle_time: Duration,
+    /// Current time of day as fraction of the cycle; `0.0` is sunrise, `0.25` noon, `0.5` sunset
+    time_of_day: f32,
+}
+
+impl SunCycle {
+    /// # Constructor
+    /// Starts a new cycle with the given time for a full day at sunrise
+    pub fn new(cycle_time: Duration) -> SunCycle {
+        SunCycle {
+            cycle_time,
+            time_of_day: 0.0,
+        }
+    }
+
+    /// Advances the time of day by the given time
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.scrub(elapsed.as_secs_f32() / self.cycle_time.as_secs_f32());
+    }
+
+    /// Moves the time of day by the given fraction of the cycle (negative values go back in time)
+    pub fn scrub(&mut self, amount: f32) {
+        self.time_of_day = (self.time_of_day + amount).rem_euclid(1.0);
+    }
+
+    /// Get the current time of day as fraction of the cycle
+    pub fn time_of_day(&self) -> f32 {
+        self.time_of_day
+    }
+
+    /// Direction from the center of the mesh to the sun (normalized)
+    pub fn direction(&self) -> Vector3<f32> {
+        let angle = self.time_of_day * std::f32::consts::TAU;
+        cgmath::vec3(angle.cos(), SUN_PATH_TILT, angle.sin()).normalize()
+    }
+
+    /// Position of the sun far away from the mesh, so it can be used like the position of a point light
+    pub fn position(&self) -> Vector3<f32> {
+        self.direction() * SUN_DISTANCE
+    }
+
+    /// Color of the sunlight in sRGB. It is warm close to the horizon, white at noon and dark at night.
+    pub fn color(&self) -> [f32; 3] {
+        let elevation = (self.time_of_day * std::f32::consts::TAU).sin();
+        if elevation <= 0.0 {
+            // Fade to the night color shortly after sunset
+            let t = (-elevation * 4.0).min(1.0);
+            return mix(HORIZON_COLOR, NIGHT_COLOR, t);
+        }
+        mix(HORIZON_COLOR, NOON_COLOR, elevation.sqrt())
+    }
+}
+
+/// Linear interpolation between two colors
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [0, 1, 2].map(|idx| a[idx] + (b[idx] - a[idx]) * t)
+}